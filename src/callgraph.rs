@@ -0,0 +1,340 @@
+use crate::code::{action_lists, code_bodies, CodeLocation};
+use crate::game::{Game, ResourceId, Script, ScriptId};
+use crate::gml::ast::{assigned_names, var_names, walk_exprs, ExprKind};
+use crate::gml::{self, builtins, Span};
+use crate::library::{ArgumentKind, ArgumentValue, ACTIONS};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Callee {
+    Script(ScriptId),
+    // A function defined by an extension package, by index into `Game::extensions` and the
+    // extension's files.
+    Extension { extension: usize, file: usize },
+    // A runner function, including the ones drag-and-drop actions call.
+    Builtin,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub location: CodeLocation,
+    pub function: String,
+    pub callee: Callee,
+    // Where the call is in the code at `location`. Actions that run a script don't have one.
+    pub span: Option<Span>,
+    // The script is named as a value rather than called, e.g. in `script_execute(scr_move)`.
+    pub indirect: bool,
+}
+
+pub struct CallGraph {
+    pub calls: Vec<Call>,
+}
+
+// Functions that take a script as an argument.
+const SCRIPT_FUNCTIONS: &[&str] = &[
+    "script_execute",
+    "script_exists",
+    "script_get_name",
+    "script_get_text",
+];
+
+struct Functions<'a> {
+    scripts: HashMap<&'a str, ScriptId>,
+    extensions: HashMap<&'a str, (usize, usize)>,
+    actions: HashSet<&'static str>,
+}
+
+impl Functions<'_> {
+    fn callee(&self, name: &str) -> Callee {
+        if let Some(&id) = self.scripts.get(name) {
+            Callee::Script(id)
+        } else if let Some(&(extension, file)) = self.extensions.get(name) {
+            Callee::Extension { extension, file }
+        } else if builtins::is_function(name) || self.actions.contains(name) {
+            Callee::Builtin
+        } else {
+            Callee::Unknown
+        }
+    }
+}
+
+impl CallGraph {
+    pub fn new(game: &Game) -> Self {
+        let functions = Functions {
+            scripts: game
+                .scripts
+                .iter()
+                .map(|s| (s.name.as_str(), s.id))
+                .collect(),
+            extensions: game
+                .extensions
+                .iter()
+                .enumerate()
+                .flat_map(|(i, e)| e.files.iter().enumerate().map(move |(j, f)| (i, j, f)))
+                .flat_map(|(i, j, f)| f.functions.iter().map(move |f| (f.name.as_str(), (i, j))))
+                .collect(),
+            actions: ACTIONS
+                .iter()
+                .map(|a| a.function)
+                .filter(|f| !f.is_empty())
+                .collect(),
+        };
+
+        let mut calls = vec![];
+        for body in code_bodies(game) {
+            let (program, _) = gml::parse(body.code);
+            // A local or instance variable with a script's name hides the script.
+            let mut variables = var_names(&program.statements);
+            variables.extend(assigned_names(&program.statements));
+            let mut script_arguments = HashSet::new();
+            walk_exprs(&program.statements, &mut |expr| match &expr.kind {
+                ExprKind::Call(function, args) => {
+                    if SCRIPT_FUNCTIONS.contains(&function.name.as_str()) {
+                        script_arguments.extend(args.iter().map(|a| a.span));
+                    }
+                    calls.push(Call {
+                        location: body.location,
+                        function: function.name.clone(),
+                        callee: functions.callee(&function.name),
+                        span: Some(function.span),
+                        indirect: false,
+                    })
+                }
+                ExprKind::Identifier(name) => {
+                    let id = match functions.scripts.get(name.as_str()) {
+                        Some(&id) => id,
+                        None => return,
+                    };
+                    if script_arguments.contains(&expr.span) || !variables.contains(name.as_str()) {
+                        calls.push(Call {
+                            location: body.location,
+                            function: name.clone(),
+                            callee: Callee::Script(id),
+                            span: Some(expr.span),
+                            indirect: true,
+                        });
+                    }
+                }
+                _ => (),
+            });
+        }
+
+        // "Execute script" and other actions with a script argument.
+        for (owner, actions) in action_lists(game) {
+            for (i, action) in actions.iter().enumerate() {
+                for (j, argument) in action.typed_arguments().into_iter().enumerate() {
+                    let id = match argument {
+                        ArgumentValue::Resource(ArgumentKind::Script, id) => id,
+                        _ => continue,
+                    };
+                    if let Some(script) = game.script(ScriptId::from_raw(id)) {
+                        calls.push(Call {
+                            location: CodeLocation::Action {
+                                owner,
+                                action: i,
+                                argument: j,
+                            },
+                            function: script.name.clone(),
+                            callee: Callee::Script(script.id),
+                            span: None,
+                            indirect: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        CallGraph { calls }
+    }
+
+    pub fn calls_from(&self, location: CodeLocation) -> impl Iterator<Item = &Call> {
+        self.calls.iter().filter(move |c| c.location == location)
+    }
+
+    pub fn callers(&self, script: ScriptId) -> impl Iterator<Item = &Call> {
+        self.calls
+            .iter()
+            .filter(move |c| c.callee == Callee::Script(script))
+    }
+
+    // Calls to functions that aren't scripts, extension functions or built in. Game Maker
+    // reports these as "Unknown function or script" when it compiles the code.
+    pub fn unknown_calls(&self) -> impl Iterator<Item = &Call> {
+        self.calls.iter().filter(|c| c.callee == Callee::Unknown)
+    }
+
+    // Scripts that nothing calls or names, apart from the script itself.
+    pub fn uncalled_scripts<'a>(&self, game: &'a Game) -> Vec<&'a Script> {
+        game.scripts
+            .iter()
+            .filter(|script| {
+                self.callers(script.id)
+                    .all(|c| c.location == CodeLocation::Script(script.id))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Extension, ExtensionFile, ExtensionFunction, Trigger};
+
+    fn game(scripts: &[(&str, &str)]) -> Game {
+        let mut game = Game::default();
+        for (i, &(name, code)) in scripts.iter().enumerate() {
+            game.scripts.push(Script {
+                id: ScriptId(i as u32),
+                name: name.to_string(),
+                script: code.to_string(),
+            });
+        }
+        game
+    }
+
+    fn callees(graph: &CallGraph, game: &Game, name: &str) -> Vec<(String, Callee, bool)> {
+        let script = game.scripts.iter().find(|s| s.name == name).unwrap();
+        graph
+            .calls_from(CodeLocation::Script(script.id))
+            .map(|c| (c.function.clone(), c.callee, c.indirect))
+            .collect()
+    }
+
+    fn uncalled(graph: &CallGraph, game: &Game) -> Vec<String> {
+        graph
+            .uncalled_scripts(game)
+            .iter()
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn scripts_are_called_or_named_as_values() {
+        let game = game(&[
+            ("scr_a", ""),
+            ("scr_b", ""),
+            ("scr_c", ""),
+            ("scr_main", "scr_a(1); f = scr_b; script_execute(scr_c)"),
+        ]);
+        let graph = CallGraph::new(&game);
+        let script = |i| Callee::Script(ScriptId(i));
+        assert_eq!(
+            callees(&graph, &game, "scr_main"),
+            vec![
+                ("scr_a".to_string(), script(0), false),
+                ("scr_b".to_string(), script(1), true),
+                ("script_execute".to_string(), Callee::Builtin, false),
+                ("scr_c".to_string(), script(2), true),
+            ]
+        );
+        assert_eq!(uncalled(&graph, &game), vec!["scr_main"]);
+    }
+
+    #[test]
+    fn variables_named_like_scripts_are_not_calls() {
+        let game = game(&[
+            ("scr_local", ""),
+            ("scr_instance", ""),
+            ("scr_field", ""),
+            ("scr_run", ""),
+            (
+                "scr_main",
+                "var scr_local; scr_local = 1; x = scr_local;\n\
+                 scr_instance[0] = 2; y = scr_instance;\n\
+                 other.scr_field = 3; z = other.scr_field;\n\
+                 var scr_run; script_execute(scr_run)",
+            ),
+        ]);
+        let graph = CallGraph::new(&game);
+        assert_eq!(
+            callees(&graph, &game, "scr_main"),
+            vec![
+                ("script_execute".to_string(), Callee::Builtin, false),
+                ("scr_run".to_string(), Callee::Script(ScriptId(3)), true),
+            ]
+        );
+        assert_eq!(
+            uncalled(&graph, &game),
+            vec!["scr_local", "scr_instance", "scr_field", "scr_main"]
+        );
+    }
+
+    #[test]
+    fn extension_and_unknown_functions() {
+        let mut game = game(&[(
+            "scr_main",
+            "ext_draw(); ext_init(); show_message(''); nope()",
+        )]);
+        let function = |name: &str| ExtensionFunction {
+            name: name.to_string(),
+            ..ExtensionFunction::default()
+        };
+        game.extensions.push(Extension {
+            name: "Graphics".to_string(),
+            files: vec![
+                ExtensionFile {
+                    functions: vec![function("ext_init")],
+                    ..ExtensionFile::default()
+                },
+                ExtensionFile {
+                    functions: vec![function("ext_draw")],
+                    ..ExtensionFile::default()
+                },
+            ],
+            ..Extension::default()
+        });
+        let graph = CallGraph::new(&game);
+        assert_eq!(
+            callees(&graph, &game, "scr_main"),
+            vec![
+                (
+                    "ext_draw".to_string(),
+                    Callee::Extension {
+                        extension: 0,
+                        file: 1
+                    },
+                    false
+                ),
+                (
+                    "ext_init".to_string(),
+                    Callee::Extension {
+                        extension: 0,
+                        file: 0
+                    },
+                    false
+                ),
+                ("show_message".to_string(), Callee::Builtin, false),
+                ("nope".to_string(), Callee::Unknown, false),
+            ]
+        );
+        let unknown: Vec<_> = graph.unknown_calls().map(|c| &c.function[..]).collect();
+        assert_eq!(unknown, vec!["nope"]);
+    }
+
+    #[test]
+    fn triggers_and_library_code_call_scripts() {
+        let mut game = game(&[
+            ("scr_check", ""),
+            ("scr_setup", ""),
+            ("scr_self", "scr_self()"),
+        ]);
+        game.triggers.push(Trigger {
+            id: 4,
+            condition: "return scr_check()".to_string(),
+            ..Trigger::default()
+        });
+        game.library_init_scripts.push("scr_setup()".to_string());
+        let graph = CallGraph::new(&game);
+        let locations: Vec<_> = graph.calls.iter().map(|c| c.location).collect();
+        assert_eq!(
+            locations,
+            vec![
+                CodeLocation::Script(ScriptId(2)),
+                CodeLocation::Trigger(4),
+                CodeLocation::LibraryInit(0),
+            ]
+        );
+        assert_eq!(uncalled(&graph, &game), vec!["scr_self"]);
+    }
+}
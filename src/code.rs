@@ -0,0 +1,234 @@
+use crate::event::Event;
+use crate::game::{Action, Game, ObjectId, RoomId, ScriptId, TimelineId};
+use crate::library::{ActionKind, ArgumentValue};
+
+// Where a list of actions lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionOwner {
+    Object(ObjectId, Event),
+    Timeline(TimelineId, u32),
+}
+
+// Where a piece of GML lives in the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeLocation {
+    Script(ScriptId),
+    // The code of an "Execute code" action, or an expression argument of any other action.
+    Action {
+        owner: ActionOwner,
+        action: usize,
+        argument: usize,
+    },
+    RoomCreation(RoomId),
+    InstanceCreation(RoomId, i32),
+    Trigger(u32),
+    LibraryInit(usize),
+}
+
+impl ActionOwner {
+    pub fn display_name(&self, game: &Game) -> String {
+        match *self {
+            ActionOwner::Object(id, event) => {
+                let object = game.object(id).map_or("<undefined>", |o| o.name.as_str());
+                format!("object {}, {}", object, event.display_name(game))
+            }
+            ActionOwner::Timeline(id, position) => {
+                let timeline = game.timeline(id).map_or("<undefined>", |t| t.name.as_str());
+                format!("timeline {}, moment {}", timeline, position)
+            }
+        }
+    }
+
+    // Like `display_name`, as a path, e.g. "objects/obj_player/Step (Normal)".
+    pub fn path(&self, game: &Game) -> String {
+        match *self {
+            ActionOwner::Object(id, event) => {
+                let object = game.object(id).map_or("<undefined>", |o| o.name.as_str());
+                format!("objects/{}/{}", object, event.display_name(game))
+            }
+            ActionOwner::Timeline(id, position) => {
+                let timeline = game.timeline(id).map_or("<undefined>", |t| t.name.as_str());
+                format!("timelines/{}/moment {}", timeline, position)
+            }
+        }
+    }
+}
+
+impl CodeLocation {
+    // Describes the location with resource names from `game`, e.g. "object obj_player, Step
+    // (Normal), action 2".
+    pub fn display_name(&self, game: &Game) -> String {
+        let room_name = |id| game.room(id).map_or("<undefined>", |r| r.name.as_str());
+        match *self {
+            CodeLocation::Script(id) => {
+                let script = game.script(id).map_or("<undefined>", |s| s.name.as_str());
+                format!("script {}", script)
+            }
+            CodeLocation::Action {
+                owner,
+                action,
+                argument,
+            } => {
+                let code_action = action_list(game, owner)
+                    .and_then(|actions| actions.get(action))
+                    .is_some_and(|a| a.kind() == ActionKind::Code);
+                if code_action {
+                    format!("{}, action {}", owner.display_name(game), action)
+                } else {
+                    format!(
+                        "{}, action {} argument {}",
+                        owner.display_name(game),
+                        action,
+                        argument
+                    )
+                }
+            }
+            CodeLocation::RoomCreation(id) => format!("room {} creation code", room_name(id)),
+            CodeLocation::InstanceCreation(id, instance) => {
+                format!(
+                    "room {}, instance {} creation code",
+                    room_name(id),
+                    instance
+                )
+            }
+            CodeLocation::Trigger(id) => match game.trigger(id) {
+                Some(trigger) => format!("trigger {}", trigger.name),
+                None => format!("trigger {}", id),
+            },
+            CodeLocation::LibraryInit(i) => format!("library initialization script {}", i),
+        }
+    }
+
+    // Like `display_name`, as a path, e.g. "objects/obj_player/Step (Normal)/action 2".
+    pub fn path(&self, game: &Game) -> String {
+        let room_name = |id| game.room(id).map_or("<undefined>", |r| r.name.as_str());
+        match *self {
+            CodeLocation::Script(id) => {
+                let script = game.script(id).map_or("<undefined>", |s| s.name.as_str());
+                format!("scripts/{}", script)
+            }
+            CodeLocation::Action {
+                owner,
+                action,
+                argument,
+            } => {
+                let code_action = action_list(game, owner)
+                    .and_then(|actions| actions.get(action))
+                    .is_some_and(|a| a.kind() == ActionKind::Code);
+                if code_action {
+                    format!("{}/action {}", owner.path(game), action)
+                } else {
+                    format!(
+                        "{}/action {}/argument {}",
+                        owner.path(game),
+                        action,
+                        argument
+                    )
+                }
+            }
+            CodeLocation::RoomCreation(id) => format!("rooms/{}/creation code", room_name(id)),
+            CodeLocation::InstanceCreation(id, instance) => format!(
+                "rooms/{}/instance {}/creation code",
+                room_name(id),
+                instance
+            ),
+            CodeLocation::Trigger(id) => match game.trigger(id) {
+                Some(trigger) => format!("triggers/{}/condition", trigger.name),
+                None => format!("triggers/{}/condition", id),
+            },
+            CodeLocation::LibraryInit(i) => format!("library initialization/script {}", i),
+        }
+    }
+}
+
+// Every action list in the game, in resource order.
+pub fn action_lists(game: &Game) -> Vec<(ActionOwner, &[Action])> {
+    let mut lists: Vec<(ActionOwner, &[Action])> = vec![];
+    for timeline in &game.timelines {
+        for moment in &timeline.moments {
+            let owner = ActionOwner::Timeline(timeline.id, moment.position);
+            lists.push((owner, &moment.actions));
+        }
+    }
+    for object in &game.objects {
+        for event in &object.events {
+            lists.push((
+                ActionOwner::Object(object.id, event.event()),
+                &event.actions,
+            ));
+        }
+    }
+    lists
+}
+
+pub(crate) fn action_list(game: &Game, owner: ActionOwner) -> Option<&[Action]> {
+    match owner {
+        ActionOwner::Object(id, event) => game
+            .object(id)?
+            .events
+            .iter()
+            .find(|e| e.event() == event)
+            .map(|e| e.actions.as_slice()),
+        ActionOwner::Timeline(id, position) => game
+            .timeline(id)?
+            .moments
+            .iter()
+            .find(|m| m.position == position)
+            .map(|m| m.actions.as_slice()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CodeBody<'a> {
+    pub location: CodeLocation,
+    pub code: &'a str,
+}
+
+// Every piece of GML in the game: scripts, code actions and expression arguments, creation code,
+// trigger conditions and library initialization code. Empty bodies are skipped.
+pub fn code_bodies<'a>(game: &'a Game) -> Vec<CodeBody<'a>> {
+    let mut bodies = vec![];
+    let mut add = |location, code: &'a str| {
+        if !code.trim().is_empty() {
+            bodies.push(CodeBody { location, code });
+        }
+    };
+
+    for script in &game.scripts {
+        add(CodeLocation::Script(script.id), &script.script);
+    }
+    for (owner, actions) in action_lists(game) {
+        for (i, action) in actions.iter().enumerate() {
+            let location = |argument| CodeLocation::Action {
+                owner,
+                action: i,
+                argument,
+            };
+            if action.kind() == ActionKind::Code {
+                if let Some(code) = action.arguments.first() {
+                    add(location(0), code);
+                }
+                continue;
+            }
+            for (j, argument) in action.typed_arguments().into_iter().enumerate() {
+                if let ArgumentValue::Expression(code) = argument {
+                    add(location(j), code);
+                }
+            }
+        }
+    }
+    for room in &game.rooms {
+        add(CodeLocation::RoomCreation(room.id), &room.creation_code);
+        for instance in &room.instances {
+            let location = CodeLocation::InstanceCreation(room.id, instance.id);
+            add(location, &instance.creation_code);
+        }
+    }
+    for trigger in &game.triggers {
+        add(CodeLocation::Trigger(trigger.id), &trigger.condition);
+    }
+    for (i, code) in game.library_init_scripts.iter().enumerate() {
+        add(CodeLocation::LibraryInit(i), code);
+    }
+    bodies
+}
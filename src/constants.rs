@@ -0,0 +1,372 @@
+use crate::game::{Constant, Game, Trigger};
+use crate::gml::ast::{BinaryOp, Expr, ExprKind, StmtKind, UnaryOp};
+use crate::gml::{self, builtins, ParseError, Span};
+use crate::reference::ResourceIndex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    Real(f64),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Real(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    Parse(ParseError),
+    // An identifier that isn't a constant, resource or built-in constant, like a variable.
+    Unknown(String),
+    // Something that can't appear in a constant expression, like a function call.
+    NotConstant(Span),
+    // E.g. `"a" - 1`. Game Maker reports these as "wrong type of arguments".
+    TypeMismatch(Span),
+    DivisionByZero(Span),
+    // Constants that refer to each other, in the order they refer to each other.
+    Cycle(Vec<String>),
+    // A constant that isn't part of a cycle, but refers to the named constant, which is.
+    DependsOnCycle(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Parse(error) => write!(f, "{}", error),
+            EvalError::Unknown(name) => write!(f, "unknown identifier `{}`", name),
+            EvalError::NotConstant(span) => {
+                write!(
+                    f,
+                    "not a constant expression at {}..{}",
+                    span.start, span.end
+                )
+            }
+            EvalError::TypeMismatch(span) => {
+                write!(f, "wrong type of arguments at {}..{}", span.start, span.end)
+            }
+            EvalError::DivisionByZero(span) => {
+                write!(f, "division by 0 at {}..{}", span.start, span.end)
+            }
+            EvalError::Cycle(names) => write!(
+                f,
+                "constants refer to each other: {} -> {}",
+                names.join(" -> "),
+                names[0]
+            ),
+            EvalError::DependsOnCycle(name) => write!(
+                f,
+                "refers to `{}`, which is part of a cycle of constants",
+                name
+            ),
+        }
+    }
+}
+
+fn truthy(n: f64) -> bool {
+    n > 0.5
+}
+
+fn boolean(b: bool) -> Value {
+    Value::Real(if b { 1.0 } else { 0.0 })
+}
+
+// Bitwise operators work on the values rounded to integers.
+fn integer(n: f64) -> i64 {
+    n.round() as i64
+}
+
+// Evaluates GM8 constant expressions: numbers, strings, operators, game and extension constants,
+// resource names, trigger constants and built-in constants like `c_red`. Constants are evaluated
+// once, on first use.
+pub struct Evaluator<'a> {
+    game: &'a Game,
+    index: ResourceIndex<'a>,
+    // Game constants first, so they win over extension constants with the same name.
+    definitions: HashMap<&'a str, &'a str>,
+    values: HashMap<&'a str, Result<Value, EvalError>>,
+    evaluating: Vec<&'a str>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        let mut definitions = HashMap::new();
+        for constant in all_constants(game) {
+            definitions
+                .entry(constant.name.as_str())
+                .or_insert(constant.value.as_str());
+        }
+        Evaluator {
+            game,
+            index: ResourceIndex::new(game),
+            definitions,
+            values: HashMap::new(),
+            evaluating: vec![],
+        }
+    }
+
+    pub fn evaluate(&mut self, source: &str) -> Result<Value, EvalError> {
+        let (expr, errors) = gml::parse_expression(source);
+        if let Some(error) = errors.into_iter().next() {
+            return Err(EvalError::Parse(error));
+        }
+        self.expr(&expr)
+    }
+
+    // The value of a game or extension constant, or `None` if there isn't one called `name`.
+    pub fn constant(&mut self, name: &str) -> Option<Result<Value, EvalError>> {
+        let (&name, &source) = self.definitions.get_key_value(name)?;
+        if let Some(value) = self.values.get(name) {
+            return Some(value.clone());
+        }
+        if let Some(i) = self.evaluating.iter().position(|&n| n == name) {
+            let cycle = self.evaluating[i..].iter().map(|n| n.to_string()).collect();
+            return Some(Err(EvalError::Cycle(cycle)));
+        }
+
+        self.evaluating.push(name);
+        let value = match self.evaluate(source) {
+            Err(EvalError::Cycle(cycle)) if !cycle.iter().any(|n| n == name) => {
+                Err(EvalError::DependsOnCycle(cycle[0].clone()))
+            }
+            value => value,
+        };
+        self.evaluating.pop();
+        self.values.insert(name, value.clone());
+        Some(value)
+    }
+
+    // Trigger conditions are code, so this only handles conditions that are a single `return` of
+    // a constant expression, like `return DEBUG`.
+    pub fn trigger_condition(&mut self, trigger: &Trigger) -> Result<Value, EvalError> {
+        let (program, errors) = gml::parse(&trigger.condition);
+        if let Some(error) = errors.into_iter().next() {
+            return Err(EvalError::Parse(error));
+        }
+        match program.statements.as_slice() {
+            [statement] => match &statement.kind {
+                StmtKind::Return(Some(expr)) | StmtKind::Expr(expr) => self.expr(expr),
+                _ => Err(EvalError::NotConstant(statement.span)),
+            },
+            _ => Err(EvalError::NotConstant(Span::new(
+                0,
+                trigger.condition.len(),
+            ))),
+        }
+    }
+
+    fn identifier(&mut self, name: &str) -> Result<Value, EvalError> {
+        if let Some(value) = self.constant(name) {
+            return value;
+        }
+        if let Some((_, id)) = self.index.resource_by_name(name) {
+            return Ok(Value::Real(id as f64));
+        }
+        let trigger = self
+            .game
+            .triggers
+            .iter()
+            .find(|t| !t.constant_name.is_empty() && t.constant_name == name);
+        if let Some(trigger) = trigger {
+            return Ok(Value::Real(trigger.id as f64));
+        }
+        match builtins::constant(name) {
+            Some(n) => Ok(Value::Real(n)),
+            None => Err(EvalError::Unknown(name.to_string())),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Value, EvalError> {
+        match &expr.kind {
+            ExprKind::Real(n) => Ok(Value::Real(*n)),
+            ExprKind::String(s) => Ok(Value::String(s.clone())),
+            ExprKind::Identifier(name) => self.identifier(name),
+            ExprKind::Parenthesized(inner) => self.expr(inner),
+            ExprKind::Unary(op, operand) => {
+                let n = match self.expr(operand)? {
+                    Value::Real(n) => n,
+                    Value::String(_) => return Err(EvalError::TypeMismatch(expr.span)),
+                };
+                Ok(match op {
+                    UnaryOp::Not => boolean(!truthy(n)),
+                    UnaryOp::Negate => Value::Real(-n),
+                    UnaryOp::Plus => Value::Real(n),
+                    UnaryOp::BitNot => Value::Real(!integer(n) as f64),
+                })
+            }
+            ExprKind::Binary(op, left, right) => {
+                let left = self.expr(left)?;
+                let right = self.expr(right)?;
+                binary(*op, left, right, expr.span)
+            }
+            ExprKind::Field(..) | ExprKind::Index(..) | ExprKind::Call(..) | ExprKind::Error => {
+                Err(EvalError::NotConstant(expr.span))
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: Value, right: Value, span: Span) -> Result<Value, EvalError> {
+    let (a, b) = match (left, right) {
+        (Value::Real(a), Value::Real(b)) => (a, b),
+        (Value::String(a), Value::String(b)) => {
+            return match op {
+                BinaryOp::Add => Ok(Value::String(a + &b)),
+                BinaryOp::Equal => Ok(boolean(a == b)),
+                BinaryOp::NotEqual => Ok(boolean(a != b)),
+                BinaryOp::Less => Ok(boolean(a < b)),
+                BinaryOp::LessEqual => Ok(boolean(a <= b)),
+                BinaryOp::Greater => Ok(boolean(a > b)),
+                BinaryOp::GreaterEqual => Ok(boolean(a >= b)),
+                _ => Err(EvalError::TypeMismatch(span)),
+            };
+        }
+        // A string is never equal to a number, but can't be ordered against one.
+        _ => {
+            return match op {
+                BinaryOp::Equal => Ok(boolean(false)),
+                BinaryOp::NotEqual => Ok(boolean(true)),
+                _ => Err(EvalError::TypeMismatch(span)),
+            };
+        }
+    };
+    let checked = |n: f64| {
+        if n == 0.0 {
+            Err(EvalError::DivisionByZero(span))
+        } else {
+            Ok(n)
+        }
+    };
+    Ok(match op {
+        BinaryOp::Add => Value::Real(a + b),
+        BinaryOp::Subtract => Value::Real(a - b),
+        BinaryOp::Multiply => Value::Real(a * b),
+        BinaryOp::Divide => Value::Real(a / checked(b)?),
+        BinaryOp::IntDivide => Value::Real((a / checked(b)?).trunc()),
+        BinaryOp::Modulo => Value::Real(a % checked(b)?),
+        BinaryOp::Equal => boolean(a == b),
+        BinaryOp::NotEqual => boolean(a != b),
+        BinaryOp::Less => boolean(a < b),
+        BinaryOp::LessEqual => boolean(a <= b),
+        BinaryOp::Greater => boolean(a > b),
+        BinaryOp::GreaterEqual => boolean(a >= b),
+        BinaryOp::And => boolean(truthy(a) && truthy(b)),
+        BinaryOp::Or => boolean(truthy(a) || truthy(b)),
+        BinaryOp::Xor => boolean(truthy(a) != truthy(b)),
+        BinaryOp::BitAnd => Value::Real((integer(a) & integer(b)) as f64),
+        BinaryOp::BitOr => Value::Real((integer(a) | integer(b)) as f64),
+        BinaryOp::BitXor => Value::Real((integer(a) ^ integer(b)) as f64),
+        BinaryOp::ShiftLeft => Value::Real(integer(a).wrapping_shl(integer(b) as u32) as f64),
+        BinaryOp::ShiftRight => Value::Real(integer(a).wrapping_shr(integer(b) as u32) as f64),
+    })
+}
+
+// Game constants followed by the constants of every extension file.
+fn all_constants(game: &Game) -> impl Iterator<Item = &Constant> {
+    let extension_constants = game
+        .extensions
+        .iter()
+        .flat_map(|e| &e.files)
+        .flat_map(|f| &f.constants);
+    game.constants.iter().chain(extension_constants)
+}
+
+// Every game and extension constant with its value. A constant defined twice gets the value of
+// the first definition.
+pub fn resolve_constants(game: &Game) -> Vec<(&Constant, Result<Value, EvalError>)> {
+    let mut evaluator = Evaluator::new(game);
+    all_constants(game)
+        .filter_map(|constant| Some((constant, evaluator.constant(&constant.name)?)))
+        .collect()
+}
+
+// Groups of constants whose values refer to each other, each listed once.
+pub fn constant_cycles(game: &Game) -> Vec<Vec<String>> {
+    let mut cycles: Vec<Vec<String>> = vec![];
+    for (constant, value) in resolve_constants(game) {
+        if let Err(EvalError::Cycle(cycle)) = value {
+            if !cycles.iter().any(|c| c.contains(&constant.name)) {
+                cycles.push(cycle);
+            }
+        }
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(constants: &[(&str, &str)]) -> Game {
+        let mut game = Game::default();
+        for &(name, value) in constants {
+            game.constants.push(Constant {
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+        game
+    }
+
+    #[test]
+    fn evaluates_constants() {
+        let game = game(&[("A", "B * 2"), ("B", "1 + 2"), ("S", "\"a\" + \"b\"")]);
+        let mut evaluator = Evaluator::new(&game);
+        assert_eq!(evaluator.constant("A"), Some(Ok(Value::Real(6.0))));
+        assert_eq!(
+            evaluator.constant("S"),
+            Some(Ok(Value::String("ab".to_string())))
+        );
+        assert_eq!(evaluator.constant("C"), None);
+        // Left to right within each group of logical and bitwise operators.
+        assert_eq!(evaluator.evaluate("1 | 2 & 0"), Ok(Value::Real(0.0)));
+        assert_eq!(evaluator.evaluate("1 || 0 && 0"), Ok(Value::Real(0.0)));
+        assert_eq!(
+            evaluator.evaluate("A / 0"),
+            Err(EvalError::DivisionByZero(Span::new(0, 5)))
+        );
+    }
+
+    #[test]
+    fn finds_cycles() {
+        let game = game(&[("A", "B"), ("B", "C + 1"), ("C", "B"), ("D", "D")]);
+        assert_eq!(
+            constant_cycles(&game),
+            vec![
+                vec!["B".to_string(), "C".to_string()],
+                vec!["D".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn constants_that_depend_on_a_cycle_are_not_in_it() {
+        let game = game(&[("A", "E"), ("E", "B"), ("B", "C"), ("C", "B")]);
+        let values: Vec<_> = resolve_constants(&game)
+            .into_iter()
+            .map(|(c, value)| (c.name.as_str(), value))
+            .collect();
+        let depends = Err(EvalError::DependsOnCycle("B".to_string()));
+        assert_eq!(values[0], ("A", depends.clone()));
+        assert_eq!(values[1], ("E", depends));
+        assert_eq!(
+            values[2],
+            (
+                "B",
+                Err(EvalError::Cycle(vec!["B".to_string(), "C".to_string()]))
+            )
+        );
+        assert_eq!(
+            constant_cycles(&game),
+            vec![vec!["B".to_string(), "C".to_string()]]
+        );
+    }
+}
@@ -7,7 +7,7 @@ use gmstream::{decode_string, GmStream};
 use std::io;
 use std::io::{Cursor, Read, Seek};
 
-use image::{ConvertBuffer, RgbaImage};
+use image::{buffer::ConvertBuffer, RgbaImage};
 
 type BufferStream = Cursor<Vec<u8>>;
 type BgraImage = image::ImageBuffer<image::Bgra<u8>, Vec<u8>>;
@@ -24,7 +24,7 @@ fn assert_eof<T: Read>(mut s: T) {
 fn read_image(data: &[u8]) -> io::Result<Image> {
     Ok(image::load_from_memory(&data)
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
-        .into_rgba()
+        .into_rgba8()
         .into())
 }
 
@@ -195,7 +195,7 @@ fn read_settings(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
     Ok(())
 }
 
-fn read_extensions(_game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
+fn read_extensions(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
     println!("Reading extensions...");
     let version = stream.next_u32()?;
     assert_eq!(version, 700);
@@ -257,6 +257,7 @@ fn read_extensions(_game: &mut Game, stream: &mut BufferStream) -> io::Result<()
         for file in &mut extension.files {
             file.data = decrypted.next_compressed()?.into_inner();
         }
+        game.extensions.push(extension);
     }
     Ok(())
 }
@@ -311,17 +312,17 @@ fn read_sounds(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut sound = Sound::default();
-        sound.id = i;
+        sound.id = SoundId(i);
         sound.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 600 || version == 800 {
-            sound.kind = stream.next_u32()?;
+            sound.kind = stream.next_u32()?.into();
             sound.filetype = stream.next_string()?;
             sound.filename = stream.next_string()?;
             if stream.next_bool()? {
                 sound.data = stream.next_blob()?;
             }
-            sound.effects = stream.next_u32()?;
+            sound.effects = SoundEffects(stream.next_u32()?);
             sound.volume = stream.next_f64()?;
             sound.pan = stream.next_f64()?;
             sound.preload = stream.next_bool()?;
@@ -345,7 +346,7 @@ fn read_sprites(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut sprite = Sprite::default();
-        sprite.id = i;
+        sprite.id = SpriteId(i);
         sprite.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 542 {
@@ -460,7 +461,7 @@ fn read_backgrounds(game: &mut Game, stream: &mut BufferStream) -> io::Result<()
         }
 
         let mut background = Background::default();
-        background.id = i;
+        background.id = BackgroundId(i);
         background.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 543 {
@@ -510,7 +511,7 @@ fn read_paths(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut path = Path::default();
-        path.id = i;
+        path.id = PathId(i);
         path.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 530 {
@@ -546,7 +547,7 @@ fn read_scripts(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut script = Script::default();
-        script.id = i;
+        script.id = ScriptId(i);
         script.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 400 {
@@ -577,7 +578,7 @@ fn read_fonts(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut font = Font::default();
-        font.id = i;
+        font.id = FontId(i);
         font.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version >= 540 {
@@ -632,7 +633,7 @@ fn read_timelines(game: &mut Game, stream: &mut BufferStream) -> io::Result<()>
         }
 
         let mut timeline = Timeline::default();
-        timeline.id = i;
+        timeline.id = TimelineId(i);
         timeline.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 500 {
@@ -664,17 +665,17 @@ fn read_objects(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut object = Object::default();
-        object.id = i;
+        object.id = ObjectId(i);
         object.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 430 {
-            object.sprite = stream.next_i32()?;
+            object.sprite = SpriteId::from_raw(stream.next_i32()?);
             object.solid = stream.next_bool()?;
             object.visible = stream.next_bool()?;
             object.depth = stream.next_i32()?;
             object.persistent = stream.next_bool()?;
-            object.parent = stream.next_i32()?;
-            object.mask = stream.next_i32()?;
+            object.parent = ObjectId::from_raw(stream.next_i32()?);
+            object.mask = SpriteId::from_raw(stream.next_i32()?);
 
             let num_events = stream.next_u32()? + 1;
             for event_type in 0..num_events {
@@ -712,7 +713,7 @@ fn read_rooms(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
         }
 
         let mut room = Room::default();
-        room.id = i;
+        room.id = RoomId(i);
         room.name = stream.next_string()?;
         let version = stream.next_u32()?;
         if version == 541 {
@@ -730,7 +731,7 @@ fn read_rooms(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
                 let mut background = RoomBackground::default();
                 background.visible = stream.next_bool()?;
                 background.foreground = stream.next_bool()?;
-                background.background = stream.next_i32()?;
+                background.background = BackgroundId::from_raw(stream.next_i32()?);
                 background.x = stream.next_i32()?;
                 background.y = stream.next_i32()?;
                 background.tile_h = stream.next_bool()?;
@@ -758,7 +759,7 @@ fn read_rooms(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
                 view.v_border = stream.next_u32()?;
                 view.h_speed = stream.next_i32()?;
                 view.v_speed = stream.next_i32()?;
-                view.target_object = stream.next_i32()?;
+                view.target_object = ObjectId::from_raw(stream.next_i32()?);
                 room.views.push(view);
             }
 
@@ -767,7 +768,7 @@ fn read_rooms(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
                 let mut instance = RoomInstance::default();
                 instance.x = stream.next_i32()?;
                 instance.y = stream.next_i32()?;
-                instance.object = stream.next_i32()?;
+                instance.object = ObjectId::from_raw(stream.next_i32()?);
                 instance.id = stream.next_i32()?;
                 instance.creation_code = stream.next_string()?;
                 room.instances.push(instance);
@@ -778,7 +779,7 @@ fn read_rooms(game: &mut Game, stream: &mut BufferStream) -> io::Result<()> {
                 let mut tile = RoomTile::default();
                 tile.x = stream.next_i32()?;
                 tile.y = stream.next_i32()?;
-                tile.background = stream.next_i32()?;
+                tile.background = BackgroundId::from_raw(stream.next_i32()?);
                 tile.tile_x = stream.next_i32()?;
                 tile.tile_y = stream.next_i32()?;
                 tile.width = stream.next_u32()?;
@@ -883,7 +884,7 @@ fn read_room_order(game: &mut Game, stream: &mut BufferStream) -> io::Result<()>
         let num_rooms = stream.next_u32()?;
         game.room_order.reserve(num_rooms as usize);
         for _ in 0..num_rooms {
-            game.room_order.push(stream.next_u32()?);
+            game.room_order.push(RoomId(stream.next_u32()?));
         }
     } else {
         unimplemented!();
@@ -0,0 +1,473 @@
+use crate::event::Event;
+use crate::game::{Action, Game, ResourceId};
+use crate::gml;
+use crate::gml::ast::{var_names, walk_exprs, ExprKind};
+use crate::library::{ActionKind, ArgumentValue};
+use crate::reference::{ResourceIndex, ResourceKind};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResourceRef {
+    pub kind: ResourceKind,
+    pub id: u32,
+}
+
+impl ResourceRef {
+    fn from_raw(kind: ResourceKind, id: i32) -> Option<ResourceRef> {
+        if id < 0 {
+            None
+        } else {
+            Some(ResourceRef {
+                kind,
+                id: id as u32,
+            })
+        }
+    }
+
+    fn from_id<I: ResourceId>(kind: ResourceKind, id: Option<I>) -> Option<ResourceRef> {
+        id.map(|id| ResourceRef { kind, id: id.get() })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Dependency {
+    pub from: ResourceRef,
+    pub to: ResourceRef,
+    // What kind of reference it is, e.g. "sprite", "instance" or "code".
+    pub via: &'static str,
+}
+
+pub struct DependencyGraph<'a> {
+    pub game: &'a Game,
+    pub edges: BTreeSet<Dependency>,
+    // Resources the game uses without anything referencing them: the rooms in the room order
+    // and whatever trigger conditions and library initialization code name.
+    pub roots: BTreeSet<ResourceRef>,
+    index: ResourceIndex<'a>,
+}
+
+fn all_resources(game: &Game) -> Vec<ResourceRef> {
+    let sprites = game.sprites.iter().map(|r| (ResourceKind::Sprite, r.id.0));
+    let sounds = game.sounds.iter().map(|r| (ResourceKind::Sound, r.id.0));
+    let backgrounds = game
+        .backgrounds
+        .iter()
+        .map(|r| (ResourceKind::Background, r.id.0));
+    let paths = game.paths.iter().map(|r| (ResourceKind::Path, r.id.0));
+    let scripts = game.scripts.iter().map(|r| (ResourceKind::Script, r.id.0));
+    let fonts = game.fonts.iter().map(|r| (ResourceKind::Font, r.id.0));
+    let timelines = game
+        .timelines
+        .iter()
+        .map(|r| (ResourceKind::Timeline, r.id.0));
+    let objects = game.objects.iter().map(|r| (ResourceKind::Object, r.id.0));
+    let rooms = game.rooms.iter().map(|r| (ResourceKind::Room, r.id.0));
+    sprites
+        .chain(sounds)
+        .chain(backgrounds)
+        .chain(paths)
+        .chain(scripts)
+        .chain(fonts)
+        .chain(timelines)
+        .chain(objects)
+        .chain(rooms)
+        .map(|(kind, id)| ResourceRef { kind, id })
+        .collect()
+}
+
+pub(crate) fn dot_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// The resources named by identifiers in `code`, in the order they appear. Code that doesn't parse
+// is still searched as far as the parser could recover.
+pub fn code_references(game: &Game, code: &str) -> Vec<ResourceRef> {
+    code_references_in(&ResourceIndex::new(game), code)
+}
+
+// GML refers to resources by name. A `var` local with a resource's name hides the resource, and
+// field names after a `.` are never resources.
+fn code_references_in(index: &ResourceIndex<'_>, code: &str) -> Vec<ResourceRef> {
+    let (program, _) = gml::parse(code);
+    let locals = var_names(&program.statements);
+    let mut references = vec![];
+    walk_exprs(&program.statements, &mut |expr| {
+        let name = match &expr.kind {
+            ExprKind::Identifier(name) if !locals.contains(name.as_str()) => name,
+            ExprKind::Call(function, _) => &function.name,
+            _ => return,
+        };
+        if let Some((kind, id)) = index.resource_by_name(name) {
+            references.push(ResourceRef { kind, id });
+        }
+    });
+    references
+}
+
+struct Builder<'a> {
+    index: ResourceIndex<'a>,
+    edges: BTreeSet<Dependency>,
+}
+
+impl Builder<'_> {
+    fn edge(&mut self, from: ResourceRef, to: Option<ResourceRef>, via: &'static str) {
+        if let Some(to) = to {
+            self.edges.insert(Dependency { from, to, via });
+        }
+    }
+
+    fn code(&mut self, from: ResourceRef, code: &str, via: &'static str) {
+        for to in code_references_in(&self.index, code) {
+            self.edge(from, Some(to), via);
+        }
+    }
+
+    fn actions(&mut self, from: ResourceRef, actions: &[Action]) {
+        for action in actions {
+            if action.has_target {
+                let target = ResourceRef::from_raw(ResourceKind::Object, action.target);
+                self.edge(from, target, "action target");
+            }
+            if action.kind() == ActionKind::Code {
+                if let Some(code) = action.arguments.first() {
+                    self.code(from, code, "code");
+                }
+                continue;
+            }
+            for argument in action.typed_arguments() {
+                match argument {
+                    ArgumentValue::Resource(kind, id) => {
+                        let to = kind
+                            .resource_kind()
+                            .and_then(|kind| ResourceRef::from_raw(kind, id));
+                        self.edge(from, to, "action argument");
+                    }
+                    ArgumentValue::Expression(code) => self.code(from, code, "action argument"),
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        let mut builder = Builder {
+            index: ResourceIndex::new(game),
+            edges: BTreeSet::new(),
+        };
+
+        for script in &game.scripts {
+            let from = ResourceRef {
+                kind: ResourceKind::Script,
+                id: script.id.0,
+            };
+            builder.code(from, &script.script, "code");
+        }
+
+        for timeline in &game.timelines {
+            let from = ResourceRef {
+                kind: ResourceKind::Timeline,
+                id: timeline.id.0,
+            };
+            for moment in &timeline.moments {
+                builder.actions(from, &moment.actions);
+            }
+        }
+
+        for object in &game.objects {
+            let from = ResourceRef {
+                kind: ResourceKind::Object,
+                id: object.id.0,
+            };
+            let sprite = ResourceRef::from_id(ResourceKind::Sprite, object.sprite);
+            builder.edge(from, sprite, "sprite");
+            let mask = ResourceRef::from_id(ResourceKind::Sprite, object.mask);
+            builder.edge(from, mask, "mask");
+            let parent = ResourceRef::from_id(ResourceKind::Object, object.parent);
+            builder.edge(from, parent, "parent");
+            for event in &object.events {
+                if let Event::Collision(id) = event.event() {
+                    let other = ResourceRef::from_id(ResourceKind::Object, id);
+                    builder.edge(from, other, "collision");
+                }
+                builder.actions(from, &event.actions);
+            }
+        }
+
+        for room in &game.rooms {
+            let from = ResourceRef {
+                kind: ResourceKind::Room,
+                id: room.id.0,
+            };
+            for background in &room.backgrounds {
+                let to = ResourceRef::from_id(ResourceKind::Background, background.background);
+                builder.edge(from, to, "background");
+            }
+            for view in &room.views {
+                let to = ResourceRef::from_id(ResourceKind::Object, view.target_object);
+                builder.edge(from, to, "view");
+            }
+            for instance in &room.instances {
+                let to = ResourceRef::from_id(ResourceKind::Object, instance.object);
+                builder.edge(from, to, "instance");
+                builder.code(from, &instance.creation_code, "creation code");
+            }
+            for tile in &room.tiles {
+                let to = ResourceRef::from_id(ResourceKind::Background, tile.background);
+                builder.edge(from, to, "tile");
+            }
+            builder.code(from, &room.creation_code, "creation code");
+        }
+
+        let mut roots = BTreeSet::new();
+        for &id in &game.room_order {
+            roots.insert(ResourceRef {
+                kind: ResourceKind::Room,
+                id: id.0,
+            });
+        }
+        for trigger in &game.triggers {
+            roots.extend(code_references_in(&builder.index, &trigger.condition));
+        }
+        for code in &game.library_init_scripts {
+            roots.extend(code_references_in(&builder.index, code));
+        }
+
+        // Drop references to resources that don't exist; `reference::dangling_references` reports
+        // those.
+        let index = builder.index;
+        let exists = |r: &ResourceRef| index.resource_name(r.kind, r.id as i32).is_some();
+        let edges = builder
+            .edges
+            .into_iter()
+            .filter(|e| exists(&e.to))
+            .collect();
+        let roots = roots.into_iter().filter(exists).collect();
+
+        DependencyGraph {
+            game,
+            edges,
+            roots,
+            index,
+        }
+    }
+
+    pub fn dependencies(&self, resource: ResourceRef) -> impl Iterator<Item = &Dependency> {
+        self.edges.iter().filter(move |e| e.from == resource)
+    }
+
+    pub fn dependents(&self, resource: ResourceRef) -> impl Iterator<Item = &Dependency> {
+        self.edges.iter().filter(move |e| e.to == resource)
+    }
+
+    // Resources that no other resource refers to, and that aren't roots.
+    pub fn unreferenced(&self) -> Vec<ResourceRef> {
+        let referenced: BTreeSet<_> = self
+            .edges
+            .iter()
+            .filter(|e| e.from != e.to)
+            .map(|e| e.to)
+            .collect();
+        all_resources(self.game)
+            .into_iter()
+            .filter(|r| !referenced.contains(r) && !self.roots.contains(r))
+            .collect()
+    }
+
+    // Resources that can't be reached from the roots. This also catches groups of resources that
+    // only refer to each other, like an object and the sprite only it uses.
+    pub fn unreachable(&self) -> Vec<ResourceRef> {
+        let mut reached = BTreeSet::new();
+        let mut stack: Vec<_> = self.roots.iter().copied().collect();
+        while let Some(resource) = stack.pop() {
+            if reached.insert(resource) {
+                stack.extend(self.dependencies(resource).map(|e| e.to));
+            }
+        }
+        all_resources(self.game)
+            .into_iter()
+            .filter(|r| !reached.contains(r))
+            .collect()
+    }
+
+    pub fn name(&self, resource: ResourceRef) -> &str {
+        self.index
+            .resource_name(resource.kind, resource.id as i32)
+            .unwrap_or("")
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+        for resource in all_resources(self.game) {
+            let _ = writeln!(
+                out,
+                "    \"{}{}\" [label={}, shape={}];",
+                resource.kind,
+                resource.id,
+                dot_string(self.name(resource)),
+                match resource.kind {
+                    ResourceKind::Object => "box",
+                    ResourceKind::Room => "house",
+                    ResourceKind::Script => "note",
+                    _ => "ellipse",
+                }
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "    \"{}{}\" -> \"{}{}\" [label={}];",
+                edge.from.kind,
+                edge.from.id,
+                edge.to.kind,
+                edge.to.id,
+                dot_string(edge.via)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_json(&self) -> Value {
+        let resource = |r: ResourceRef| {
+            json!({
+                "kind": r.kind.to_string(),
+                "id": r.id,
+                "name": self.name(r),
+            })
+        };
+        json!({
+            "resources": all_resources(self.game).into_iter().map(resource).collect::<Vec<_>>(),
+            "dependencies": self.edges.iter().map(|e| json!({
+                "from": resource(e.from),
+                "to": resource(e.to),
+                "via": e.via,
+            })).collect::<Vec<_>>(),
+            "roots": self.roots.iter().map(|&r| resource(r)).collect::<Vec<_>>(),
+            "unreferenced": self.unreferenced().into_iter().map(resource).collect::<Vec<_>>(),
+            "unreachable": self.unreachable().into_iter().map(resource).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        Object, ObjectId, Room, RoomId, RoomInstance, Script, ScriptId, Sprite, SpriteId,
+    };
+
+    fn sprite(id: u32, name: &str) -> Sprite {
+        Sprite {
+            id: SpriteId(id),
+            name: name.to_string(),
+            ..Sprite::default()
+        }
+    }
+
+    fn script(id: u32, name: &str, code: &str) -> Script {
+        Script {
+            id: ScriptId(id),
+            name: name.to_string(),
+            script: code.to_string(),
+        }
+    }
+
+    fn resource(kind: ResourceKind, id: u32) -> ResourceRef {
+        ResourceRef { kind, id }
+    }
+
+    fn game() -> Game {
+        let mut game = Game::default();
+        for (id, name) in [(0, "spr_player"), (1, "spr_orphan"), (2, "spr_pair")] {
+            game.sprites.push(sprite(id, name));
+        }
+        game.scripts
+            .push(script(0, "scr_start", "instance_create(0, 0, obj_player)"));
+        game.scripts
+            .push(script(1, "scr_pair", "sprite_index = spr_pair"));
+        game.objects.push(Object {
+            id: ObjectId(0),
+            name: "obj_player".to_string(),
+            sprite: Some(SpriteId(0)),
+            ..Object::default()
+        });
+        game.rooms.push(Room {
+            id: RoomId(0),
+            name: "rm_start".to_string(),
+            instances: vec![RoomInstance {
+                object: Some(ObjectId(0)),
+                ..RoomInstance::default()
+            }],
+            ..Room::default()
+        });
+        game.room_order.push(RoomId(0));
+        game
+    }
+
+    #[test]
+    fn locals_and_fields_are_not_references() {
+        let game = game();
+        let code = "var spr_player; spr_player = 1; a = other.spr_orphan; b = spr_pair";
+        assert_eq!(
+            code_references(&game, code),
+            vec![resource(ResourceKind::Sprite, 2)]
+        );
+        assert_eq!(
+            code_references(&game, "spr_player.spr_orphan = scr_pair()"),
+            vec![
+                resource(ResourceKind::Sprite, 0),
+                resource(ResourceKind::Script, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn unreferenced_resources() {
+        let game = game();
+        let graph = DependencyGraph::new(&game);
+        // The room is a root, and nothing names the scripts or `spr_orphan`.
+        assert_eq!(
+            graph.unreferenced(),
+            vec![
+                resource(ResourceKind::Sprite, 1),
+                resource(ResourceKind::Script, 0),
+                resource(ResourceKind::Script, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_resources() {
+        let game = game();
+        let graph = DependencyGraph::new(&game);
+        // `spr_pair` is referenced, but only by a script nothing reaches.
+        assert_eq!(
+            graph.unreachable(),
+            vec![
+                resource(ResourceKind::Sprite, 1),
+                resource(ResourceKind::Sprite, 2),
+                resource(ResourceKind::Script, 0),
+                resource(ResourceKind::Script, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn library_code_makes_roots() {
+        let mut game = game();
+        game.library_init_scripts.push("scr_pair()".to_string());
+        let graph = DependencyGraph::new(&game);
+        assert!(graph.roots.contains(&resource(ResourceKind::Script, 1)));
+        assert_eq!(
+            graph.unreachable(),
+            vec![
+                resource(ResourceKind::Sprite, 1),
+                resource(ResourceKind::Script, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,705 @@
+use crate::game::{Action, Game};
+use crate::gml::ast::{Stmt, StmtKind};
+use crate::gml::lexer::{tokenize, TokenKind};
+use crate::gml::{self, Span};
+use crate::library::{resource_name, ActionKind, ArgumentKind, ArgumentValue};
+use std::fmt;
+
+const INDENT: &str = "    ";
+
+// Action ids in the standard library that have a direct GML equivalent.
+const SET_HSPEED: u32 = 103;
+const SET_VSPEED: u32 = 104;
+const JUMP_TO_POSITION: u32 = 109;
+const SET_ALARM: u32 = 301;
+const TEST_EXPRESSION: u32 = 408;
+const EXECUTE_SCRIPT: u32 = 601;
+const CALL_PARENT_EVENT: u32 = 604;
+const COMMENT: u32 = 605;
+const TEST_VARIABLE: u32 = 612;
+const SET_SCORE: u32 = 701;
+const SET_LIVES: u32 = 711;
+const SET_HEALTH: u32 = 721;
+
+enum Node<'a> {
+    Action(&'a Action),
+    Block(Vec<Node<'a>>),
+    If {
+        action: &'a Action,
+        then: Option<Box<Node<'a>>>,
+        otherwise: Option<Box<Node<'a>>>,
+    },
+    Repeat(&'a Action, Option<Box<Node<'a>>>),
+    // An else or end of block without anything to attach to.
+    Stray(&'a Action),
+}
+
+struct Parser<'a> {
+    actions: &'a [Action],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Action> {
+        self.actions.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Action> {
+        let action = self.peek();
+        self.position += 1;
+        action
+    }
+
+    fn skip_ignored(&mut self) {
+        while let Some(action) = self.peek() {
+            match action.kind() {
+                ActionKind::Placeholder | ActionKind::Separator | ActionKind::Label => {
+                    self.position += 1
+                }
+                _ => break,
+            }
+        }
+    }
+
+    // Parses a single statement, which is what a question, else or repeat applies to.
+    fn statement(&mut self) -> Option<Node<'a>> {
+        self.skip_ignored();
+        match self.peek()?.kind() {
+            ActionKind::EndGroup => return None,
+            ActionKind::Else => return None,
+            _ => (),
+        }
+        let action = self.next()?;
+        Some(match action.kind() {
+            ActionKind::BeginGroup => {
+                let mut nodes = Vec::new();
+                loop {
+                    self.skip_ignored();
+                    match self.peek().map(|a| a.kind()) {
+                        None => break,
+                        Some(ActionKind::EndGroup) => {
+                            self.position += 1;
+                            break;
+                        }
+                        Some(ActionKind::Else) => nodes.push(Node::Stray(self.next()?)),
+                        Some(_) => nodes.extend(self.statement()),
+                    }
+                }
+                Node::Block(nodes)
+            }
+            ActionKind::Repeat => Node::Repeat(action, self.statement().map(Box::new)),
+            _ if action.is_question => {
+                let then = self.statement().map(Box::new);
+                self.skip_ignored();
+                let otherwise = match self.peek().map(|a| a.kind()) {
+                    Some(ActionKind::Else) => {
+                        self.position += 1;
+                        self.statement().map(Box::new)
+                    }
+                    _ => None,
+                };
+                Node::If {
+                    action,
+                    then,
+                    otherwise,
+                }
+            }
+            _ => Node::Action(action),
+        })
+    }
+
+    fn parse(mut self) -> Vec<Node<'a>> {
+        let mut nodes = Vec::new();
+        while self.position < self.actions.len() {
+            match self.statement() {
+                Some(node) => nodes.push(node),
+                None => {
+                    if let Some(action) = self.next() {
+                        if !matches!(
+                            action.kind(),
+                            ActionKind::Placeholder | ActionKind::Separator | ActionKind::Label
+                        ) {
+                            nodes.push(Node::Stray(action));
+                        }
+                    }
+                }
+            }
+        }
+        nodes
+    }
+}
+
+// Writes a string as a GML literal. GML strings have no escapes, so quotes are handled by picking
+// the other quote character or by concatenating.
+pub fn string_literal(text: &str) -> String {
+    if !text.contains('"') {
+        format!("\"{}\"", text)
+    } else if !text.contains('\'') {
+        format!("'{}'", text)
+    } else {
+        let mut parts = Vec::new();
+        for (i, part) in text.split('"').enumerate() {
+            if i > 0 {
+                parts.push("'\"'".to_string());
+            }
+            if !part.is_empty() {
+                parts.push(format!("\"{}\"", part));
+            }
+        }
+        parts.join(" + ")
+    }
+}
+
+fn argument_to_gml(game: &Game, kind: ArgumentKind, value: &str) -> String {
+    match ArgumentValue::parse(kind, value) {
+        ArgumentValue::Expression(e) if e.trim().is_empty() => "0".to_string(),
+        ArgumentValue::Expression(e) => e.trim().to_string(),
+        // "Both" arguments are expressions when they start with a quote and text otherwise.
+        ArgumentValue::Text(t) if kind == ArgumentKind::Both && t.starts_with(&['"', '\''][..]) => {
+            t.to_string()
+        }
+        ArgumentValue::Text(t) => string_literal(t),
+        ArgumentValue::Boolean(b) => b.to_string(),
+        ArgumentValue::Menu(n) => n.to_string(),
+        ArgumentValue::Color(c) => c.to_string(),
+        ArgumentValue::Resource(kind, id) => {
+            resource_name(game, kind, id).unwrap_or_else(|| id.to_string())
+        }
+    }
+}
+
+fn arguments_to_gml(game: &Game, action: &Action) -> Vec<String> {
+    action
+        .argument_kinds()
+        .into_iter()
+        .zip(&action.arguments)
+        .map(|(kind, value)| argument_to_gml(game, kind, value))
+        .collect()
+}
+
+fn is_standard(action: &Action, action_id: u32) -> bool {
+    action.library_action().map(|a| a.action_id) == Some(action_id)
+}
+
+fn assign(target: &str, value: &str, relative: bool) -> String {
+    let operator = if relative { "+=" } else { "=" };
+    format!("{} {} {};", target, operator, value)
+}
+
+// Variable names are stored as text but written as-is.
+fn variable(action: &Action) -> String {
+    action
+        .arguments
+        .first()
+        .map(|v| v.trim().to_string())
+        .unwrap_or_default()
+}
+
+// Why a list of actions can't be written as GML that behaves the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untranslatable {
+    // The position of the action in the list.
+    pub action: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Untranslatable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "action {}: {}", self.action, self.reason)
+    }
+}
+
+// A statement that ends a code action early, and how many loops, `with`s and switches it's in.
+struct Ending {
+    keyword: Span,
+    depth: usize,
+}
+
+fn find_endings(
+    statements: &[Stmt],
+    depth: usize,
+    endings: &mut Vec<Ending>,
+) -> Result<(), &'static str> {
+    for statement in statements {
+        let keyword = |length| Span::new(statement.span.start, statement.span.start + length);
+        match &statement.kind {
+            StmtKind::Exit => endings.push(Ending {
+                keyword: keyword("exit".len()),
+                depth,
+            }),
+            StmtKind::Return(None) => endings.push(Ending {
+                keyword: keyword("return".len()),
+                depth,
+            }),
+            StmtKind::Return(Some(_)) => return Err("code returns a value"),
+            // Outside of a loop these end the code, like exit.
+            StmtKind::Break | StmtKind::Continue if depth == 0 => endings.push(Ending {
+                keyword: Span::new(statement.span.start, statement.span.start),
+                depth,
+            }),
+            StmtKind::Block(body) => find_endings(body, depth, endings)?,
+            StmtKind::If(_, then, otherwise) => {
+                find_endings(std::slice::from_ref(then), depth, endings)?;
+                if let Some(otherwise) = otherwise {
+                    find_endings(std::slice::from_ref(otherwise), depth, endings)?;
+                }
+            }
+            StmtKind::While(_, body)
+            | StmtKind::DoUntil(body, _)
+            | StmtKind::Repeat(_, body)
+            | StmtKind::With(_, body) => {
+                find_endings(std::slice::from_ref(body), depth + 1, endings)?
+            }
+            StmtKind::For(init, _, step, body) => {
+                find_endings(std::slice::from_ref(init), depth, endings)?;
+                find_endings(std::slice::from_ref(step), depth, endings)?;
+                find_endings(std::slice::from_ref(body), depth + 1, endings)?;
+            }
+            StmtKind::Switch(_, body) => find_endings(body, depth + 1, endings)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+// Each code action runs as its own piece of code, so `exit` only ends that action. Once it's part
+// of the event's code it would end the whole event, so code that ends early is wrapped in a loop
+// that runs once, with `exit` turned into `break`.
+fn code_lines(code: &str) -> Result<Vec<String>, &'static str> {
+    let lines = |code: &str| code.lines().map(|l| l.trim_end().to_string()).collect();
+    let (program, errors) = gml::parse(code);
+    let (tokens, _) = tokenize(code);
+    if !errors.is_empty() {
+        let ends_early = tokens.iter().any(|t| {
+            t.kind == TokenKind::Keyword
+                && matches!(t.text(code), "exit" | "return" | "break" | "continue")
+        });
+        return if ends_early {
+            Err("code doesn't parse, so it's unclear where it ends")
+        } else {
+            Ok(lines(code))
+        };
+    }
+
+    let mut endings = vec![];
+    find_endings(&program.statements, 0, &mut endings)?;
+    if endings.is_empty() {
+        return Ok(lines(code));
+    }
+    if endings.iter().any(|e| e.depth > 0) {
+        return Err("code exits from inside a loop, with or switch");
+    }
+
+    let mut wrapped = String::with_capacity(code.len());
+    let mut position = 0;
+    for ending in endings.iter().filter(|e| e.keyword.end > e.keyword.start) {
+        wrapped.push_str(&code[position..ending.keyword.start]);
+        wrapped.push_str("break");
+        position = ending.keyword.end;
+    }
+    wrapped.push_str(&code[position..]);
+
+    // Indenting would change strings that span lines.
+    let multiline_string = tokens
+        .iter()
+        .any(|t| t.kind == TokenKind::String && t.text(code).contains('\n'));
+    let mut result = vec!["repeat (1) {".to_string()];
+    for line in wrapped.lines() {
+        let line = line.trim_end();
+        if line.is_empty() || multiline_string {
+            result.push(line.to_string());
+        } else {
+            result.push(format!("{}{}", INDENT, line));
+        }
+    }
+    result.push("}".to_string());
+    Ok(result)
+}
+
+// The GML for a single non-question action, one line per element.
+fn action_lines(game: &Game, action: &Action) -> Result<Vec<String>, &'static str> {
+    let args = arguments_to_gml(game, action);
+    let arg = |i: usize| args.get(i).cloned().unwrap_or_else(|| "0".to_string());
+    let relative = action.has_relative && action.relative;
+    match action.kind() {
+        ActionKind::Code => {
+            return code_lines(action.arguments.first().map(String::as_str).unwrap_or(""));
+        }
+        ActionKind::Variable => return Ok(vec![assign(&variable(action), &arg(1), relative)]),
+        ActionKind::Exit => return Ok(vec!["exit;".to_string()]),
+        _ => (),
+    }
+    Ok(standard_action_lines(action, &args, relative))
+}
+
+fn standard_action_lines(action: &Action, args: &[String], relative: bool) -> Vec<String> {
+    let arg = |i: usize| args.get(i).cloned().unwrap_or_else(|| "0".to_string());
+
+    if is_standard(action, COMMENT) {
+        let text = action.arguments.first().map(String::as_str).unwrap_or("");
+        return text.lines().map(|l| format!("// {}", l)).collect();
+    }
+    if is_standard(action, JUMP_TO_POSITION) {
+        return vec![
+            assign("x", &arg(0), relative),
+            assign("y", &arg(1), relative),
+        ];
+    }
+    if is_standard(action, SET_HSPEED) {
+        return vec![assign("hspeed", &arg(0), relative)];
+    }
+    if is_standard(action, SET_VSPEED) {
+        return vec![assign("vspeed", &arg(0), relative)];
+    }
+    if is_standard(action, SET_ALARM) {
+        return vec![assign(&format!("alarm[{}]", arg(1)), &arg(0), relative)];
+    }
+    if is_standard(action, SET_SCORE) {
+        return vec![assign("score", &arg(0), relative)];
+    }
+    if is_standard(action, SET_LIVES) {
+        return vec![assign("lives", &arg(0), relative)];
+    }
+    if is_standard(action, SET_HEALTH) {
+        return vec![assign("health", &arg(0), relative)];
+    }
+    if is_standard(action, CALL_PARENT_EVENT) {
+        return vec!["event_inherited();".to_string()];
+    }
+    if is_standard(action, EXECUTE_SCRIPT) {
+        let mut args = args.to_vec();
+        let script = if args.is_empty() {
+            "0".to_string()
+        } else {
+            args.remove(0)
+        };
+        // Unused trailing arguments are left at 0 in the action form.
+        while args.last().is_some_and(|a| a == "0") {
+            args.pop();
+        }
+        return vec![format!("{}({});", script, args.join(", "))];
+    }
+
+    if action.name.is_empty() {
+        return vec![format!(
+            "// unsupported action: library {}, action {}",
+            action.library_id, action.action_id
+        )];
+    }
+    let call = format!("{}({});", action.name, args.join(", "));
+    if relative {
+        vec![
+            "argument_relative = true;".to_string(),
+            call,
+            "argument_relative = false;".to_string(),
+        ]
+    } else {
+        vec![call]
+    }
+}
+
+fn condition(game: &Game, action: &Action) -> String {
+    let args = arguments_to_gml(game, action);
+    let arg = |i: usize| args.get(i).cloned().unwrap_or_else(|| "0".to_string());
+    if is_standard(action, TEST_EXPRESSION) {
+        return arg(0);
+    }
+    if is_standard(action, TEST_VARIABLE) {
+        let operator = match action.arguments.get(2).map(|s| s.trim()) {
+            Some("1") => Some("<"),
+            Some("2") => Some(">"),
+            Some("0") | None => Some("=="),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            return format!("{} {} {}", variable(action), operator, arg(1));
+        }
+    }
+    format!("{}({})", action.name, args.join(", "))
+}
+
+fn target(game: &Game, action: &Action) -> Option<String> {
+    if !action.has_target {
+        return None;
+    }
+    match action.target {
+        -1 => None,
+        -2 => Some("other".to_string()),
+        id => Some(resource_name(game, ArgumentKind::Object, id).unwrap_or_else(|| id.to_string())),
+    }
+}
+
+struct Writer<'a> {
+    game: &'a Game,
+    actions: &'a [Action],
+    error: Option<Untranslatable>,
+    output: String,
+    depth: usize,
+    // Used to name the temporaries holding question results.
+    questions: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn line(&mut self, line: &str) {
+        if line.is_empty() {
+            self.output.push('\n');
+            return;
+        }
+        for _ in 0..self.depth {
+            self.output.push_str(INDENT);
+        }
+        self.output.push_str(line);
+        self.output.push('\n');
+    }
+
+    fn question_result(&mut self) -> String {
+        let result = format!("__dnd_question{}", self.questions);
+        self.questions += 1;
+        self.line(&format!("var {};", result));
+        result
+    }
+
+    fn braced(&mut self, header: &str, node: Option<&Node>) {
+        self.line(&format!("{} {{", header));
+        self.depth += 1;
+        match node {
+            Some(Node::Block(nodes)) => self.nodes(nodes),
+            Some(node) => self.node(node),
+            None => (),
+        }
+        self.depth -= 1;
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Action(action) => {
+                let lines = match action_lines(self.game, action) {
+                    Ok(lines) => lines,
+                    Err(reason) => {
+                        if self.error.is_none() {
+                            let position =
+                                self.actions.iter().position(|a| std::ptr::eq(a, *action));
+                            self.error = Some(Untranslatable {
+                                action: position.unwrap_or(0),
+                                reason,
+                            });
+                        }
+                        return;
+                    }
+                };
+                match target(self.game, action) {
+                    Some(target) => {
+                        self.line(&format!("with ({}) {{", target));
+                        self.depth += 1;
+                        for line in &lines {
+                            self.line(line);
+                        }
+                        self.depth -= 1;
+                        self.line("}");
+                    }
+                    None => {
+                        for line in &lines {
+                            self.line(line);
+                        }
+                    }
+                }
+            }
+            Node::Block(nodes) => {
+                self.line("{");
+                self.depth += 1;
+                self.nodes(nodes);
+                self.depth -= 1;
+                self.line("}");
+            }
+            Node::If {
+                action,
+                then,
+                otherwise,
+            } => {
+                let condition = condition(self.game, action);
+                let relative = action.has_relative && action.relative;
+                let condition = match target(self.game, action) {
+                    // The question is only true if it holds for every instance it applies to.
+                    Some(target) => {
+                        let result = self.question_result();
+                        self.line(&format!("{} = true;", result));
+                        if relative {
+                            self.line("argument_relative = true;");
+                        }
+                        self.line(&format!(
+                            "with ({}) if !({}) {} = false;",
+                            target, condition, result
+                        ));
+                        if relative {
+                            self.line("argument_relative = false;");
+                        }
+                        result
+                    }
+                    // Relative questions read argument_relative, which has to be reset before
+                    // the actions that follow.
+                    None if relative => {
+                        let result = self.question_result();
+                        self.line("argument_relative = true;");
+                        self.line(&format!("{} = {};", result, condition));
+                        self.line("argument_relative = false;");
+                        result
+                    }
+                    None => condition,
+                };
+                let header = if action.negate {
+                    format!("if !({})", condition)
+                } else {
+                    format!("if ({})", condition)
+                };
+                self.braced(&header, then.as_deref());
+                if let Some(otherwise) = otherwise {
+                    self.braced("} else", Some(otherwise));
+                }
+                self.line("}");
+            }
+            Node::Repeat(action, body) => {
+                let count = arguments_to_gml(self.game, action)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| "0".to_string());
+                self.braced(&format!("repeat ({})", count), body.as_deref());
+                self.line("}");
+            }
+            Node::Stray(action) => {
+                let name = match action.kind() {
+                    ActionKind::Else => "else",
+                    ActionKind::EndGroup => "end of block",
+                    _ => "action",
+                };
+                self.line(&format!("// unmatched {}", name));
+            }
+        }
+    }
+}
+
+// Translates a list of actions, as found in object events and time line moments, into GML that
+// behaves the same way.
+pub fn actions_to_gml(game: &Game, actions: &[Action]) -> Result<String, Untranslatable> {
+    let nodes = Parser {
+        actions,
+        position: 0,
+    }
+    .parse();
+    let mut writer = Writer {
+        game,
+        actions,
+        error: None,
+        output: String::new(),
+        depth: 0,
+        questions: 0,
+    };
+    writer.nodes(&nodes);
+    match writer.error {
+        Some(error) => Err(error),
+        None => Ok(writer.output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(code: &str) -> Action {
+        Action {
+            library_id: 1,
+            action_id: 603,
+            action_kind: 7,
+            parameters_used: 1,
+            parameters: vec![1],
+            target: -1,
+            arguments: vec![code.to_string()],
+            ..Action::default()
+        }
+    }
+
+    fn test_expression(expression: &str, negate: bool) -> Action {
+        Action {
+            library_id: 1,
+            action_id: TEST_EXPRESSION,
+            is_question: true,
+            name: "action_if".to_string(),
+            parameters_used: 1,
+            parameters: vec![0],
+            target: -1,
+            arguments: vec![expression.to_string()],
+            negate,
+            ..Action::default()
+        }
+    }
+
+    fn kind(action_kind: u32) -> Action {
+        Action {
+            action_kind,
+            target: -1,
+            ..Action::default()
+        }
+    }
+
+    fn translate(actions: &[Action]) -> Result<String, Untranslatable> {
+        actions_to_gml(&Game::default(), actions)
+    }
+
+    #[test]
+    fn questions_blocks_and_else() {
+        let actions = [
+            test_expression("x > 0", false),
+            kind(1),
+            code("a = 1;"),
+            code("b = 2;"),
+            kind(2),
+            kind(3),
+            test_expression("y", true),
+            code("c = 3;"),
+        ];
+        assert_eq!(
+            translate(&actions).unwrap(),
+            "if (x > 0) {\n    a = 1;\n    b = 2;\n} else {\n    if !(y) {\n        c = 3;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn code_without_exit_is_copied() {
+        let actions = [code("// exit in a comment\ns = \"exit\";"), code("t = 1;")];
+        assert_eq!(
+            translate(&actions).unwrap(),
+            "// exit in a comment\ns = \"exit\";\nt = 1;\n"
+        );
+    }
+
+    #[test]
+    fn exit_only_ends_its_code_action() {
+        let actions = [code("if (done) exit;\na = 1;"), code("b = 2;")];
+        assert_eq!(
+            translate(&actions).unwrap(),
+            "repeat (1) {\n    if (done) break;\n    a = 1;\n}\nb = 2;\n"
+        );
+    }
+
+    #[test]
+    fn exit_inside_a_loop_is_untranslatable() {
+        let actions = [code("a = 1;"), code("with (obj) { if (x) exit; }")];
+        let error = translate(&actions).unwrap_err();
+        assert_eq!(error.action, 1);
+        assert!(translate(&[code("return 1;")]).is_err());
+    }
+
+    #[test]
+    fn exit_action_ends_the_event() {
+        let actions = [test_expression("dead", false), kind(4), code("a = 1;")];
+        assert_eq!(
+            translate(&actions).unwrap(),
+            "if (dead) {\n    exit;\n}\na = 1;\n"
+        );
+    }
+}
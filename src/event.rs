@@ -0,0 +1,469 @@
+use crate::game::{Game, ObjectEvent, ObjectId, ResourceId};
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepKind {
+    Normal,
+    Begin,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JoystickInput {
+    Left,
+    Right,
+    Up,
+    Down,
+    Button(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEvent {
+    Button(MouseButton),
+    NoButton,
+    Pressed(MouseButton),
+    Released(MouseButton),
+    Enter,
+    Leave,
+    // Joysticks are numbered from 1. Only joysticks 1 and 2 with buttons 0 to 7 have events.
+    Joystick(u32, JoystickInput),
+    GlobalButton(MouseButton),
+    GlobalPressed(MouseButton),
+    GlobalReleased(MouseButton),
+    WheelUp,
+    WheelDown,
+    Unknown(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OtherEvent {
+    OutsideRoom,
+    IntersectBoundary,
+    GameStart,
+    GameEnd,
+    RoomStart,
+    RoomEnd,
+    NoMoreLives,
+    AnimationEnd,
+    EndOfPath,
+    NoMoreHealth,
+    CloseButton,
+    User(u32),
+    OutsideView(u32),
+    BoundaryView(u32),
+    Unknown(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    Create,
+    Destroy,
+    Alarm(u32),
+    Step(StepKind),
+    // `None` for a collision event with no object, which Game Maker writes as -1.
+    Collision(Option<ObjectId>),
+    Keyboard(u32),
+    Mouse(MouseEvent),
+    Other(OtherEvent),
+    Draw,
+    KeyPress(u32),
+    KeyRelease(u32),
+    Trigger(u32),
+    // Anything that doesn't fit the above is kept as the raw pair.
+    Unknown(u32, i32),
+}
+
+const BUTTONS: [MouseButton; 3] = [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+fn raw_number(n: u32) -> Option<i32> {
+    i32::try_from(n).ok()
+}
+
+// For the numbered events that only have `count` slots.
+fn slot(base: i32, n: u32, count: u32) -> Option<i32> {
+    if n < count {
+        Some(base + n as i32)
+    } else {
+        None
+    }
+}
+
+fn button_index(button: MouseButton) -> i32 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Right => 1,
+        MouseButton::Middle => 2,
+    }
+}
+
+impl MouseEvent {
+    pub fn from_raw(number: i32) -> MouseEvent {
+        let button = |base: i32| BUTTONS[(number - base) as usize];
+        match number {
+            0..=2 => MouseEvent::Button(button(0)),
+            3 => MouseEvent::NoButton,
+            4..=6 => MouseEvent::Pressed(button(4)),
+            7..=9 => MouseEvent::Released(button(7)),
+            10 => MouseEvent::Enter,
+            11 => MouseEvent::Leave,
+            16..=19 | 21..=28 | 31..=34 | 36..=43 => {
+                let (joystick, offset) = if number < 31 {
+                    (1, number - 16)
+                } else {
+                    (2, number - 31)
+                };
+                let input = match offset {
+                    0 => JoystickInput::Left,
+                    1 => JoystickInput::Right,
+                    2 => JoystickInput::Up,
+                    3 => JoystickInput::Down,
+                    n => JoystickInput::Button(n as u32 - 5),
+                };
+                MouseEvent::Joystick(joystick, input)
+            }
+            50..=52 => MouseEvent::GlobalButton(button(50)),
+            53..=55 => MouseEvent::GlobalPressed(button(53)),
+            56..=58 => MouseEvent::GlobalReleased(button(56)),
+            60 => MouseEvent::WheelUp,
+            61 => MouseEvent::WheelDown,
+            n => MouseEvent::Unknown(n),
+        }
+    }
+
+    // `None` for a joystick or button that has no event.
+    pub fn to_raw(self) -> Option<i32> {
+        let number = match self {
+            MouseEvent::Button(b) => button_index(b),
+            MouseEvent::NoButton => 3,
+            MouseEvent::Pressed(b) => 4 + button_index(b),
+            MouseEvent::Released(b) => 7 + button_index(b),
+            MouseEvent::Enter => 10,
+            MouseEvent::Leave => 11,
+            MouseEvent::Joystick(joystick, input) => {
+                let base = match joystick {
+                    1 => 16,
+                    2 => 31,
+                    _ => return None,
+                };
+                match input {
+                    JoystickInput::Left => base,
+                    JoystickInput::Right => base + 1,
+                    JoystickInput::Up => base + 2,
+                    JoystickInput::Down => base + 3,
+                    JoystickInput::Button(n) => slot(base + 5, n, 8)?,
+                }
+            }
+            MouseEvent::GlobalButton(b) => 50 + button_index(b),
+            MouseEvent::GlobalPressed(b) => 53 + button_index(b),
+            MouseEvent::GlobalReleased(b) => 56 + button_index(b),
+            MouseEvent::WheelUp => 60,
+            MouseEvent::WheelDown => 61,
+            MouseEvent::Unknown(n) => n,
+        };
+        Some(number)
+    }
+}
+
+impl OtherEvent {
+    pub fn from_raw(number: i32) -> OtherEvent {
+        match number {
+            0 => OtherEvent::OutsideRoom,
+            1 => OtherEvent::IntersectBoundary,
+            2 => OtherEvent::GameStart,
+            3 => OtherEvent::GameEnd,
+            4 => OtherEvent::RoomStart,
+            5 => OtherEvent::RoomEnd,
+            6 => OtherEvent::NoMoreLives,
+            7 => OtherEvent::AnimationEnd,
+            8 => OtherEvent::EndOfPath,
+            9 => OtherEvent::NoMoreHealth,
+            10..=25 => OtherEvent::User(number as u32 - 10),
+            30 => OtherEvent::CloseButton,
+            40..=47 => OtherEvent::OutsideView(number as u32 - 40),
+            50..=57 => OtherEvent::BoundaryView(number as u32 - 50),
+            n => OtherEvent::Unknown(n),
+        }
+    }
+
+    // `None` for a user event or view that has no event.
+    pub fn to_raw(self) -> Option<i32> {
+        match self {
+            OtherEvent::OutsideRoom => Some(0),
+            OtherEvent::IntersectBoundary => Some(1),
+            OtherEvent::GameStart => Some(2),
+            OtherEvent::GameEnd => Some(3),
+            OtherEvent::RoomStart => Some(4),
+            OtherEvent::RoomEnd => Some(5),
+            OtherEvent::NoMoreLives => Some(6),
+            OtherEvent::AnimationEnd => Some(7),
+            OtherEvent::EndOfPath => Some(8),
+            OtherEvent::NoMoreHealth => Some(9),
+            OtherEvent::CloseButton => Some(30),
+            OtherEvent::User(n) => slot(10, n, 16),
+            OtherEvent::OutsideView(n) => slot(40, n, 8),
+            OtherEvent::BoundaryView(n) => slot(50, n, 8),
+            OtherEvent::Unknown(n) => Some(n),
+        }
+    }
+}
+
+impl Event {
+    pub fn from_raw(event_type: u32, event_number: i32) -> Event {
+        match (event_type, event_number) {
+            (0, 0) => Event::Create,
+            (1, 0) => Event::Destroy,
+            (2, n) if n >= 0 => Event::Alarm(n as u32),
+            (3, 0) => Event::Step(StepKind::Normal),
+            (3, 1) => Event::Step(StepKind::Begin),
+            (3, 2) => Event::Step(StepKind::End),
+            (4, n) if n >= -1 => Event::Collision(ObjectId::from_raw(n)),
+            (5, n) if n >= 0 => Event::Keyboard(n as u32),
+            (6, n) => Event::Mouse(MouseEvent::from_raw(n)),
+            (7, n) => Event::Other(OtherEvent::from_raw(n)),
+            (8, 0) => Event::Draw,
+            (9, n) if n >= 0 => Event::KeyPress(n as u32),
+            (10, n) if n >= 0 => Event::KeyRelease(n as u32),
+            (11, n) if n >= 0 => Event::Trigger(n as u32),
+            (event_type, n) => Event::Unknown(event_type, n),
+        }
+    }
+
+    // The raw type and number, or `None` for an event that can't be stored, such as a third
+    // joystick or a number that doesn't fit.
+    pub fn to_raw(self) -> Option<(u32, i32)> {
+        let raw = match self {
+            Event::Create => (0, 0),
+            Event::Destroy => (1, 0),
+            Event::Alarm(n) => (2, raw_number(n)?),
+            Event::Step(StepKind::Normal) => (3, 0),
+            Event::Step(StepKind::Begin) => (3, 1),
+            Event::Step(StepKind::End) => (3, 2),
+            Event::Collision(None) => (4, -1),
+            Event::Collision(Some(object)) => (4, raw_number(object.0)?),
+            Event::Keyboard(key) => (5, raw_number(key)?),
+            Event::Mouse(mouse) => (6, mouse.to_raw()?),
+            Event::Other(other) => (7, other.to_raw()?),
+            Event::Draw => (8, 0),
+            Event::KeyPress(key) => (9, raw_number(key)?),
+            Event::KeyRelease(key) => (10, raw_number(key)?),
+            Event::Trigger(n) => (11, raw_number(n)?),
+            Event::Unknown(event_type, n) => (event_type, n),
+        };
+        Some(raw)
+    }
+
+    // Like the `Display` output, but with object and trigger names looked up in `game`.
+    pub fn display_name(&self, game: &Game) -> String {
+        match *self {
+            Event::Collision(id) => match game.object(id) {
+                Some(object) => format!("Collision with {}", object.name),
+                None => self.to_string(),
+            },
+            Event::Trigger(id) => match game.trigger(id) {
+                Some(trigger) if !trigger.name.is_empty() => format!("Trigger ({})", trigger.name),
+                _ => self.to_string(),
+            },
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl From<&ObjectEvent> for Event {
+    fn from(event: &ObjectEvent) -> Self {
+        Event::from_raw(event.event_type, event.event_number)
+    }
+}
+
+impl ObjectEvent {
+    pub fn event(&self) -> Event {
+        Event::from(self)
+    }
+}
+
+// Names of the virtual key codes used by keyboard events.
+pub fn key_name(key: u32) -> String {
+    let name = match key {
+        0 => "No Key",
+        1 => "Any Key",
+        8 => "Backspace",
+        9 => "Tab",
+        13 => "Enter",
+        16 => "Shift",
+        17 => "Ctrl",
+        18 => "Alt",
+        19 => "Pause",
+        27 => "Escape",
+        32 => "Space",
+        33 => "Page Up",
+        34 => "Page Down",
+        35 => "End",
+        36 => "Home",
+        37 => "Left",
+        38 => "Up",
+        39 => "Right",
+        40 => "Down",
+        45 => "Insert",
+        46 => "Delete",
+        48..=57 | 65..=90 => return (key as u8 as char).to_string(),
+        96..=105 => return format!("Keypad {}", key - 96),
+        106 => "Keypad *",
+        107 => "Keypad +",
+        109 => "Keypad -",
+        110 => "Keypad .",
+        111 => "Keypad /",
+        112..=123 => return format!("F{}", key - 111),
+        _ => return format!("Key {}", key),
+    };
+    name.to_string()
+}
+
+impl fmt::Display for MouseButton {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            MouseButton::Left => "Left",
+            MouseButton::Right => "Right",
+            MouseButton::Middle => "Middle",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for MouseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MouseEvent::Button(b) => write!(f, "{} Button", b),
+            MouseEvent::NoButton => write!(f, "No Button"),
+            MouseEvent::Pressed(b) => write!(f, "{} Pressed", b),
+            MouseEvent::Released(b) => write!(f, "{} Released", b),
+            MouseEvent::Enter => write!(f, "Mouse Enter"),
+            MouseEvent::Leave => write!(f, "Mouse Leave"),
+            MouseEvent::Joystick(joystick, input) => match input {
+                JoystickInput::Left => write!(f, "Joystick {} Left", joystick),
+                JoystickInput::Right => write!(f, "Joystick {} Right", joystick),
+                JoystickInput::Up => write!(f, "Joystick {} Up", joystick),
+                JoystickInput::Down => write!(f, "Joystick {} Down", joystick),
+                JoystickInput::Button(n) => write!(f, "Joystick {} Button {}", joystick, n + 1),
+            },
+            MouseEvent::GlobalButton(b) => write!(f, "Global {} Button", b),
+            MouseEvent::GlobalPressed(b) => write!(f, "Global {} Pressed", b),
+            MouseEvent::GlobalReleased(b) => write!(f, "Global {} Released", b),
+            MouseEvent::WheelUp => write!(f, "Mouse Wheel Up"),
+            MouseEvent::WheelDown => write!(f, "Mouse Wheel Down"),
+            MouseEvent::Unknown(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl fmt::Display for OtherEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OtherEvent::OutsideRoom => write!(f, "Outside Room"),
+            OtherEvent::IntersectBoundary => write!(f, "Intersect Boundary"),
+            OtherEvent::GameStart => write!(f, "Game Start"),
+            OtherEvent::GameEnd => write!(f, "Game End"),
+            OtherEvent::RoomStart => write!(f, "Room Start"),
+            OtherEvent::RoomEnd => write!(f, "Room End"),
+            OtherEvent::NoMoreLives => write!(f, "No More Lives"),
+            OtherEvent::AnimationEnd => write!(f, "Animation End"),
+            OtherEvent::EndOfPath => write!(f, "End Of Path"),
+            OtherEvent::NoMoreHealth => write!(f, "No More Health"),
+            OtherEvent::CloseButton => write!(f, "Close Button"),
+            OtherEvent::User(n) => write!(f, "User Defined {}", n),
+            OtherEvent::OutsideView(n) => write!(f, "Outside View {}", n),
+            OtherEvent::BoundaryView(n) => write!(f, "Boundary View {}", n),
+            OtherEvent::Unknown(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Create => write!(f, "Create"),
+            Event::Destroy => write!(f, "Destroy"),
+            Event::Alarm(n) => write!(f, "Alarm {}", n),
+            Event::Step(StepKind::Normal) => write!(f, "Step (Normal)"),
+            Event::Step(StepKind::Begin) => write!(f, "Step (Begin)"),
+            Event::Step(StepKind::End) => write!(f, "Step (End)"),
+            Event::Collision(Some(id)) => write!(f, "Collision with object {}", id),
+            Event::Collision(None) => write!(f, "Collision with <undefined>"),
+            Event::Keyboard(key) => write!(f, "Keyboard <{}>", key_name(*key)),
+            Event::Mouse(mouse) => write!(f, "Mouse ({})", mouse),
+            Event::Other(other) => write!(f, "Other ({})", other),
+            Event::Draw => write!(f, "Draw"),
+            Event::KeyPress(key) => write!(f, "Key Press <{}>", key_name(*key)),
+            Event::KeyRelease(key) => write!(f, "Key Release <{}>", key_name(*key)),
+            Event::Trigger(n) => write!(f, "Trigger {}", n),
+            Event::Unknown(event_type, n) => write!(f, "Event {} ({})", event_type, n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_events_round_trip() {
+        for event_type in 0..=12 {
+            for number in -2..=70 {
+                let event = Event::from_raw(event_type, number);
+                assert_eq!(
+                    event.to_raw(),
+                    Some((event_type, number)),
+                    "{:?} read from ({}, {})",
+                    event,
+                    event_type,
+                    number
+                );
+            }
+        }
+        for &(event_type, number) in &[(2, i32::MAX), (4, i32::MAX), (7, i32::MIN), (12, 5)] {
+            assert_eq!(
+                Event::from_raw(event_type, number).to_raw(),
+                Some((event_type, number))
+            );
+        }
+    }
+
+    #[test]
+    fn events_without_a_raw_number_are_rejected() {
+        let joystick = |n, input| Event::Mouse(MouseEvent::Joystick(n, input));
+        assert_eq!(joystick(0, JoystickInput::Left).to_raw(), None);
+        assert_eq!(joystick(3, JoystickInput::Up).to_raw(), None);
+        assert_eq!(joystick(1, JoystickInput::Button(8)).to_raw(), None);
+        assert_eq!(
+            joystick(2, JoystickInput::Button(7)).to_raw(),
+            Some((6, 43))
+        );
+        assert_eq!(Event::Other(OtherEvent::User(16)).to_raw(), None);
+        assert_eq!(Event::Other(OtherEvent::OutsideView(8)).to_raw(), None);
+        assert_eq!(Event::Other(OtherEvent::BoundaryView(8)).to_raw(), None);
+        assert_eq!(Event::Alarm(u32::MAX).to_raw(), None);
+        assert_eq!(Event::Collision(Some(ObjectId(u32::MAX))).to_raw(), None);
+        assert_eq!(Event::Keyboard(1 << 31).to_raw(), None);
+    }
+
+    #[test]
+    fn events_have_readable_names() {
+        assert_eq!(Event::from_raw(3, 2).to_string(), "Step (End)");
+        assert_eq!(
+            Event::from_raw(6, 27).to_string(),
+            "Mouse (Joystick 1 Button 7)"
+        );
+        assert_eq!(Event::from_raw(7, 12).to_string(), "Other (User Defined 2)");
+        assert_eq!(Event::from_raw(9, 65).to_string(), "Key Press <A>");
+        assert_eq!(
+            Event::from_raw(4, -1).to_string(),
+            "Collision with <undefined>"
+        );
+    }
+}
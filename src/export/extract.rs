@@ -0,0 +1,199 @@
+use crate::dnd::actions_to_gml;
+use crate::export::includes::{extract_includes, IncludeOptions};
+use crate::export::{file_name, write_json, write_png};
+use crate::game::{Action, Game, Image};
+use crate::gml::{self, FormatOptions};
+use image::RgbaImage;
+use serde_json::json;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    // Reformat scripts and event code. Code that doesn't parse is written unchanged.
+    pub format: Option<FormatOptions>,
+}
+
+fn write_code(code: &str, path: &Path, options: &ExtractOptions) -> io::Result<()> {
+    let formatted = options
+        .format
+        .as_ref()
+        .and_then(|format_options| gml::format(code, format_options).ok());
+    fs::write(path, formatted.as_deref().unwrap_or(code))
+}
+
+// The actions are also written as JSON, so nothing is lost when they can't be translated.
+fn actions_code(game: &Game, actions: &[Action]) -> String {
+    match actions_to_gml(game, actions) {
+        Ok(code) => code,
+        Err(error) => format!("// Not translated to GML: {}\n", error),
+    }
+}
+
+fn write_image(image: &Image, path: &Path) -> io::Result<()> {
+    if image.width == 0 || image.height == 0 {
+        return Ok(());
+    }
+    write_png(&RgbaImage::from(image), path)
+}
+
+// Writes every resource in the game to an organized directory tree under `dir`.
+pub fn extract_all<P: AsRef<Path>>(
+    game: &Game,
+    dir: P,
+    options: &ExtractOptions,
+) -> io::Result<()> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let sprites = dir.join("sprites");
+    for sprite in &game.sprites {
+        let sprite_dir = sprites.join(file_name(&sprite.name));
+        fs::create_dir_all(&sprite_dir)?;
+        for (i, frame) in sprite.frames.iter().enumerate() {
+            write_image(frame, &sprite_dir.join(format!("{}.png", i)))?;
+        }
+        write_json(
+            &json!({
+                "origin": sprite.origin,
+                "frames": sprite.frames.len(),
+                "masks": sprite.masks.iter().map(|m| json!({
+                    "left": m.left,
+                    "right": m.right,
+                    "top": m.top,
+                    "bottom": m.bottom,
+                })).collect::<Vec<_>>(),
+            }),
+            sprite_dir.join("sprite.json"),
+        )?;
+    }
+
+    let backgrounds = dir.join("backgrounds");
+    fs::create_dir_all(&backgrounds)?;
+    for background in &game.backgrounds {
+        let path = backgrounds.join(format!("{}.png", file_name(&background.name)));
+        write_image(&background.image, &path)?;
+    }
+
+    let sounds = dir.join("sounds");
+    fs::create_dir_all(&sounds)?;
+    for sound in game.sounds.iter().filter(|s| !s.data.is_empty()) {
+        let extension = match sound.format().extension() {
+            Some(extension) => extension.to_string(),
+            None if sound.filetype.starts_with('.') => file_name(&sound.filetype),
+            None => ".bin".to_string(),
+        };
+        let path = sounds.join(format!("{}{}", file_name(&sound.name), extension));
+        fs::write(path, &sound.data)?;
+    }
+
+    let paths = dir.join("paths");
+    fs::create_dir_all(&paths)?;
+    for path in &game.paths {
+        write_json(path, paths.join(format!("{}.json", file_name(&path.name))))?;
+    }
+
+    let scripts = dir.join("scripts");
+    fs::create_dir_all(&scripts)?;
+    for script in &game.scripts {
+        let path = scripts.join(format!("{}.gml", file_name(&script.name)));
+        write_code(&script.script, &path, options)?;
+    }
+
+    let timelines = dir.join("timelines");
+    for timeline in &game.timelines {
+        let timeline_dir = timelines.join(file_name(&timeline.name));
+        fs::create_dir_all(&timeline_dir)?;
+        for moment in &timeline.moments {
+            let name = format!("moment_{}", moment.position);
+            write_code(
+                &actions_code(game, &moment.actions),
+                &timeline_dir.join(format!("{}.gml", name)),
+                options,
+            )?;
+            write_json(&moment.actions, timeline_dir.join(format!("{}.json", name)))?;
+        }
+    }
+
+    let objects = dir.join("objects");
+    for object in &game.objects {
+        let object_dir = objects.join(file_name(&object.name));
+        fs::create_dir_all(&object_dir)?;
+        write_json(
+            &json!({
+                "sprite": game.sprite(object.sprite).map(|s| &s.name),
+                "solid": object.solid,
+                "visible": object.visible,
+                "depth": object.depth,
+                "persistent": object.persistent,
+                "parent": game.object(object.parent).map(|o| &o.name),
+                "mask": game.sprite(object.mask).map(|s| &s.name),
+            }),
+            object_dir.join("object.json"),
+        )?;
+        for event in &object.events {
+            // The same names the event has everywhere else, e.g. "Step (Normal)".
+            let name = file_name(&event.event().display_name(game));
+            write_code(
+                &actions_code(game, &event.actions),
+                &object_dir.join(format!("{}.gml", name)),
+                options,
+            )?;
+            write_json(&event.actions, object_dir.join(format!("{}.json", name)))?;
+        }
+    }
+
+    let rooms = dir.join("rooms");
+    fs::create_dir_all(&rooms)?;
+    for room in &game.rooms {
+        write_json(room, rooms.join(format!("{}.json", file_name(&room.name))))?;
+    }
+
+    let include_options = IncludeOptions {
+        export_all: true,
+        ..IncludeOptions::default()
+    };
+    extract_includes(game, dir.join("includes"), &include_options)?;
+
+    let extensions = dir.join("extensions");
+    for extension in &game.extensions {
+        let extension_dir = extensions.join(file_name(&extension.name));
+        fs::create_dir_all(&extension_dir)?;
+        for file in &extension.files {
+            fs::write(extension_dir.join(file_name(&file.name)), &file.data)?;
+        }
+    }
+
+    let settings = dir.join("settings");
+    fs::create_dir_all(&settings)?;
+    let settings_images = [
+        ("loading_bar_back", &game.settings.loading_bar_back),
+        ("loading_bar_front", &game.settings.loading_bar_front),
+        ("loading_background", &game.settings.loading_background),
+    ];
+    for (name, image) in &settings_images {
+        if let Some(image) = image {
+            write_image(image, &settings.join(format!("{}.png", name)))?;
+        }
+    }
+    // The images were written above, so leave them out of the JSON.
+    let mut settings_json = serde_json::to_value(&game.settings)?;
+    if let Some(fields) = settings_json.as_object_mut() {
+        for (name, _) in &settings_images {
+            fields.remove(*name);
+        }
+    }
+    write_json(&settings_json, settings.join("settings.json"))?;
+    write_json(&game.constants, settings.join("constants.json"))?;
+    write_json(&game.triggers, settings.join("triggers.json"))?;
+    fs::write(settings.join("game_information.rtf"), &game.help.content)?;
+    fs::write(settings.join("game_information.txt"), game.help.to_text())?;
+    fs::write(
+        settings.join("game_information.md"),
+        game.help.to_markdown(),
+    )?;
+    fs::write(settings.join("game_information.html"), game.help.to_html())?;
+
+    Ok(())
+}
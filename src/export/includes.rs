@@ -0,0 +1,347 @@
+use crate::game::{Game, Include};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Where the runner puts an included file when the game starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTarget {
+    None,
+    TempDirectory,
+    GameDirectory,
+    CustomFolder,
+    Unknown(u32),
+}
+
+impl From<u32> for ExportTarget {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => ExportTarget::None,
+            1 => ExportTarget::TempDirectory,
+            2 => ExportTarget::GameDirectory,
+            3 => ExportTarget::CustomFolder,
+            value => ExportTarget::Unknown(value),
+        }
+    }
+}
+
+impl Include {
+    pub fn export_target(&self) -> ExportTarget {
+        self.export.into()
+    }
+}
+
+// Stand-ins for the directories the runner would use, relative to the target directory.
+#[derive(Debug, Clone)]
+pub struct IncludeOptions {
+    pub temp_dir: PathBuf,
+    pub game_dir: PathBuf,
+    pub custom_dir: PathBuf,
+    // Also write includes that are set not to be exported, into the game directory.
+    pub export_all: bool,
+}
+
+impl Default for IncludeOptions {
+    fn default() -> Self {
+        IncludeOptions {
+            temp_dir: PathBuf::from("temp"),
+            game_dir: PathBuf::from("game"),
+            custom_dir: PathBuf::from("custom"),
+            export_all: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncludeStatus {
+    Written(PathBuf),
+    // The file already existed and the include isn't set to overwrite it.
+    Kept(PathBuf),
+    NotExported,
+    // The file wasn't stored in the game, so there's nothing to write.
+    NoData,
+    // The name or export folder would escape the target directory.
+    UnsafePath,
+}
+
+#[derive(Debug, Clone)]
+pub struct IncludeResult<'a> {
+    pub include: &'a Include,
+    pub status: IncludeStatus,
+}
+
+// The same flags the decoder checks before reading the data, so an empty file that was stored
+// still counts.
+pub fn has_data(include: &Include) -> bool {
+    include.original_chosen && include.store_in_editable
+}
+
+// Includes that only reference a file on the author's machine.
+pub fn missing_data(game: &Game) -> impl Iterator<Item = &Include> {
+    game.includes.iter().filter(|i| !has_data(i))
+}
+
+// Turns a path from the game file into a relative path that stays inside whatever it's joined to.
+// Drive letters and leading separators are dropped so absolute folders land under the target
+// directory; any `..` component rejects the path outright.
+pub fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let path = match path.find(':') {
+        Some(1) if path.as_bytes()[0].is_ascii_alphabetic() => &path[2..],
+        _ => path,
+    };
+    let mut result = PathBuf::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => continue,
+            ".." => return None,
+            c if c.chars().any(|c| c == ':' || c.is_control()) => return None,
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+fn include_path(include: &Include, dir: &Path, options: &IncludeOptions) -> Option<PathBuf> {
+    let base = match include.export_target() {
+        ExportTarget::TempDirectory => dir.join(&options.temp_dir),
+        ExportTarget::GameDirectory => dir.join(&options.game_dir),
+        ExportTarget::CustomFolder => dir
+            .join(&options.custom_dir)
+            .join(safe_relative_path(&include.export_folder)?),
+        ExportTarget::None | ExportTarget::Unknown(_) => dir.join(&options.game_dir),
+    };
+    // The runner only ever uses the name as a file name.
+    let name = safe_relative_path(&include.name)?;
+    if name.components().count() != 1 {
+        return None;
+    }
+    Some(base.join(name))
+}
+
+// Writes each included file under `dir` where the runner would put it, and reports what happened
+// to every include. Includes are processed in order, so a later include that isn't set to
+// overwrite keeps the file written by an earlier one.
+pub fn extract_includes<'a, P: AsRef<Path>>(
+    game: &'a Game,
+    dir: P,
+    options: &IncludeOptions,
+) -> io::Result<Vec<IncludeResult<'a>>> {
+    let dir = dir.as_ref();
+    let mut results = Vec::with_capacity(game.includes.len());
+    for include in &game.includes {
+        let status = match include.export_target() {
+            ExportTarget::None | ExportTarget::Unknown(_) if !options.export_all => {
+                IncludeStatus::NotExported
+            }
+            _ if !has_data(include) => IncludeStatus::NoData,
+            _ => match include_path(include, dir, options) {
+                None => IncludeStatus::UnsafePath,
+                Some(path) if path.exists() && !include.overwrite => IncludeStatus::Kept(path),
+                Some(path) => {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&path, &include.data)?;
+                    IncludeStatus::Written(path)
+                }
+            },
+        };
+        results.push(IncludeResult { include, status });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_paths_are_kept() {
+        assert_eq!(
+            safe_relative_path("data/levels\\one.txt"),
+            Some(["data", "levels", "one.txt"].iter().collect())
+        );
+        assert_eq!(safe_relative_path("./a//b"), Some(PathBuf::from("a/b")));
+        assert_eq!(safe_relative_path(""), Some(PathBuf::new()));
+    }
+
+    #[test]
+    fn parent_components_are_rejected() {
+        assert_eq!(safe_relative_path(".."), None);
+        assert_eq!(safe_relative_path("../x"), None);
+        assert_eq!(safe_relative_path("a/../../x"), None);
+        assert_eq!(safe_relative_path("a\\..\\x"), None);
+    }
+
+    #[test]
+    fn absolute_paths_stay_relative() {
+        assert_eq!(
+            safe_relative_path("/etc/passwd"),
+            Some(PathBuf::from("etc/passwd"))
+        );
+        assert_eq!(
+            safe_relative_path("C:\\Windows\\x.dll"),
+            Some(PathBuf::from("Windows/x.dll"))
+        );
+        assert_eq!(
+            safe_relative_path("\\\\server\\share"),
+            Some(PathBuf::from("server/share"))
+        );
+        assert_eq!(safe_relative_path("a/C:b"), None);
+    }
+
+    fn stored(name: &str, export: ExportTarget, data: &[u8]) -> Include {
+        Include {
+            name: name.to_string(),
+            original_chosen: true,
+            store_in_editable: true,
+            data: data.to_vec(),
+            export: match export {
+                ExportTarget::None => 0,
+                ExportTarget::TempDirectory => 1,
+                ExportTarget::GameDirectory => 2,
+                ExportTarget::CustomFolder => 3,
+                ExportTarget::Unknown(value) => value,
+            },
+            ..Include::default()
+        }
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "gm_reader_includes_{}_{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn statuses(results: &[IncludeResult]) -> Vec<IncludeStatus> {
+        results.iter().map(|r| r.status.clone()).collect()
+    }
+
+    #[test]
+    fn the_editable_flags_decide_whether_an_include_is_stored() {
+        let mut include = Include::default();
+        assert!(!has_data(&include));
+        include.original_chosen = true;
+        assert!(!has_data(&include));
+        include.store_in_editable = true;
+        assert!(has_data(&include));
+    }
+
+    #[test]
+    fn includes_are_written_by_export_target() {
+        let dir = scratch_dir("targets");
+        let mut custom = stored("c.txt", ExportTarget::CustomFolder, b"c");
+        custom.export_folder = "C:\\levels\\one".to_string();
+        let game = Game {
+            includes: vec![
+                stored("a.txt", ExportTarget::TempDirectory, b"a"),
+                stored("b.txt", ExportTarget::GameDirectory, b""),
+                custom,
+                stored("d.txt", ExportTarget::None, b"d"),
+            ],
+            ..Game::default()
+        };
+        let results = extract_includes(&game, &dir, &IncludeOptions::default()).unwrap();
+        let custom_path = dir.join("custom").join("levels").join("one").join("c.txt");
+        assert_eq!(
+            statuses(&results),
+            vec![
+                IncludeStatus::Written(dir.join("temp").join("a.txt")),
+                IncludeStatus::Written(dir.join("game").join("b.txt")),
+                IncludeStatus::Written(custom_path.clone()),
+                IncludeStatus::NotExported,
+            ]
+        );
+        assert_eq!(fs::read(dir.join("temp").join("a.txt")).unwrap(), b"a");
+        assert_eq!(fs::read(dir.join("game").join("b.txt")).unwrap(), b"");
+        assert_eq!(fs::read(custom_path).unwrap(), b"c");
+        assert!(!dir.join("game").join("d.txt").exists());
+
+        let options = IncludeOptions {
+            export_all: true,
+            ..IncludeOptions::default()
+        };
+        let results = extract_includes(&game, &dir, &options).unwrap();
+        assert_eq!(
+            results[3].status,
+            IncludeStatus::Written(dir.join("game").join("d.txt"))
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn existing_files_are_only_replaced_when_overwriting() {
+        let dir = scratch_dir("overwrite");
+        let mut second = stored("a.txt", ExportTarget::GameDirectory, b"second");
+        let game = Game {
+            includes: vec![
+                stored("a.txt", ExportTarget::GameDirectory, b"first"),
+                stored("a.txt", ExportTarget::GameDirectory, b"kept"),
+            ],
+            ..Game::default()
+        };
+        let path = dir.join("game").join("a.txt");
+        let results = extract_includes(&game, &dir, &IncludeOptions::default()).unwrap();
+        assert_eq!(
+            statuses(&results),
+            vec![
+                IncludeStatus::Written(path.clone()),
+                IncludeStatus::Kept(path.clone()),
+            ]
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+
+        second.overwrite = true;
+        let game = Game {
+            includes: vec![second],
+            ..Game::default()
+        };
+        let results = extract_includes(&game, &dir, &IncludeOptions::default()).unwrap();
+        assert_eq!(
+            statuses(&results),
+            vec![IncludeStatus::Written(path.clone())]
+        );
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unsafe_and_unstored_includes_are_not_written() {
+        let dir = scratch_dir("unsafe");
+        let mut escaping_folder = stored("x.txt", ExportTarget::CustomFolder, b"x");
+        escaping_folder.export_folder = "a/../..".to_string();
+        let mut not_stored = stored("y.txt", ExportTarget::GameDirectory, b"");
+        not_stored.store_in_editable = false;
+        let game = Game {
+            includes: vec![
+                stored("../x.txt", ExportTarget::GameDirectory, b"x"),
+                stored("sub/x.txt", ExportTarget::GameDirectory, b"x"),
+                stored("", ExportTarget::TempDirectory, b"x"),
+                escaping_folder,
+                not_stored,
+            ],
+            ..Game::default()
+        };
+        let results = extract_includes(&game, &dir, &IncludeOptions::default()).unwrap();
+        assert_eq!(
+            statuses(&results),
+            vec![
+                IncludeStatus::UnsafePath,
+                IncludeStatus::UnsafePath,
+                IncludeStatus::UnsafePath,
+                IncludeStatus::UnsafePath,
+                IncludeStatus::NoData,
+            ]
+        );
+        assert_eq!(
+            missing_data(&game).map(|i| &i.name[..]).collect::<Vec<_>>(),
+            vec!["y.txt"]
+        );
+        assert!(!dir.exists());
+        assert!(!std::env::temp_dir().join("x.txt").exists());
+    }
+}
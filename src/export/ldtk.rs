@@ -0,0 +1,891 @@
+use crate::export::{file_name, write_backgrounds, write_json};
+use crate::game::{Background, BackgroundId, Game, Object, ObjectId, Room, RoomTile, Sprite};
+use crate::render::color_to_rgba;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+const LDTK_VERSION: &str = "1.5.3";
+const ENTITY_GRID_SIZE: u32 = 16;
+// Horizontal gap between rooms laid out in the world view.
+const WORLD_SPACING: u32 = 64;
+
+#[derive(Debug, Clone)]
+pub struct LdtkOptions {
+    // Directory, relative to the project, that background images are referenced from.
+    pub image_dir: String,
+}
+
+impl Default for LdtkOptions {
+    fn default() -> Self {
+        LdtkOptions {
+            image_dir: "backgrounds".to_string(),
+        }
+    }
+}
+
+// LDtk wants UUIDs for instance identifiers. These are derived from the game ID so that exporting
+// the same game twice gives the same project.
+struct Iids {
+    seed: u64,
+    counter: u64,
+}
+
+impl Iids {
+    fn next(&mut self) -> String {
+        self.counter += 1;
+        // splitmix64
+        let mix = |mut z: u64| {
+            z = z
+                .wrapping_add(self.seed)
+                .wrapping_add(0x9E37_79B9_7F4A_7C15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        let high = mix(self.counter * 2);
+        let low = mix(self.counter * 2 + 1);
+        format!(
+            "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xFFFF,
+            high & 0x0FFF,
+            0x8000 | ((low >> 48) & 0x3FFF),
+            low & 0xFFFF_FFFF_FFFF
+        )
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// LDtk identifiers must start with a letter and contain only letters, digits and underscores.
+fn identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !identifier.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        identifier.insert(0, 'R');
+    }
+    identifier
+}
+
+fn hex_color(color: u32) -> String {
+    let color = color_to_rgba(color);
+    format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2])
+}
+
+fn field_def(uid: u32, name: &str, kind: &str, field_type: &str) -> Value {
+    json!({
+        "identifier": name,
+        "doc": null,
+        "__type": kind,
+        "uid": uid,
+        "type": field_type,
+        "isArray": false,
+        "canBeNull": true,
+        "arrayMinLength": null,
+        "arrayMaxLength": null,
+        "editorDisplayMode": "Hidden",
+        "editorDisplayScale": 1,
+        "editorDisplayPos": "Above",
+        "editorLinkStyle": "StraightArrow",
+        "editorDisplayColor": null,
+        "editorAlwaysShow": false,
+        "editorShowInWorld": true,
+        "editorCutLongValues": true,
+        "editorTextSuffix": null,
+        "editorTextPrefix": null,
+        "useForSmartColor": false,
+        "exportToToc": false,
+        "searchable": false,
+        "min": null,
+        "max": null,
+        "regex": null,
+        "acceptFileTypes": null,
+        "defaultOverride": null,
+        "textLanguageMode": null,
+        "symmetricalRef": false,
+        "autoChainRef": true,
+        "allowOutOfLevelRef": true,
+        "allowedRefs": "OnlySame",
+        "allowedRefsEntityUid": null,
+        "allowedRefTags": [],
+        "tilesetUid": null,
+    })
+}
+
+fn field_instance(def_uid: u32, name: &str, kind: &str, value: Value) -> Value {
+    let editor_value = match kind {
+        "Int" => json!({ "id": "V_Int", "params": [value] }),
+        "Bool" => json!({ "id": "V_Bool", "params": [value] }),
+        _ => json!({ "id": "V_String", "params": [value] }),
+    };
+    json!({
+        "__identifier": name,
+        "__type": kind,
+        "__value": value,
+        "__tile": null,
+        "defUid": def_uid,
+        "realEditorValues": [editor_value],
+    })
+}
+
+fn entity_def(
+    uid: u32,
+    name: &str,
+    size: (u32, u32),
+    pivot: (f64, f64),
+    resizable: bool,
+    field_defs: Vec<Value>,
+) -> Value {
+    json!({
+        "identifier": name,
+        "uid": uid,
+        "tags": [],
+        "exportToToc": false,
+        "allowOutOfBounds": true,
+        "doc": null,
+        "width": size.0,
+        "height": size.1,
+        "resizableX": resizable,
+        "resizableY": resizable,
+        "minWidth": null,
+        "maxWidth": null,
+        "minHeight": null,
+        "maxHeight": null,
+        "keepAspectRatio": false,
+        "tileOpacity": 1,
+        "fillOpacity": 0.08,
+        "lineOpacity": 1,
+        "hollow": false,
+        "color": "#94D9B3",
+        "renderMode": "Rectangle",
+        "showName": true,
+        "tilesetId": null,
+        "tileRenderMode": "FitInside",
+        "tileRect": null,
+        "uiTileRect": null,
+        "nineSliceBorders": [],
+        "maxCount": 0,
+        "limitScope": "PerLevel",
+        "limitBehavior": "MoveLastOne",
+        "pivotX": pivot.0,
+        "pivotY": pivot.1,
+        "fieldDefs": field_defs,
+    })
+}
+
+fn layer_def(uid: u32, name: &str, kind: &str, grid_size: u32, tileset: Option<u32>) -> Value {
+    json!({
+        "__type": kind,
+        "identifier": name,
+        "type": kind,
+        "uid": uid,
+        "doc": null,
+        "uiColor": null,
+        "gridSize": grid_size,
+        "guideGridWid": 0,
+        "guideGridHei": 0,
+        "displayOpacity": 1,
+        "inactiveOpacity": 1,
+        "hideInList": false,
+        "hideFieldsWhenInactive": false,
+        "canSelectWhenInactive": true,
+        "renderInWorldView": true,
+        "pxOffsetX": 0,
+        "pxOffsetY": 0,
+        "parallaxFactorX": 0,
+        "parallaxFactorY": 0,
+        "parallaxScaling": true,
+        "requiredTags": [],
+        "excludedTags": [],
+        "autoTilesKilledByOtherLayerUid": null,
+        "uiFilterTags": [],
+        "useAsyncRender": false,
+        "intGridValues": [],
+        "intGridValuesGroups": [],
+        "autoRuleGroups": [],
+        "autoSourceLayerDefUid": null,
+        "tilesetDefUid": tileset,
+        "tilePivotX": 0,
+        "tilePivotY": 0,
+        "biomeFieldUid": null,
+    })
+}
+
+struct TilesetDef<'a> {
+    uid: u32,
+    background: &'a Background,
+    grid_size: u32,
+}
+
+struct EntityDef<'a> {
+    uid: u32,
+    object: &'a Object,
+    sprite: Option<&'a Sprite>,
+    creation_code_uid: u32,
+    instance_id_uid: u32,
+}
+
+impl EntityDef<'_> {
+    fn size(&self) -> (u32, u32) {
+        match self.sprite.and_then(|s| s.frames.first()) {
+            Some(frame) if frame.width > 0 && frame.height > 0 => (frame.width, frame.height),
+            _ => (ENTITY_GRID_SIZE, ENTITY_GRID_SIZE),
+        }
+    }
+
+    fn pivot(&self) -> (f64, f64) {
+        let (width, height) = self.size();
+        let origin = self.sprite.map(|s| s.origin).unwrap_or((0, 0));
+        (
+            (f64::from(origin.0) / f64::from(width)).clamp(0.0, 1.0),
+            (f64::from(origin.1) / f64::from(height)).clamp(0.0, 1.0),
+        )
+    }
+}
+
+// Tile cells outside their room can't be stored in a tile layer, so they're kept as instances of
+// this entity instead.
+struct TileEntityDef {
+    uid: u32,
+    background_uid: u32,
+    src_x_uid: u32,
+    src_y_uid: u32,
+    depth_uid: u32,
+}
+
+// The grid cells a tile covers, as a position in the room and a column and row in the tileset.
+// Parts of the tile outside the background image are left out.
+fn tile_cells(tile: &RoomTile, tileset: &TilesetDef) -> Vec<((i32, i32), (u32, u32))> {
+    let grid = tileset.grid_size;
+    let columns = tileset.background.image.width / grid;
+    let rows = tileset.background.image.height / grid;
+    let first_column = tile.tile_x.max(0) as u32 / grid;
+    let first_row = tile.tile_y.max(0) as u32 / grid;
+    let mut cells = vec![];
+    for j in 0..tile.height / grid {
+        for i in 0..tile.width / grid {
+            let (column, row) = (first_column + i, first_row + j);
+            if column < columns && row < rows {
+                let x = tile.x + (i * grid) as i32;
+                let y = tile.y + (j * grid) as i32;
+                cells.push(((x, y), (column, row)));
+            }
+        }
+    }
+    cells
+}
+
+// The tile layer cell at (x, y), or `None` if it's outside the room.
+fn layer_cell(room: &Room, grid: u32, x: i32, y: i32) -> Option<u32> {
+    if x < 0 || y < 0 {
+        return None;
+    }
+    let (column, row) = (x as u32 / grid, y as u32 / grid);
+    let columns = room.width.div_ceil(grid);
+    if column < columns && row < room.height.div_ceil(grid) {
+        Some(row * columns + column)
+    } else {
+        None
+    }
+}
+
+// A tile cell outside the room, as an instance of the tile entity.
+fn stray_tile(
+    entity: &TileEntityDef,
+    tileset: &TilesetDef,
+    tile: &RoomTile,
+    position: (i32, i32),
+    source: (u32, u32),
+    iid: String,
+    world_x: u32,
+) -> Value {
+    let grid = tileset.grid_size;
+    json!({
+        "__identifier": "Tile",
+        "__grid": [
+            position.0.div_euclid(ENTITY_GRID_SIZE as i32),
+            position.1.div_euclid(ENTITY_GRID_SIZE as i32),
+        ],
+        "__pivot": [0, 0],
+        "__tags": [],
+        "__tile": {
+            "tilesetUid": tileset.uid,
+            "x": source.0,
+            "y": source.1,
+            "w": grid,
+            "h": grid,
+        },
+        "__smartColor": "#94D9B3",
+        "__worldX": world_x as i32 + position.0,
+        "__worldY": position.1,
+        "iid": iid,
+        "width": grid,
+        "height": grid,
+        "defUid": entity.uid,
+        "px": [position.0, position.1],
+        "fieldInstances": [
+            field_instance(
+                entity.background_uid,
+                "background",
+                "String",
+                json!(tileset.background.name),
+            ),
+            field_instance(entity.src_x_uid, "src_x", "Int", json!(source.0)),
+            field_instance(entity.src_y_uid, "src_y", "Int", json!(source.1)),
+            field_instance(entity.depth_uid, "depth", "Int", json!(tile.depth)),
+        ],
+    })
+}
+
+// Rooms in the order the game visits them, followed by any rooms missing from the room order.
+fn ordered_rooms(game: &Game) -> Vec<&Room> {
+    let mut rooms: Vec<&Room> = game
+        .room_order
+        .iter()
+        .filter_map(|&id| game.room(id))
+        .collect();
+    for room in &game.rooms {
+        if !rooms.iter().any(|r| r.id == room.id) {
+            rooms.push(room);
+        }
+    }
+    rooms
+}
+
+pub fn game_to_ldtk(game: &Game, options: &LdtkOptions) -> Value {
+    let mut iids = Iids {
+        seed: u64::from(game.game_id),
+        counter: 0,
+    };
+    let mut next_uid = 1;
+    let mut uid = || {
+        next_uid += 1;
+        next_uid - 1
+    };
+    let rooms = ordered_rooms(game);
+
+    // Each background used by a tile becomes a tileset, with a grid size that evenly divides
+    // every tile cut from it and every position it's placed at, so all tiles line up with the grid.
+    let mut grid_sizes: BTreeMap<Option<BackgroundId>, u32> = BTreeMap::new();
+    for tile in rooms.iter().flat_map(|room| &room.tiles) {
+        let size = grid_sizes.entry(tile.background).or_insert(0);
+        for &value in &[
+            tile.width,
+            tile.height,
+            tile.tile_x.max(0) as u32,
+            tile.tile_y.max(0) as u32,
+            tile.x.unsigned_abs(),
+            tile.y.unsigned_abs(),
+        ] {
+            *size = gcd(*size, value);
+        }
+    }
+    let mut tilesets: BTreeMap<Option<BackgroundId>, TilesetDef> = BTreeMap::new();
+    for (&id, &grid_size) in &grid_sizes {
+        if let Some(background) = game.background(id) {
+            if grid_size > 0 && background.image.width > 0 && background.image.height > 0 {
+                tilesets.insert(
+                    id,
+                    TilesetDef {
+                        uid: uid(),
+                        background,
+                        grid_size,
+                    },
+                );
+            }
+        }
+    }
+
+    // One tile layer for each (depth, background) pair, since an LDtk tile layer draws from a
+    // single tileset. Lower depths are drawn on top, and LDtk lists the top layer first.
+    let mut tile_layers: BTreeMap<(i32, Option<BackgroundId>), u32> = BTreeMap::new();
+    for tile in rooms.iter().flat_map(|room| &room.tiles) {
+        if tilesets.contains_key(&tile.background) {
+            tile_layers
+                .entry((tile.depth, tile.background))
+                .or_insert(0);
+        }
+    }
+    for layer_uid in tile_layers.values_mut() {
+        *layer_uid = uid();
+    }
+    let entity_layer_uid = uid();
+
+    let mut entities: BTreeMap<Option<ObjectId>, EntityDef> = BTreeMap::new();
+    for instance in rooms.iter().flat_map(|room| &room.instances) {
+        if entities.contains_key(&instance.object) {
+            continue;
+        }
+        if let Some(object) = game.object(instance.object) {
+            entities.insert(
+                instance.object,
+                EntityDef {
+                    uid: uid(),
+                    object,
+                    sprite: game.sprite(object.sprite),
+                    creation_code_uid: uid(),
+                    instance_id_uid: uid(),
+                },
+            );
+        }
+    }
+
+    let stray_tiles = rooms.iter().any(|room| {
+        room.tiles
+            .iter()
+            .any(|tile| match tilesets.get(&tile.background) {
+                Some(tileset) => tile_cells(tile, tileset)
+                    .iter()
+                    .any(|&((x, y), _)| layer_cell(room, tileset.grid_size, x, y).is_none()),
+                None => false,
+            })
+    });
+    let tile_entity = if stray_tiles {
+        Some(TileEntityDef {
+            uid: uid(),
+            background_uid: uid(),
+            src_x_uid: uid(),
+            src_y_uid: uid(),
+            depth_uid: uid(),
+        })
+    } else {
+        None
+    };
+
+    let level_fields = [
+        (uid(), "caption", "String", "F_String"),
+        (uid(), "speed", "Int", "F_Int"),
+        (uid(), "persistent", "Bool", "F_Bool"),
+        (uid(), "creation_code", "String", "F_Text"),
+    ];
+
+    let image_path = |background: &Background| {
+        if options.image_dir.is_empty() {
+            format!("{}.png", file_name(&background.name))
+        } else {
+            format!("{}/{}.png", options.image_dir, file_name(&background.name))
+        }
+    };
+    let tile_layer_name = |depth: i32, background: Option<BackgroundId>| {
+        let name = &tilesets[&background].background.name;
+        identifier(&format!(
+            "Tiles_{}_{}",
+            depth.to_string().replace('-', "m"),
+            name
+        ))
+    };
+
+    let mut layer_defs = vec![layer_def(
+        entity_layer_uid,
+        "Instances",
+        "Entities",
+        ENTITY_GRID_SIZE,
+        None,
+    )];
+    for (&(depth, background), &layer_uid) in &tile_layers {
+        let tileset = &tilesets[&background];
+        layer_defs.push(layer_def(
+            layer_uid,
+            &tile_layer_name(depth, background),
+            "Tiles",
+            tileset.grid_size,
+            Some(tileset.uid),
+        ));
+    }
+
+    let tileset_defs: Vec<Value> = tilesets
+        .values()
+        .map(|tileset| {
+            let image = &tileset.background.image;
+            json!({
+                "__cWid": image.width / tileset.grid_size,
+                "__cHei": image.height / tileset.grid_size,
+                "identifier": identifier(&tileset.background.name),
+                "uid": tileset.uid,
+                "relPath": image_path(tileset.background),
+                "embedAtlas": null,
+                "pxWid": image.width,
+                "pxHei": image.height,
+                "tileGridSize": tileset.grid_size,
+                "spacing": 0,
+                "padding": 0,
+                "tags": [],
+                "tagsSourceEnumUid": null,
+                "enumTags": [],
+                "customData": [],
+                "savedSelections": [],
+                "cachedPixelData": null,
+            })
+        })
+        .collect();
+
+    let mut entity_defs: Vec<Value> = entities
+        .values()
+        .map(|entity| {
+            entity_def(
+                entity.uid,
+                &identifier(&entity.object.name),
+                entity.size(),
+                entity.pivot(),
+                false,
+                vec![
+                    field_def(
+                        entity.creation_code_uid,
+                        "creation_code",
+                        "String",
+                        "F_Text",
+                    ),
+                    field_def(entity.instance_id_uid, "instance_id", "Int", "F_Int"),
+                ],
+            )
+        })
+        .collect();
+    if let Some(tile) = &tile_entity {
+        entity_defs.push(entity_def(
+            tile.uid,
+            "Tile",
+            (ENTITY_GRID_SIZE, ENTITY_GRID_SIZE),
+            (0.0, 0.0),
+            true,
+            vec![
+                field_def(tile.background_uid, "background", "String", "F_String"),
+                field_def(tile.src_x_uid, "src_x", "Int", "F_Int"),
+                field_def(tile.src_y_uid, "src_y", "Int", "F_Int"),
+                field_def(tile.depth_uid, "depth", "Int", "F_Int"),
+            ],
+        ));
+    }
+
+    let mut levels = vec![];
+    let mut world_x = 0;
+    for room in &rooms {
+        let level_uid = uid();
+        let level_iid = iids.next();
+
+        let mut entity_instances: Vec<Value> = room
+            .instances
+            .iter()
+            .filter_map(|instance| {
+                let entity = entities.get(&instance.object)?;
+                let (width, height) = entity.size();
+                let (pivot_x, pivot_y) = entity.pivot();
+                Some(json!({
+                    "__identifier": identifier(&entity.object.name),
+                    "__grid": [
+                        instance.x.div_euclid(ENTITY_GRID_SIZE as i32),
+                        instance.y.div_euclid(ENTITY_GRID_SIZE as i32),
+                    ],
+                    "__pivot": [pivot_x, pivot_y],
+                    "__tags": [],
+                    "__tile": null,
+                    "__smartColor": "#94D9B3",
+                    "__worldX": world_x as i32 + instance.x,
+                    "__worldY": instance.y,
+                    "iid": iids.next(),
+                    "width": width,
+                    "height": height,
+                    "defUid": entity.uid,
+                    "px": [instance.x, instance.y],
+                    "fieldInstances": [
+                        field_instance(
+                            entity.creation_code_uid,
+                            "creation_code",
+                            "String",
+                            json!(instance.creation_code),
+                        ),
+                        field_instance(
+                            entity.instance_id_uid,
+                            "instance_id",
+                            "Int",
+                            json!(instance.id),
+                        ),
+                    ],
+                }))
+            })
+            .collect();
+
+        // Tile layers are built before the instances layer, which also gets the tile cells that
+        // are outside the room.
+        let mut tile_layer_instances = vec![];
+        for (&(depth, background), &layer_uid) in &tile_layers {
+            let tileset = &tilesets[&background];
+            let grid = tileset.grid_size;
+            let columns = tileset.background.image.width / grid;
+            let layer_columns = room.width.div_ceil(grid);
+
+            let mut grid_tiles = vec![];
+            for tile in room
+                .tiles
+                .iter()
+                .filter(|t| t.depth == depth && t.background == background)
+            {
+                for ((x, y), (column, row)) in tile_cells(tile, tileset) {
+                    let cell = match layer_cell(room, grid, x, y) {
+                        Some(cell) => cell,
+                        None => {
+                            if let Some(entity) = &tile_entity {
+                                entity_instances.push(stray_tile(
+                                    entity,
+                                    tileset,
+                                    tile,
+                                    (x, y),
+                                    (column * grid, row * grid),
+                                    iids.next(),
+                                    world_x,
+                                ));
+                            }
+                            continue;
+                        }
+                    };
+                    grid_tiles.push(json!({
+                        "px": [x, y],
+                        "src": [column * grid, row * grid],
+                        "f": 0,
+                        "t": row * columns + column,
+                        "d": [cell],
+                        "a": 1,
+                    }));
+                }
+            }
+
+            tile_layer_instances.push(json!({
+                "__identifier": tile_layer_name(depth, background),
+                "__type": "Tiles",
+                "__cWid": layer_columns,
+                "__cHei": room.height.div_ceil(grid),
+                "__gridSize": grid,
+                "__opacity": 1,
+                "__pxTotalOffsetX": 0,
+                "__pxTotalOffsetY": 0,
+                "__tilesetDefUid": tileset.uid,
+                "__tilesetRelPath": image_path(tileset.background),
+                "iid": iids.next(),
+                "levelId": level_uid,
+                "layerDefUid": layer_uid,
+                "pxOffsetX": 0,
+                "pxOffsetY": 0,
+                "visible": true,
+                "optionalRules": [],
+                "intGridCsv": [],
+                "autoLayerTiles": [],
+                "seed": 0,
+                "overrideTilesetUid": null,
+                "gridTiles": grid_tiles,
+                "entityInstances": [],
+            }));
+        }
+
+        let entity_grid = (
+            room.width.div_ceil(ENTITY_GRID_SIZE),
+            room.height.div_ceil(ENTITY_GRID_SIZE),
+        );
+        let mut layer_instances = vec![json!({
+            "__identifier": "Instances",
+            "__type": "Entities",
+            "__cWid": entity_grid.0,
+            "__cHei": entity_grid.1,
+            "__gridSize": ENTITY_GRID_SIZE,
+            "__opacity": 1,
+            "__pxTotalOffsetX": 0,
+            "__pxTotalOffsetY": 0,
+            "__tilesetDefUid": null,
+            "__tilesetRelPath": null,
+            "iid": iids.next(),
+            "levelId": level_uid,
+            "layerDefUid": entity_layer_uid,
+            "pxOffsetX": 0,
+            "pxOffsetY": 0,
+            "visible": true,
+            "optionalRules": [],
+            "intGridCsv": [],
+            "autoLayerTiles": [],
+            "seed": 0,
+            "overrideTilesetUid": null,
+            "gridTiles": [],
+            "entityInstances": entity_instances,
+        })];
+        layer_instances.extend(tile_layer_instances);
+
+        let field_values = [
+            json!(room.caption),
+            json!(room.speed),
+            json!(room.persistent),
+            json!(room.creation_code),
+        ];
+        let field_instances: Vec<Value> = level_fields
+            .iter()
+            .zip(field_values.iter())
+            .map(|(&(uid, name, kind, _), value)| field_instance(uid, name, kind, value.clone()))
+            .collect();
+
+        levels.push(json!({
+            "identifier": identifier(&room.name),
+            "iid": level_iid,
+            "uid": level_uid,
+            "worldX": world_x,
+            "worldY": 0,
+            "worldDepth": 0,
+            "pxWid": room.width,
+            "pxHei": room.height,
+            "__bgColor": hex_color(room.clear_color),
+            "bgColor": hex_color(room.clear_color),
+            "useAutoIdentifier": false,
+            "bgRelPath": null,
+            "bgPos": null,
+            "bgPivotX": 0.5,
+            "bgPivotY": 0.5,
+            "__smartColor": "#ADADB5",
+            "__bgPos": null,
+            "externalRelPath": null,
+            "fieldInstances": field_instances,
+            "layerInstances": layer_instances,
+            "__neighbours": [],
+        }));
+        world_x += room.width + WORLD_SPACING;
+    }
+
+    let level_field_defs: Vec<Value> = level_fields
+        .iter()
+        .map(|&(uid, name, kind, field_type)| field_def(uid, name, kind, field_type))
+        .collect();
+
+    json!({
+        "__header__": {
+            "fileType": "LDtk Project JSON",
+            "app": "LDtk",
+            "doc": "https://ldtk.io/json",
+            "schema": "https://ldtk.io/files/JSON_SCHEMA.json",
+            "appAuthor": "Sebastien 'deepnight' Benard",
+            "appVersion": LDTK_VERSION,
+            "url": "https://ldtk.io",
+        },
+        "iid": iids.next(),
+        "jsonVersion": LDTK_VERSION,
+        "appBuildId": 473703,
+        "nextUid": uid(),
+        "identifierStyle": "Free",
+        "toc": [],
+        "worldLayout": "LinearHorizontal",
+        "worldGridWidth": 256,
+        "worldGridHeight": 256,
+        "defaultLevelWidth": 640,
+        "defaultLevelHeight": 480,
+        "defaultPivotX": 0,
+        "defaultPivotY": 0,
+        "defaultGridSize": ENTITY_GRID_SIZE,
+        "defaultEntityWidth": ENTITY_GRID_SIZE,
+        "defaultEntityHeight": ENTITY_GRID_SIZE,
+        "bgColor": "#40465B",
+        "defaultLevelBgColor": hex_color(game.settings.background_color),
+        "minifyJson": false,
+        "externalLevels": false,
+        "exportTiled": false,
+        "simplifiedExport": false,
+        "imageExportMode": "None",
+        "exportLevelBg": true,
+        "pngFilePattern": null,
+        "backupOnSave": false,
+        "backupLimit": 10,
+        "backupRelPath": null,
+        "levelNamePattern": "Level_%idx",
+        "tutorialDesc": null,
+        "customCommands": [],
+        "flags": [],
+        "defs": {
+            "layers": layer_defs,
+            "entities": entity_defs,
+            "tilesets": tileset_defs,
+            "enums": [],
+            "externalEnums": [],
+            "levelFields": level_field_defs,
+        },
+        "levels": levels,
+        "worlds": [],
+        "dummyWorldIid": iids.next(),
+    })
+}
+
+// Writes the project to `path`, along with the background images its tilesets use.
+pub fn write_ldtk<P: AsRef<Path>>(game: &Game, path: P, options: &LdtkOptions) -> io::Result<()> {
+    let path = path.as_ref();
+    let image_dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(&options.image_dir);
+    let used = game
+        .rooms
+        .iter()
+        .flat_map(|room| room.tiles.iter().filter_map(|t| t.background));
+    write_backgrounds(game, used, &image_dir)?;
+    write_json(&game_to_ldtk(game, options), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+
+    fn tile(x: i32, y: i32) -> RoomTile {
+        RoomTile {
+            x,
+            y,
+            background: Some(BackgroundId(0)),
+            width: 8,
+            height: 8,
+            ..RoomTile::default()
+        }
+    }
+
+    #[test]
+    fn tiles_outside_the_grid_or_room_keep_their_position() {
+        let mut game = Game::default();
+        game.backgrounds.push(Background {
+            id: BackgroundId(0),
+            name: "bg_tiles".to_string(),
+            image: RgbaImage::new(16, 16).into(),
+        });
+        game.rooms.push(Room {
+            name: "rm_test".to_string(),
+            width: 16,
+            height: 16,
+            // Off the 8 pixel grid, left of the room and right of the room.
+            tiles: vec![tile(4, 8), tile(-8, 0), tile(16, 0)],
+            ..Room::default()
+        });
+
+        let project = game_to_ldtk(&game, &LdtkOptions::default());
+        let layers = &project["levels"][0]["layerInstances"];
+        let tiles = layers[1]["gridTiles"].as_array().unwrap();
+        assert_eq!(layers[1]["__gridSize"], 4);
+        let cells: Vec<_> = tiles.iter().map(|t| (&t["px"], &t["d"])).collect();
+        assert_eq!(
+            cells,
+            vec![
+                (&json!([4, 8]), &json!([9])),
+                (&json!([8, 8]), &json!([10])),
+                (&json!([4, 12]), &json!([13])),
+                (&json!([8, 12]), &json!([14])),
+            ]
+        );
+        let stray: Vec<_> = layers[0]["entityInstances"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|e| e["__identifier"] == "Tile")
+            .map(|e| &e["px"])
+            .collect();
+        assert_eq!(stray.len(), 8);
+        assert!(stray.contains(&&json!([-8, 0])));
+        assert!(stray.contains(&&json!([20, 4])));
+    }
+}
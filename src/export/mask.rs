@@ -0,0 +1,88 @@
+use crate::export::sprite::frame_mask;
+use crate::export::{compress_scanlines, write_png_chunk, PNG_SIGNATURE};
+use crate::game::{Sprite, SpriteMask};
+use image::{Pixel, Rgba, RgbaImage};
+use std::io;
+use std::io::Write;
+
+#[derive(Debug, Clone)]
+pub struct OverlayOptions {
+    // Blended over every pixel covered by the mask, so the alpha controls the strength of the tint.
+    pub mask_color: Rgba<u8>,
+    pub bbox_color: Option<Rgba<u8>>,
+}
+
+impl Default for OverlayOptions {
+    fn default() -> Self {
+        OverlayOptions {
+            mask_color: Rgba([255, 0, 0, 128]),
+            bbox_color: Some(Rgba([0, 255, 0, 255])),
+        }
+    }
+}
+
+// Writes the mask as a 1-bit grayscale PNG, with set pixels in white.
+pub fn write_mask_png<W: Write>(mask: &SpriteMask, mut w: W) -> io::Result<()> {
+    let (width, height) = mask.size;
+    if width == 0 || height == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot write an empty mask",
+        ));
+    }
+
+    let stride = (width as usize).div_ceil(8);
+    let mut packed = vec![0u8; stride * height as usize];
+    for y in 0..height {
+        for x in 0..width {
+            if mask.get(x, y) {
+                packed[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+
+    w.write_all(PNG_SIGNATURE)?;
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 1-bit grayscale, deflate, no filtering, no interlacing.
+    header.extend_from_slice(&[1, 0, 0, 0, 0]);
+    write_png_chunk(&mut w, b"IHDR", &header)?;
+    write_png_chunk(&mut w, b"IDAT", &compress_scanlines(&packed, stride)?)?;
+    write_png_chunk(&mut w, b"IEND", &[])?;
+    w.flush()
+}
+
+// Draws the collision mask and its bounding box over the given frame of the sprite.
+pub fn mask_overlay(sprite: &Sprite, frame: usize, options: &OverlayOptions) -> Option<RgbaImage> {
+    let mut image = RgbaImage::from(sprite.frames.get(frame)?);
+    let mask = match frame_mask(sprite, frame) {
+        Some(mask) => mask,
+        None => return Some(image),
+    };
+
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        if mask.get(x, y) {
+            pixel.blend(&options.mask_color);
+        }
+    }
+
+    if let Some(color) = options.bbox_color {
+        if mask.left <= mask.right && mask.top <= mask.bottom {
+            let mut plot = |x: i32, y: i32| {
+                if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+                    image.put_pixel(x as u32, y as u32, color);
+                }
+            };
+            for x in mask.left..=mask.right {
+                plot(x, mask.top);
+                plot(x, mask.bottom);
+            }
+            for y in mask.top..=mask.bottom {
+                plot(mask.left, y);
+                plot(mask.right, y);
+            }
+        }
+    }
+    Some(image)
+}
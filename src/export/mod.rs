@@ -0,0 +1,123 @@
+pub mod extract;
+pub mod includes;
+pub mod ldtk;
+pub mod mask;
+pub mod sprite;
+pub mod svg;
+pub mod tiled;
+
+use crate::game::{BackgroundId, Game};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use image::RgbaImage;
+use serde::Serialize;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn image_error(err: image::ImageError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+pub fn write_png<P: AsRef<Path>>(image: &RgbaImage, path: P) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    image::png::PngEncoder::new(file)
+        .encode(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            image::ColorType::Rgba8,
+        )
+        .map_err(image_error)
+}
+
+pub fn write_json<T: Serialize, P: AsRef<Path>>(value: &T, path: P) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut file, value)?;
+    file.flush()
+}
+
+fn write_png_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut crc_data = Vec::with_capacity(data.len() + 4);
+    crc_data.extend_from_slice(kind);
+    crc_data.extend_from_slice(data);
+
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(&crc_data)?;
+    w.write_all(&crc::crc32::checksum_ieee(&crc_data).to_be_bytes())
+}
+
+fn compress_scanlines(data: &[u8], stride: usize) -> io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in data.chunks(stride) {
+        // Filter type 0 (none).
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    encoder.finish()
+}
+
+// Resource names come from the game file, so make sure they can't escape the directory they're
+// written to.
+pub fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim_end_matches(['.', ' ']);
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        "_".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Writes each of the given backgrounds to `dir` as `<file name>.png`, skipping duplicates and
+// backgrounds without an image.
+fn write_backgrounds<I: IntoIterator<Item = BackgroundId>>(
+    game: &Game,
+    ids: I,
+    dir: &Path,
+) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut written = vec![];
+    for id in ids {
+        if written.contains(&id) {
+            continue;
+        }
+        written.push(id);
+        if let Some(background) = game.background(id) {
+            if background.image.width == 0 || background.image.height == 0 {
+                continue;
+            }
+            let file = dir.join(format!("{}.png", file_name(&background.name)));
+            write_png(&RgbaImage::from(&background.image), file)?;
+        }
+    }
+    Ok(())
+}
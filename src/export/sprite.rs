@@ -0,0 +1,401 @@
+use crate::export::{
+    compress_scanlines, image_error, write_json, write_png, write_png_chunk, PNG_SIGNATURE,
+};
+use crate::game::{Game, Room, Sprite, SpriteMask};
+use image::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, Rgba, RgbaImage};
+use serde::Serialize;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct SheetOptions {
+    // Number of frames per row, or 0 to lay the frames out in a roughly square grid.
+    pub columns: u32,
+    pub padding: u32,
+    // Duration of each frame in milliseconds, as used by Aseprite.
+    pub frame_duration: u32,
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        SheetOptions {
+            columns: 0,
+            padding: 0,
+            frame_duration: 100,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Size {
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Debug, Copy, Clone, Serialize)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+// Follows the TexturePacker "JSON (Array)" layout, which Aseprite also reads and writes.
+#[derive(Debug, Serialize)]
+pub struct SpriteSheet {
+    pub frames: Vec<SheetFrame>,
+    pub meta: SheetMeta,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetFrame {
+    pub filename: String,
+    pub frame: Rect,
+    pub rotated: bool,
+    pub trimmed: bool,
+    pub sprite_source_size: Rect,
+    pub source_size: Size,
+    pub pivot: Point<f64>,
+    pub duration: u32,
+    pub origin: Point<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mask: Option<Rect>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SheetMeta {
+    pub app: String,
+    pub version: String,
+    pub image: String,
+    pub format: String,
+    pub size: Size,
+    pub scale: String,
+}
+
+pub fn mask_bounds(mask: &SpriteMask) -> Option<Rect> {
+    if mask.right < mask.left || mask.bottom < mask.top {
+        return None;
+    }
+    Some(Rect {
+        x: mask.left,
+        y: mask.top,
+        w: (mask.right - mask.left + 1) as u32,
+        h: (mask.bottom - mask.top + 1) as u32,
+    })
+}
+
+// Returns the collision mask for the given frame. Sprites without separate masks share the first one.
+pub fn frame_mask(sprite: &Sprite, frame: usize) -> Option<&SpriteMask> {
+    sprite.masks.get(frame).or_else(|| sprite.masks.first())
+}
+
+fn no_frames(sprite: &Sprite) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("sprite {} has no frames", sprite.name),
+    )
+}
+
+pub fn sprite_sheet(
+    sprite: &Sprite,
+    image_name: &str,
+    options: &SheetOptions,
+) -> io::Result<(RgbaImage, SpriteSheet)> {
+    if sprite.frames.is_empty() {
+        return Err(no_frames(sprite));
+    }
+    let num_frames = sprite.frames.len() as u32;
+    let cell_width = sprite.frames.iter().map(|f| f.width).max().unwrap_or(0);
+    let cell_height = sprite.frames.iter().map(|f| f.height).max().unwrap_or(0);
+
+    let columns = if options.columns > 0 {
+        options.columns.min(num_frames)
+    } else {
+        (f64::from(num_frames).sqrt().ceil() as u32).max(1)
+    };
+    let rows = num_frames.div_ceil(columns);
+
+    let span = |cells: u32, size: u32| cells * size + (cells - 1) * options.padding;
+    let size = Size {
+        w: span(columns.min(num_frames), cell_width),
+        h: span(rows, cell_height),
+    };
+
+    let mut sheet = RgbaImage::new(size.w, size.h);
+    let mut frames = Vec::with_capacity(sprite.frames.len());
+    for (i, frame) in sprite.frames.iter().enumerate() {
+        let column = i as u32 % columns;
+        let row = i as u32 / columns;
+        let x = column * (cell_width + options.padding);
+        let y = row * (cell_height + options.padding);
+        image::imageops::replace(&mut sheet, &RgbaImage::from(frame), x, y);
+
+        let pivot = |origin: i32, size: u32| {
+            if size == 0 {
+                0.0
+            } else {
+                f64::from(origin) / f64::from(size)
+            }
+        };
+        frames.push(SheetFrame {
+            filename: format!("{}_{}", sprite.name, i),
+            frame: Rect {
+                x: x as i32,
+                y: y as i32,
+                w: frame.width,
+                h: frame.height,
+            },
+            rotated: false,
+            trimmed: false,
+            sprite_source_size: Rect {
+                x: 0,
+                y: 0,
+                w: frame.width,
+                h: frame.height,
+            },
+            source_size: Size {
+                w: frame.width,
+                h: frame.height,
+            },
+            pivot: Point {
+                x: pivot(sprite.origin.0, frame.width),
+                y: pivot(sprite.origin.1, frame.height),
+            },
+            duration: options.frame_duration,
+            origin: Point {
+                x: sprite.origin.0,
+                y: sprite.origin.1,
+            },
+            mask: frame_mask(sprite, i).and_then(mask_bounds),
+        });
+    }
+
+    let meta = SheetMeta {
+        app: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        image: image_name.to_string(),
+        format: "RGBA8888".to_string(),
+        size,
+        scale: "1".to_string(),
+    };
+    Ok((sheet, SpriteSheet { frames, meta }))
+}
+
+// Writes the atlas to `path` and its metadata next to it, with a `.json` extension.
+pub fn write_sprite_sheet<P: AsRef<Path>>(
+    sprite: &Sprite,
+    path: P,
+    options: &SheetOptions,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let image_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (sheet, metadata) = sprite_sheet(sprite, &image_name, options)?;
+    write_png(&sheet, path)?;
+    write_json(&metadata, path.with_extension("json"))
+}
+
+// Game Maker's default room speed, for games without rooms.
+const DEFAULT_ROOM_SPEED: u32 = 30;
+
+#[derive(Debug, Clone)]
+pub struct AnimationOptions {
+    // Frames per second.
+    pub frame_rate: u32,
+    // Color of the crosshair drawn at the sprite origin, if any.
+    pub origin_marker: Option<Rgba<u8>>,
+}
+
+impl AnimationOptions {
+    // Plays at the speed of the first room in the room order, like the game does when it starts.
+    pub fn for_game(game: &Game) -> Self {
+        let first_room = game.room_order.first().and_then(|&id| game.room(id));
+        match first_room {
+            Some(room) => AnimationOptions::for_room(room),
+            None => AnimationOptions {
+                frame_rate: DEFAULT_ROOM_SPEED,
+                origin_marker: None,
+            },
+        }
+    }
+
+    // Plays at the speed of `room`, for sprites shown in a particular room.
+    pub fn for_room(room: &Room) -> Self {
+        AnimationOptions {
+            frame_rate: if room.speed > 0 {
+                room.speed
+            } else {
+                DEFAULT_ROOM_SPEED
+            },
+            origin_marker: None,
+        }
+    }
+}
+
+fn mark_origin(image: &mut RgbaImage, origin: (i32, i32), color: Rgba<u8>) {
+    const ARM: i32 = 3;
+    for d in -ARM..=ARM {
+        for &(x, y) in &[(origin.0 + d, origin.1), (origin.0, origin.1 + d)] {
+            if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+                image.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+fn animation_frames(sprite: &Sprite, options: &AnimationOptions) -> io::Result<Vec<RgbaImage>> {
+    let width = sprite.frames.iter().map(|f| f.width).max().unwrap_or(0);
+    let height = sprite.frames.iter().map(|f| f.height).max().unwrap_or(0);
+    if width == 0 || height == 0 {
+        return Err(no_frames(sprite));
+    }
+
+    let mut frames = Vec::with_capacity(sprite.frames.len());
+    for frame in &sprite.frames {
+        let mut canvas = RgbaImage::new(width, height);
+        image::imageops::replace(&mut canvas, &RgbaImage::from(frame), 0, 0);
+        if let Some(color) = options.origin_marker {
+            mark_origin(&mut canvas, sprite.origin, color);
+        }
+        frames.push(canvas);
+    }
+    Ok(frames)
+}
+
+pub fn write_gif<W: Write>(sprite: &Sprite, options: &AnimationOptions, w: W) -> io::Result<()> {
+    let frames = animation_frames(sprite, options)?;
+    let delay = Delay::from_numer_denom_ms(1000, options.frame_rate.max(1));
+    let mut encoder = GifEncoder::new(w);
+    encoder.set_repeat(Repeat::Infinite).map_err(image_error)?;
+    encoder
+        .encode_frames(
+            frames
+                .into_iter()
+                .map(|frame| Frame::from_parts(frame, 0, 0, delay)),
+        )
+        .map_err(image_error)
+}
+
+// The image crate can't write APNGs, so the chunks are assembled by hand.
+pub fn write_apng<W: Write>(
+    sprite: &Sprite,
+    options: &AnimationOptions,
+    mut w: W,
+) -> io::Result<()> {
+    let frames = animation_frames(sprite, options)?;
+    let (width, height) = frames[0].dimensions();
+
+    w.write_all(PNG_SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8-bit RGBA, deflate, no filtering, no interlacing.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    write_png_chunk(&mut w, b"IHDR", &header)?;
+
+    let mut animation_control = Vec::with_capacity(8);
+    animation_control.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+    // Loop forever.
+    animation_control.extend_from_slice(&0u32.to_be_bytes());
+    write_png_chunk(&mut w, b"acTL", &animation_control)?;
+
+    let delay_denominator = options.frame_rate.clamp(1, u32::from(u16::MAX)) as u16;
+    let mut sequence = 0u32;
+    for (i, frame) in frames.iter().enumerate() {
+        let mut frame_control = Vec::with_capacity(26);
+        frame_control.extend_from_slice(&sequence.to_be_bytes());
+        frame_control.extend_from_slice(&width.to_be_bytes());
+        frame_control.extend_from_slice(&height.to_be_bytes());
+        frame_control.extend_from_slice(&0u32.to_be_bytes());
+        frame_control.extend_from_slice(&0u32.to_be_bytes());
+        frame_control.extend_from_slice(&1u16.to_be_bytes());
+        frame_control.extend_from_slice(&delay_denominator.to_be_bytes());
+        // Dispose to background, and replace rather than blend.
+        frame_control.extend_from_slice(&[1, 0]);
+        write_png_chunk(&mut w, b"fcTL", &frame_control)?;
+        sequence += 1;
+
+        let data = compress_scanlines(frame.as_raw(), width as usize * 4)?;
+        if i == 0 {
+            write_png_chunk(&mut w, b"IDAT", &data)?;
+        } else {
+            let mut frame_data = Vec::with_capacity(data.len() + 4);
+            frame_data.extend_from_slice(&sequence.to_be_bytes());
+            frame_data.extend_from_slice(&data);
+            write_png_chunk(&mut w, b"fdAT", &frame_data)?;
+            sequence += 1;
+        }
+    }
+
+    write_png_chunk(&mut w, b"IEND", &[])?;
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::RoomId;
+
+    fn sprite(sizes: &[(u32, u32)]) -> Sprite {
+        Sprite {
+            name: "spr_test".to_string(),
+            frames: sizes
+                .iter()
+                .map(|&(w, h)| RgbaImage::new(w, h).into())
+                .collect(),
+            ..Sprite::default()
+        }
+    }
+
+    #[test]
+    fn sheet_lays_out_frames_in_a_grid() {
+        let options = SheetOptions {
+            padding: 1,
+            ..SheetOptions::default()
+        };
+        let (image, sheet) = sprite_sheet(&sprite(&[(4, 2); 3]), "spr.png", &options).unwrap();
+        assert_eq!(image.dimensions(), (9, 5));
+        let positions: Vec<_> = sheet
+            .frames
+            .iter()
+            .map(|f| (f.frame.x, f.frame.y))
+            .collect();
+        assert_eq!(positions, vec![(0, 0), (5, 0), (0, 3)]);
+    }
+
+    #[test]
+    fn sprites_without_frames_are_rejected() {
+        let empty = sprite(&[]);
+        let options = AnimationOptions::for_game(&Game::default());
+        assert!(sprite_sheet(&empty, "spr.png", &SheetOptions::default()).is_err());
+        assert!(write_gif(&empty, &options, vec![]).is_err());
+        assert!(write_apng(&empty, &options, vec![]).is_err());
+    }
+
+    #[test]
+    fn frame_rate_defaults_to_the_first_room_speed() {
+        let mut game = Game::default();
+        assert_eq!(AnimationOptions::for_game(&game).frame_rate, 30);
+        for (id, speed) in [(0, 60), (1, 15)] {
+            game.rooms.push(Room {
+                id: RoomId(id),
+                speed,
+                ..Room::default()
+            });
+        }
+        game.room_order = vec![RoomId(1), RoomId(0)];
+        assert_eq!(AnimationOptions::for_game(&game).frame_rate, 15);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::export::{escape_xml, image_error};
+use crate::game::Path;
+use crate::path::PathGeometry;
+use image::RgbaImage;
+use std::fmt::Write as _;
+use std::io;
+
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    pub stroke: String,
+    pub stroke_width: f64,
+    // Mark the path's control points, with the first one drawn larger.
+    pub show_points: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            stroke: "#ff0000".to_string(),
+            stroke_width: 2.0,
+            show_points: true,
+        }
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).cloned().unwrap_or(0),
+            chunk.get(2).cloned().unwrap_or(0),
+        ];
+        let group = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Draws the paths as an SVG. If a background (such as a room render) is given, it is embedded
+// and sets the size of the drawing; otherwise the drawing is sized to fit the paths.
+pub fn paths_to_svg(
+    paths: &[&Path],
+    background: Option<&RgbaImage>,
+    options: &SvgOptions,
+) -> io::Result<String> {
+    let geometries: Vec<PathGeometry> = paths.iter().map(|p| PathGeometry::new(p)).collect();
+
+    let (min_x, min_y, width, height) = match background {
+        Some(image) => (
+            0.0,
+            0.0,
+            f64::from(image.width()),
+            f64::from(image.height()),
+        ),
+        None => {
+            let margin = options.stroke_width * 4.0;
+            let all_points = || paths.iter().flat_map(|p| &p.points);
+            let min_x = all_points().map(|p| p.x).fold(f64::INFINITY, f64::min);
+            let min_y = all_points().map(|p| p.y).fold(f64::INFINITY, f64::min);
+            let max_x = all_points().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+            let max_y = all_points().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+            if min_x.is_finite() && min_y.is_finite() {
+                (
+                    min_x - margin,
+                    min_y - margin,
+                    max_x - min_x + margin * 2.0,
+                    max_y - min_y + margin * 2.0,
+                )
+            } else {
+                (0.0, 0.0, 1.0, 1.0)
+            }
+        }
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">",
+        width, height, min_x, min_y, width, height
+    );
+
+    if let Some(image) = background {
+        let mut png = vec![];
+        image::png::PngEncoder::new(&mut png)
+            .encode(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                image::ColorType::Rgba8,
+            )
+            .map_err(image_error)?;
+        let _ = writeln!(
+            out,
+            " <image width=\"{}\" height=\"{}\" href=\"data:image/png;base64,{}\"/>",
+            image.width(),
+            image.height(),
+            base64(&png)
+        );
+    }
+
+    for (path, geometry) in paths.iter().zip(&geometries) {
+        let _ = writeln!(out, " <g id=\"{}\">", escape_xml(&path.name));
+        let _ = writeln!(out, "  <title>{}</title>", escape_xml(&path.name));
+        let points: Vec<String> = geometry
+            .nodes()
+            .iter()
+            .map(|node| format!("{},{}", node.x, node.y))
+            .collect();
+        let _ = writeln!(
+            out,
+            "  <polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            points.join(" "),
+            escape_xml(&options.stroke),
+            options.stroke_width
+        );
+        if options.show_points {
+            for (i, point) in path.points.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"><title>{} (speed {})</title></circle>",
+                    point.x,
+                    point.y,
+                    options.stroke_width * if i == 0 { 3.0 } else { 1.5 },
+                    escape_xml(&options.stroke),
+                    i,
+                    point.speed
+                );
+            }
+        }
+        out.push_str(" </g>\n");
+    }
+
+    out.push_str("</svg>\n");
+    Ok(out)
+}
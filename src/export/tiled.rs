@@ -0,0 +1,475 @@
+use crate::export::{escape_xml, file_name, write_backgrounds};
+use crate::game::{Background, BackgroundId, Game, Room};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct TiledOptions {
+    // Directory, relative to the map, that background images are referenced from.
+    pub image_dir: String,
+}
+
+impl Default for TiledOptions {
+    fn default() -> Self {
+        TiledOptions {
+            image_dir: "backgrounds".to_string(),
+        }
+    }
+}
+
+struct Tileset<'a> {
+    background: &'a Background,
+    first_gid: u32,
+    columns: u32,
+    rows: u32,
+}
+
+#[derive(Default)]
+struct DepthLayer {
+    cells: Vec<u32>,
+    // Tiles that don't line up with the map grid, as (gid, x, y).
+    objects: Vec<(u32, i32, i32)>,
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn image_source(options: &TiledOptions, background: &Background) -> String {
+    if options.image_dir.is_empty() {
+        format!("{}.png", file_name(&background.name))
+    } else {
+        format!("{}/{}.png", options.image_dir, file_name(&background.name))
+    }
+}
+
+fn write_property(out: &mut String, indent: &str, name: &str, kind: &str, value: &str) {
+    let kind = if kind == "string" {
+        String::new()
+    } else {
+        format!(" type=\"{}\"", kind)
+    };
+    if value.contains('\n') {
+        // Tiled stores multi-line strings as element text rather than in the attribute.
+        let _ = writeln!(
+            out,
+            "{}<property name=\"{}\"{}>{}</property>",
+            indent,
+            escape_xml(name),
+            kind,
+            escape_xml(value)
+        );
+    } else {
+        let _ = writeln!(
+            out,
+            "{}<property name=\"{}\"{} value=\"{}\"/>",
+            indent,
+            escape_xml(name),
+            kind,
+            escape_xml(value)
+        );
+    }
+}
+
+// The map grid is the largest tile size that every tile in the room is made of, so that each
+// Game Maker tile splits into whole Tiled tiles.
+fn grid_size(room: &Room) -> (u32, u32) {
+    let mut width = 0;
+    let mut height = 0;
+    for tile in &room.tiles {
+        width = gcd(gcd(width, tile.width), tile.tile_x.max(0) as u32);
+        height = gcd(gcd(height, tile.height), tile.tile_y.max(0) as u32);
+    }
+    (
+        if width == 0 { 32 } else { width },
+        if height == 0 { 32 } else { height },
+    )
+}
+
+pub fn room_to_tmx(game: &Game, room: &Room, options: &TiledOptions) -> String {
+    let (grid_width, grid_height) = grid_size(room);
+    let map_width = room.width.div_ceil(grid_width);
+    let map_height = room.height.div_ceil(grid_height);
+
+    // One tileset for each background used by a tile.
+    let mut tilesets: BTreeMap<Option<BackgroundId>, Tileset> = BTreeMap::new();
+    let mut next_gid = 1;
+    for tile in &room.tiles {
+        if tilesets.contains_key(&tile.background) {
+            continue;
+        }
+        if let Some(background) = game.background(tile.background) {
+            let columns = background.image.width / grid_width;
+            let rows = background.image.height / grid_height;
+            if columns == 0 || rows == 0 {
+                continue;
+            }
+            tilesets.insert(
+                tile.background,
+                Tileset {
+                    background,
+                    first_gid: next_gid,
+                    columns,
+                    rows,
+                },
+            );
+            next_gid += columns * rows;
+        }
+    }
+
+    let mut layers: BTreeMap<i32, DepthLayer> = BTreeMap::new();
+    for tile in &room.tiles {
+        let tileset = match tilesets.get(&tile.background) {
+            Some(tileset) => tileset,
+            None => continue,
+        };
+        let layer = layers.entry(tile.depth).or_insert_with(|| DepthLayer {
+            cells: vec![0; (map_width * map_height) as usize],
+            objects: vec![],
+        });
+        let first_column = tile.tile_x.max(0) as u32 / grid_width;
+        let first_row = tile.tile_y.max(0) as u32 / grid_height;
+        for j in 0..tile.height / grid_height {
+            for i in 0..tile.width / grid_width {
+                let (column, row) = (first_column + i, first_row + j);
+                if column >= tileset.columns || row >= tileset.rows {
+                    continue;
+                }
+                let gid = tileset.first_gid + row * tileset.columns + column;
+                let x = tile.x + (i * grid_width) as i32;
+                let y = tile.y + (j * grid_height) as i32;
+
+                let aligned = x >= 0
+                    && y >= 0
+                    && (x as u32).is_multiple_of(grid_width)
+                    && (y as u32).is_multiple_of(grid_height)
+                    && (x as u32 / grid_width) < map_width
+                    && (y as u32 / grid_height) < map_height;
+                if aligned {
+                    let index =
+                        ((y as u32 / grid_height) * map_width + x as u32 / grid_width) as usize;
+                    if layer.cells[index] == 0 {
+                        layer.cells[index] = gid;
+                        continue;
+                    }
+                }
+                layer.objects.push((gid, x, y));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let mut next_layer_id = 1;
+    let mut next_object_id = 1;
+    let mut body = String::new();
+
+    // Tiled draws layers in document order, while Game Maker draws the highest depth first.
+    let background_layers = |body: &mut String, next_layer_id: &mut u32, foreground: bool| {
+        for (i, background) in room.backgrounds.iter().enumerate() {
+            if background.foreground != foreground {
+                continue;
+            }
+            let image = match game.background(background.background) {
+                Some(image) => image,
+                None => continue,
+            };
+            let _ = writeln!(
+                body,
+                " <imagelayer id=\"{}\" name=\"{} {}\" offsetx=\"{}\" offsety=\"{}\" repeatx=\"{}\" repeaty=\"{}\"{}>",
+                next_layer_id,
+                if foreground { "Foreground" } else { "Background" },
+                i,
+                background.x,
+                background.y,
+                background.tile_h as u8,
+                background.tile_v as u8,
+                if background.visible { "" } else { " visible=\"0\"" }
+            );
+            *next_layer_id += 1;
+            body.push_str("  <properties>\n");
+            write_property(body, "   ", "background", "string", &image.name);
+            write_property(
+                body,
+                "   ",
+                "stretch",
+                "bool",
+                &background.stretch.to_string(),
+            );
+            write_property(
+                body,
+                "   ",
+                "h_speed",
+                "int",
+                &background.h_speed.to_string(),
+            );
+            write_property(
+                body,
+                "   ",
+                "v_speed",
+                "int",
+                &background.v_speed.to_string(),
+            );
+            body.push_str("  </properties>\n");
+            let _ = writeln!(
+                body,
+                "  <image source=\"{}\" width=\"{}\" height=\"{}\"/>",
+                escape_xml(&image_source(options, image)),
+                image.image.width,
+                image.image.height
+            );
+            body.push_str(" </imagelayer>\n");
+        }
+    };
+
+    background_layers(&mut body, &mut next_layer_id, false);
+
+    for (depth, layer) in layers.iter().rev() {
+        if layer.cells.iter().any(|&c| c != 0) {
+            let _ = writeln!(
+                body,
+                " <layer id=\"{}\" name=\"Tiles {}\" width=\"{}\" height=\"{}\">",
+                next_layer_id, depth, map_width, map_height
+            );
+            next_layer_id += 1;
+            body.push_str("  <properties>\n");
+            write_property(&mut body, "   ", "depth", "int", &depth.to_string());
+            body.push_str("  </properties>\n");
+            body.push_str("  <data encoding=\"csv\">\n");
+            for (row, cells) in layer.cells.chunks(map_width as usize).enumerate() {
+                let line: Vec<String> = cells.iter().map(|c| c.to_string()).collect();
+                body.push_str(&line.join(","));
+                if row + 1 < map_height as usize {
+                    body.push(',');
+                }
+                body.push('\n');
+            }
+            body.push_str("  </data>\n");
+            body.push_str(" </layer>\n");
+        }
+
+        if !layer.objects.is_empty() {
+            let _ = writeln!(
+                body,
+                " <objectgroup id=\"{}\" name=\"Tiles {} (unaligned)\">",
+                next_layer_id, depth
+            );
+            next_layer_id += 1;
+            for &(gid, x, y) in &layer.objects {
+                // Tile objects are positioned by their bottom-left corner.
+                let _ = writeln!(
+                    body,
+                    "  <object id=\"{}\" gid=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+                    next_object_id,
+                    gid,
+                    x,
+                    y + grid_height as i32,
+                    grid_width,
+                    grid_height
+                );
+                next_object_id += 1;
+            }
+            body.push_str(" </objectgroup>\n");
+        }
+    }
+
+    let _ = writeln!(
+        body,
+        " <objectgroup id=\"{}\" name=\"Instances\">",
+        next_layer_id
+    );
+    next_layer_id += 1;
+    for instance in &room.instances {
+        let name = game.object(instance.object).map_or("", |o| o.name.as_str());
+        let _ = writeln!(
+            body,
+            "  <object id=\"{}\" name=\"{}\" type=\"{}\" x=\"{}\" y=\"{}\">",
+            next_object_id,
+            escape_xml(name),
+            escape_xml(name),
+            instance.x,
+            instance.y
+        );
+        next_object_id += 1;
+        body.push_str("   <properties>\n");
+        write_property(
+            &mut body,
+            "    ",
+            "instance_id",
+            "int",
+            &instance.id.to_string(),
+        );
+        write_property(&mut body, "    ", "object", "string", name);
+        if !instance.creation_code.is_empty() {
+            write_property(
+                &mut body,
+                "    ",
+                "creation_code",
+                "string",
+                &instance.creation_code,
+            );
+        }
+        body.push_str("   </properties>\n");
+        body.push_str("   <point/>\n");
+        body.push_str("  </object>\n");
+    }
+    body.push_str(" </objectgroup>\n");
+
+    if !room.views.is_empty() {
+        let _ = writeln!(
+            body,
+            " <objectgroup id=\"{}\" name=\"Views\"{}>",
+            next_layer_id,
+            if room.enable_views {
+                ""
+            } else {
+                " visible=\"0\""
+            }
+        );
+        next_layer_id += 1;
+        for (i, view) in room.views.iter().enumerate() {
+            let _ = writeln!(
+                body,
+                "  <object id=\"{}\" name=\"view{}\" type=\"view\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}>",
+                next_object_id,
+                i,
+                view.view_x,
+                view.view_y,
+                view.view_width,
+                view.view_height,
+                if view.visible { "" } else { " visible=\"0\"" }
+            );
+            next_object_id += 1;
+            body.push_str("   <properties>\n");
+            for (name, value) in &[
+                ("port_x", view.port_x),
+                ("port_y", view.port_y),
+                ("port_width", view.port_width),
+                ("port_height", view.port_height),
+                ("h_border", view.h_border),
+                ("v_border", view.v_border),
+            ] {
+                write_property(&mut body, "    ", name, "int", &value.to_string());
+            }
+            write_property(
+                &mut body,
+                "    ",
+                "h_speed",
+                "int",
+                &view.h_speed.to_string(),
+            );
+            write_property(
+                &mut body,
+                "    ",
+                "v_speed",
+                "int",
+                &view.v_speed.to_string(),
+            );
+            write_property(
+                &mut body,
+                "    ",
+                "target_object",
+                "string",
+                game.object(view.target_object)
+                    .map_or("", |o| o.name.as_str()),
+            );
+            body.push_str("   </properties>\n");
+            body.push_str("  </object>\n");
+        }
+        body.push_str(" </objectgroup>\n");
+    }
+
+    background_layers(&mut body, &mut next_layer_id, true);
+
+    let clear_color = crate::render::color_to_rgba(room.clear_color);
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<map version=\"1.10\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{}\" height=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" infinite=\"0\" backgroundcolor=\"#{:02x}{:02x}{:02x}\" nextlayerid=\"{}\" nextobjectid=\"{}\">",
+        map_width,
+        map_height,
+        grid_width,
+        grid_height,
+        clear_color[0],
+        clear_color[1],
+        clear_color[2],
+        next_layer_id,
+        next_object_id
+    );
+    out.push_str(" <properties>\n");
+    write_property(&mut out, "  ", "name", "string", &room.name);
+    write_property(&mut out, "  ", "caption", "string", &room.caption);
+    write_property(&mut out, "  ", "width", "int", &room.width.to_string());
+    write_property(&mut out, "  ", "height", "int", &room.height.to_string());
+    write_property(&mut out, "  ", "speed", "int", &room.speed.to_string());
+    write_property(
+        &mut out,
+        "  ",
+        "persistent",
+        "bool",
+        &room.persistent.to_string(),
+    );
+    write_property(&mut out, "  ", "clear", "bool", &room.clear.to_string());
+    write_property(
+        &mut out,
+        "  ",
+        "creation_code",
+        "string",
+        &room.creation_code,
+    );
+    out.push_str(" </properties>\n");
+
+    for tileset in tilesets.values() {
+        let background = tileset.background;
+        let _ = writeln!(
+            out,
+            " <tileset firstgid=\"{}\" name=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" tilecount=\"{}\" columns=\"{}\">",
+            tileset.first_gid,
+            escape_xml(&background.name),
+            grid_width,
+            grid_height,
+            tileset.columns * tileset.rows,
+            tileset.columns
+        );
+        let _ = writeln!(
+            out,
+            "  <image source=\"{}\" width=\"{}\" height=\"{}\"/>",
+            escape_xml(&image_source(options, background)),
+            background.image.width,
+            background.image.height
+        );
+        out.push_str(" </tileset>\n");
+    }
+
+    out.push_str(&body);
+    out.push_str("</map>\n");
+    out
+}
+
+// Writes the map to `path`, along with the background images it references.
+pub fn write_tmx<P: AsRef<Path>>(
+    game: &Game,
+    room: &Room,
+    path: P,
+    options: &TiledOptions,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let image_dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(&options.image_dir);
+    let used = room
+        .tiles
+        .iter()
+        .filter_map(|t| t.background)
+        .chain(room.backgrounds.iter().filter_map(|b| b.background));
+    write_backgrounds(game, used, &image_dir)?;
+
+    fs::write(path, room_to_tmx(game, room, options))
+}
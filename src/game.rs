@@ -1,5 +1,6 @@
 use image::RgbaImage;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
+use std::iter::FromIterator;
 
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq, Serialize)]
 pub enum Version {
@@ -17,6 +18,68 @@ impl Default for Version {
     }
 }
 
+// Resource references are stored as an `i32`, where a negative value (usually -1) means "none".
+// They're exposed as an `Option` of the resource's id type and serialized back to the raw value.
+pub trait ResourceId: Copy {
+    fn new(id: u32) -> Self;
+    fn get(self) -> u32;
+
+    fn from_raw(raw: i32) -> Option<Self> {
+        if raw < 0 {
+            None
+        } else {
+            Some(Self::new(raw as u32))
+        }
+    }
+
+    fn to_raw(id: Option<Self>) -> i32 {
+        id.map_or(-1, |id| id.get() as i32)
+    }
+}
+
+macro_rules! resource_ids {
+    ($($name:ident),*) => {$(
+        #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(pub u32);
+
+        impl ResourceId for $name {
+            fn new(id: u32) -> Self {
+                $name(id)
+            }
+
+            fn get(self) -> u32 {
+                self.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    )*};
+}
+
+resource_ids!(
+    SpriteId,
+    SoundId,
+    BackgroundId,
+    PathId,
+    ScriptId,
+    FontId,
+    TimelineId,
+    ObjectId,
+    RoomId
+);
+
+fn serialize_reference<T: ResourceId, S: Serializer>(
+    id: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i32(T::to_raw(*id))
+}
+
 #[derive(Debug, Serialize)]
 pub enum ColorType {
     Rgba,
@@ -54,6 +117,23 @@ impl From<RgbaImage> for Image {
     }
 }
 
+impl From<&Image> for RgbaImage {
+    fn from(other: &Image) -> Self {
+        let data = match other.color_type {
+            ColorType::Rgba => other.data.clone(),
+            ColorType::Gray => {
+                let mut data = Vec::with_capacity(other.data.len() * 4);
+                for &v in &other.data {
+                    data.extend_from_slice(&[v, v, v, 255]);
+                }
+                data
+            }
+        };
+        RgbaImage::from_raw(other.width, other.height, data)
+            .unwrap_or_else(|| RgbaImage::new(other.width, other.height))
+    }
+}
+
 #[derive(Default, Serialize)]
 pub struct Game {
     pub version: Version,
@@ -81,7 +161,10 @@ pub struct Game {
     pub includes: Vec<Include>,
 
     pub library_init_scripts: Vec<String>,
-    pub room_order: Vec<u32>,
+    pub room_order: Vec<RoomId>,
+
+    // Added after the fields above so they keep their positions in the MessagePack dump.
+    pub extensions: Vec<Extension>,
 }
 
 #[derive(Default, Debug, Serialize)]
@@ -145,23 +228,108 @@ pub struct Constant {
 
 #[derive(Default, Debug, Serialize)]
 pub struct Sound {
-    pub id: u32,
+    pub id: SoundId,
     pub name: String,
-    pub kind: u32,
+    pub kind: SoundKind,
     pub filetype: String,
     pub filename: String,
 
     #[serde(with = "serde_bytes")]
     pub data: Vec<u8>,
-    pub effects: u32,
+    pub effects: SoundEffects,
     pub volume: f64,
     pub pan: f64,
     pub preload: bool,
 }
 
+// Serialized as the raw value, like `SoundEffects`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(into = "u32")]
+pub enum SoundKind {
+    #[default]
+    Normal,
+    Background,
+    ThreeD,
+    Multimedia,
+    Unknown(u32),
+}
+
+impl From<u32> for SoundKind {
+    fn from(kind: u32) -> Self {
+        match kind {
+            0 => SoundKind::Normal,
+            1 => SoundKind::Background,
+            2 => SoundKind::ThreeD,
+            3 => SoundKind::Multimedia,
+            _ => SoundKind::Unknown(kind),
+        }
+    }
+}
+
+impl From<SoundKind> for u32 {
+    fn from(kind: SoundKind) -> Self {
+        match kind {
+            SoundKind::Normal => 0,
+            SoundKind::Background => 1,
+            SoundKind::ThreeD => 2,
+            SoundKind::Multimedia => 3,
+            SoundKind::Unknown(kind) => kind,
+        }
+    }
+}
+
+// DirectSound effects enabled on a sound. Serialized as the raw bits.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct SoundEffects(pub u32);
+
+impl SoundEffects {
+    pub const CHORUS: SoundEffects = SoundEffects(1);
+    pub const ECHO: SoundEffects = SoundEffects(2);
+    pub const FLANGER: SoundEffects = SoundEffects(4);
+    pub const GARGLE: SoundEffects = SoundEffects(8);
+    pub const REVERB: SoundEffects = SoundEffects(16);
+
+    const NAMES: [(SoundEffects, &'static str); 5] = [
+        (SoundEffects::CHORUS, "chorus"),
+        (SoundEffects::ECHO, "echo"),
+        (SoundEffects::FLANGER, "flanger"),
+        (SoundEffects::GARGLE, "gargle"),
+        (SoundEffects::REVERB, "reverb"),
+    ];
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, other: SoundEffects) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn names(self) -> Vec<&'static str> {
+        SoundEffects::NAMES
+            .iter()
+            .filter(|(effect, _)| self.contains(*effect))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl std::ops::BitOr for SoundEffects {
+    type Output = SoundEffects;
+
+    fn bitor(self, other: SoundEffects) -> SoundEffects {
+        SoundEffects(self.0 | other.0)
+    }
+}
+
 #[derive(Default, Debug, Serialize)]
 pub struct Sprite {
-    pub id: u32,
+    pub id: SpriteId,
     pub name: String,
     pub origin: (i32, i32),
 
@@ -176,19 +344,94 @@ pub struct SpriteMask {
     pub right: i32,
     pub bottom: i32,
     pub top: i32,
-    pub data: Vec<bool>,
+    pub data: BitVec,
+}
+
+impl SpriteMask {
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        x < self.size.0 && y < self.size.1 && self.data.get((y * self.size.0 + x) as usize)
+    }
+}
+
+// Packed bits, least significant bit first. Stores masks in an eighth of the space of a Vec<bool>.
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
+pub struct BitVec {
+    len: usize,
+    #[serde(with = "serde_bytes")]
+    bits: Vec<u8>,
+}
+
+impl BitVec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = (self.len + additional).div_ceil(8);
+        self.bits.reserve(needed.saturating_sub(self.bits.len()));
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bits.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    // Out of range bits read as unset.
+    pub fn get(&self, index: usize) -> bool {
+        index < self.len && self.bits[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "bit index {} out of range", index);
+        if value {
+            self.bits[index / 8] |= 1 << (index % 8);
+        } else {
+            self.bits[index / 8] &= !(1 << (index % 8));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut bits = BitVec::new();
+        bits.reserve(iter.size_hint().0);
+        for value in iter {
+            bits.push(value);
+        }
+        bits
+    }
 }
 
 #[derive(Default, Debug, Serialize)]
 pub struct Background {
-    pub id: u32,
+    pub id: BackgroundId,
     pub name: String,
     pub image: Image,
 }
 
 #[derive(Default, Debug, Serialize)]
 pub struct Path {
-    pub id: u32,
+    pub id: PathId,
     pub name: String,
     pub connection_type: u32,
     pub closed: bool,
@@ -196,7 +439,7 @@ pub struct Path {
     pub points: Vec<PathPoint>,
 }
 
-#[derive(Default, Debug, Serialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize)]
 pub struct PathPoint {
     pub x: f64,
     pub y: f64,
@@ -205,14 +448,14 @@ pub struct PathPoint {
 
 #[derive(Default, Debug, Serialize)]
 pub struct Script {
-    pub id: u32,
+    pub id: ScriptId,
     pub name: String,
     pub script: String,
 }
 
 #[derive(Default, Debug, Serialize)]
 pub struct Font {
-    pub id: u32,
+    pub id: FontId,
     pub name: String,
     pub font_name: String,
     pub size: u32,
@@ -260,7 +503,7 @@ pub struct Action {
 
 #[derive(Default, Debug, Serialize)]
 pub struct Timeline {
-    pub id: u32,
+    pub id: TimelineId,
     pub name: String,
     pub moments: Vec<TimelineMoment>,
 }
@@ -273,15 +516,18 @@ pub struct TimelineMoment {
 
 #[derive(Default, Debug, Serialize)]
 pub struct Object {
-    pub id: u32,
+    pub id: ObjectId,
     pub name: String,
-    pub sprite: i32,
+    #[serde(serialize_with = "serialize_reference")]
+    pub sprite: Option<SpriteId>,
     pub solid: bool,
     pub visible: bool,
     pub depth: i32,
     pub persistent: bool,
-    pub parent: i32,
-    pub mask: i32,
+    #[serde(serialize_with = "serialize_reference")]
+    pub parent: Option<ObjectId>,
+    #[serde(serialize_with = "serialize_reference")]
+    pub mask: Option<SpriteId>,
     pub events: Vec<ObjectEvent>,
 }
 
@@ -294,7 +540,7 @@ pub struct ObjectEvent {
 
 #[derive(Default, Debug, Serialize)]
 pub struct Room {
-    pub id: u32,
+    pub id: RoomId,
     pub name: String,
     pub caption: String,
     pub width: u32,
@@ -315,7 +561,8 @@ pub struct Room {
 pub struct RoomBackground {
     pub visible: bool,
     pub foreground: bool,
-    pub background: i32,
+    #[serde(serialize_with = "serialize_reference")]
+    pub background: Option<BackgroundId>,
     pub x: i32,
     pub y: i32,
     pub tile_h: bool,
@@ -340,14 +587,16 @@ pub struct RoomView {
     pub v_border: u32,
     pub h_speed: i32,
     pub v_speed: i32,
-    pub target_object: i32,
+    #[serde(serialize_with = "serialize_reference")]
+    pub target_object: Option<ObjectId>,
 }
 
 #[derive(Default, Debug, Serialize)]
 pub struct RoomInstance {
     pub x: i32,
     pub y: i32,
-    pub object: i32,
+    #[serde(serialize_with = "serialize_reference")]
+    pub object: Option<ObjectId>,
     pub id: i32,
     pub creation_code: String,
 }
@@ -356,7 +605,8 @@ pub struct RoomInstance {
 pub struct RoomTile {
     pub x: i32,
     pub y: i32,
-    pub background: i32,
+    #[serde(serialize_with = "serialize_reference")]
+    pub background: Option<BackgroundId>,
     pub tile_x: i32,
     pub tile_y: i32,
     pub width: u32,
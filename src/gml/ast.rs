@@ -0,0 +1,311 @@
+use super::Span;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Not,
+    Negate,
+    Plus,
+    BitNot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    IntDivide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    Xor,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+impl BinaryOp {
+    // Higher binds tighter. Unlike C, GM8 gives `&&`, `||` and `^^` one level, and `&`, `|` and
+    // `^` another, so `a || b && c` is `(a || b) && c`.
+    pub fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::And | BinaryOp::Or | BinaryOp::Xor => 1,
+            BinaryOp::Equal
+            | BinaryOp::NotEqual
+            | BinaryOp::Less
+            | BinaryOp::LessEqual
+            | BinaryOp::Greater
+            | BinaryOp::GreaterEqual => 2,
+            BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => 3,
+            BinaryOp::ShiftLeft | BinaryOp::ShiftRight => 4,
+            BinaryOp::Add | BinaryOp::Subtract => 5,
+            BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::IntDivide | BinaryOp::Modulo => 6,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/",
+            BinaryOp::IntDivide => "div",
+            BinaryOp::Modulo => "mod",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+            BinaryOp::Xor => "^^",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::ShiftLeft => "<<",
+            BinaryOp::ShiftRight => ">>",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssignOp {
+    // Both `=` and `:=`.
+    Assign,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    BitAnd,
+    BitOr,
+    BitXor,
+}
+
+impl AssignOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            AssignOp::Assign => "=",
+            AssignOp::Add => "+=",
+            AssignOp::Subtract => "-=",
+            AssignOp::Multiply => "*=",
+            AssignOp::Divide => "/=",
+            AssignOp::BitAnd => "&=",
+            AssignOp::BitOr => "|=",
+            AssignOp::BitXor => "^=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identifier {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Real(f64),
+    // The contents without the quotes.
+    String(String),
+    Identifier(String),
+    // `object.field`, where `object` is usually `self`, `other`, `global` or an object.
+    Field(Box<Expr>, Identifier),
+    // GM8 arrays have one or two dimensions.
+    Index(Box<Expr>, Vec<Expr>),
+    Call(Identifier, Vec<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Parenthesized(Box<Expr>),
+    // Stands in for an expression that failed to parse.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    // `{ }` and `begin end`.
+    Block(Vec<Stmt>),
+    Var(Vec<Identifier>),
+    GlobalVar(Vec<Identifier>),
+    Assign(Expr, AssignOp, Expr),
+    // Only calls are valid as statements, but anything is accepted.
+    Expr(Expr),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    While(Expr, Box<Stmt>),
+    DoUntil(Box<Stmt>, Expr),
+    For(Box<Stmt>, Expr, Box<Stmt>, Box<Stmt>),
+    Repeat(Expr, Box<Stmt>),
+    With(Expr, Box<Stmt>),
+    // Case labels appear as statements in the body.
+    Switch(Expr, Vec<Stmt>),
+    Case(Expr),
+    Default,
+    Break,
+    Continue,
+    Exit,
+    Return(Option<Expr>),
+    Empty,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Program {
+    pub statements: Vec<Stmt>,
+}
+
+// Calls `f` on every expression in `statements`, outer expressions before the ones inside them.
+pub fn walk_exprs<'a, F: FnMut(&'a Expr)>(statements: &'a [Stmt], f: &mut F) {
+    for statement in statements {
+        walk_stmt_exprs(statement, f);
+    }
+}
+
+fn walk_stmt_exprs<'a, F: FnMut(&'a Expr)>(statement: &'a Stmt, f: &mut F) {
+    match &statement.kind {
+        StmtKind::Block(body) => walk_exprs(body, f),
+        StmtKind::Switch(value, body) => {
+            walk_expr(value, f);
+            walk_exprs(body, f);
+        }
+        StmtKind::Assign(target, _, value) => {
+            walk_expr(target, f);
+            walk_expr(value, f);
+        }
+        StmtKind::Expr(e) | StmtKind::Case(e) | StmtKind::Return(Some(e)) => walk_expr(e, f),
+        StmtKind::If(condition, then, otherwise) => {
+            walk_expr(condition, f);
+            walk_stmt_exprs(then, f);
+            if let Some(otherwise) = otherwise {
+                walk_stmt_exprs(otherwise, f);
+            }
+        }
+        StmtKind::While(e, body) | StmtKind::Repeat(e, body) | StmtKind::With(e, body) => {
+            walk_expr(e, f);
+            walk_stmt_exprs(body, f);
+        }
+        StmtKind::DoUntil(body, e) => {
+            walk_stmt_exprs(body, f);
+            walk_expr(e, f);
+        }
+        StmtKind::For(init, condition, step, body) => {
+            walk_stmt_exprs(init, f);
+            walk_expr(condition, f);
+            walk_stmt_exprs(step, f);
+            walk_stmt_exprs(body, f);
+        }
+        StmtKind::Var(_)
+        | StmtKind::GlobalVar(_)
+        | StmtKind::Default
+        | StmtKind::Break
+        | StmtKind::Continue
+        | StmtKind::Exit
+        | StmtKind::Return(None)
+        | StmtKind::Empty
+        | StmtKind::Error => (),
+    }
+}
+
+pub fn walk_expr<'a, F: FnMut(&'a Expr)>(expr: &'a Expr, f: &mut F) {
+    f(expr);
+    match &expr.kind {
+        ExprKind::Field(object, _) => walk_expr(object, f),
+        ExprKind::Index(target, indices) => {
+            walk_expr(target, f);
+            for index in indices {
+                walk_expr(index, f);
+            }
+        }
+        ExprKind::Call(_, args) => {
+            for arg in args {
+                walk_expr(arg, f);
+            }
+        }
+        ExprKind::Unary(_, operand) | ExprKind::Parenthesized(operand) => walk_expr(operand, f),
+        ExprKind::Binary(_, left, right) => {
+            walk_expr(left, f);
+            walk_expr(right, f);
+        }
+        ExprKind::Real(_) | ExprKind::String(_) | ExprKind::Identifier(_) | ExprKind::Error => (),
+    }
+}
+
+// Calls `f` on every statement in `statements`, outer statements before the ones inside them.
+pub fn walk_statements<'a, F: FnMut(&'a Stmt)>(statements: &'a [Stmt], f: &mut F) {
+    for statement in statements {
+        walk_statement(statement, f);
+    }
+}
+
+fn walk_statement<'a, F: FnMut(&'a Stmt)>(statement: &'a Stmt, f: &mut F) {
+    f(statement);
+    match &statement.kind {
+        StmtKind::Block(body) | StmtKind::Switch(_, body) => walk_statements(body, f),
+        StmtKind::If(_, then, otherwise) => {
+            walk_statement(then, f);
+            if let Some(otherwise) = otherwise {
+                walk_statement(otherwise, f);
+            }
+        }
+        StmtKind::While(_, body)
+        | StmtKind::Repeat(_, body)
+        | StmtKind::With(_, body)
+        | StmtKind::DoUntil(body, _) => walk_statement(body, f),
+        StmtKind::For(init, _, step, body) => {
+            walk_statement(init, f);
+            walk_statement(step, f);
+            walk_statement(body, f);
+        }
+        _ => (),
+    }
+}
+
+// Names declared with `var` anywhere in `statements`. A local lasts for the whole piece of code,
+// so it hides a resource or script with the same name even before the declaration.
+pub fn var_names(statements: &[Stmt]) -> HashSet<&str> {
+    let mut names = HashSet::new();
+    walk_statements(statements, &mut |statement| {
+        if let StmtKind::Var(declared) = &statement.kind {
+            names.extend(declared.iter().map(|n| n.name.as_str()));
+        }
+    });
+    names
+}
+
+// Names assigned to directly, as in `a = 1` or `a[i] += 1`, which are variables of the instance
+// running the code.
+pub fn assigned_names(statements: &[Stmt]) -> HashSet<&str> {
+    let mut names = HashSet::new();
+    walk_statements(statements, &mut |statement| {
+        if let StmtKind::Assign(target, _, _) = &statement.kind {
+            let target = match &target.kind {
+                ExprKind::Index(target, _) => target,
+                _ => target,
+            };
+            if let ExprKind::Identifier(name) = &target.kind {
+                names.insert(name.as_str());
+            }
+        }
+    });
+    names
+}
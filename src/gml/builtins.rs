@@ -0,0 +1,457 @@
+// Names built into the GM8 runner, from the Game Maker 8.1 manual.
+
+#[rustfmt::skip]
+pub static FUNCTIONS: &[&str] = &[
+    // Computing things
+    "random", "random_range", "irandom", "irandom_range", "random_set_seed", "random_get_seed",
+    "randomize", "choose", "abs", "sign", "round", "floor", "ceil", "frac", "sqrt", "sqr", "power",
+    "exp", "ln", "log2", "log10", "logn", "sin", "cos", "tan", "arcsin", "arccos", "arctan",
+    "arctan2", "degtorad", "radtodeg", "min", "max", "mean", "median", "point_distance",
+    "point_direction", "lengthdir_x", "lengthdir_y", "is_real", "is_string",
+    // Strings
+    "chr", "ord", "real", "string", "string_format", "string_length", "string_pos", "string_copy",
+    "string_char_at", "string_delete", "string_insert", "string_replace", "string_replace_all",
+    "string_count", "string_lower", "string_upper", "string_repeat", "string_letters",
+    "string_digits", "string_lettersdigits", "clipboard_has_text", "clipboard_get_text",
+    "clipboard_set_text",
+    // Dates and times
+    "date_current_datetime", "date_current_date", "date_current_time", "date_create_datetime",
+    "date_create_date", "date_create_time", "date_valid_datetime", "date_valid_date",
+    "date_valid_time", "date_inc_year", "date_inc_month", "date_inc_week", "date_inc_day",
+    "date_inc_hour", "date_inc_minute", "date_inc_second", "date_get_year", "date_get_month",
+    "date_get_week", "date_get_day", "date_get_hour", "date_get_minute", "date_get_second",
+    "date_get_weekday", "date_get_day_of_year", "date_get_hour_of_year",
+    "date_get_minute_of_year", "date_get_second_of_year", "date_year_span", "date_month_span",
+    "date_week_span", "date_day_span", "date_hour_span", "date_minute_span", "date_second_span",
+    "date_compare_datetime", "date_compare_date", "date_compare_time", "date_date_of",
+    "date_time_of", "date_datetime_string", "date_date_string", "date_time_string",
+    "date_days_in_month", "date_days_in_year", "date_leap_year", "date_is_today",
+    // Motion
+    "motion_set", "motion_add", "place_free", "place_empty", "place_meeting", "place_snapped",
+    "move_random", "move_snap", "move_wrap", "move_towards_point", "move_bounce_solid",
+    "move_bounce_all", "move_contact_solid", "move_contact_all", "move_outside_solid",
+    "move_outside_all", "distance_to_point", "distance_to_object", "position_empty",
+    "position_meeting",
+    // Paths and motion planning
+    "path_start", "path_end", "mp_linear_step", "mp_linear_step_object", "mp_potential_step",
+    "mp_potential_step_object", "mp_potential_settings", "mp_linear_path",
+    "mp_linear_path_object", "mp_potential_path", "mp_potential_path_object", "mp_grid_create",
+    "mp_grid_destroy", "mp_grid_clear_all", "mp_grid_clear_cell", "mp_grid_clear_rectangle",
+    "mp_grid_add_cell", "mp_grid_add_rectangle", "mp_grid_add_instances", "mp_grid_path",
+    "mp_grid_draw",
+    // Collision checking
+    "collision_point", "collision_rectangle", "collision_circle", "collision_ellipse",
+    "collision_line",
+    // Instances
+    "instance_find", "instance_exists", "instance_number", "instance_position",
+    "instance_nearest", "instance_furthest", "instance_place", "instance_create",
+    "instance_copy", "instance_destroy", "instance_change", "position_destroy",
+    "position_change", "instance_deactivate_all", "instance_deactivate_object",
+    "instance_deactivate_region", "instance_activate_all", "instance_activate_object",
+    "instance_activate_region",
+    // Timing
+    "sleep", "timeline_moment_clear", "timeline_moment_add",
+    // Rooms and score
+    "room_goto", "room_goto_previous", "room_goto_next", "room_previous", "room_next",
+    "room_restart", "game_end", "game_restart", "game_load", "game_save",
+    "transition_define", "transition_exists",
+    // Events
+    "event_perform", "event_perform_object", "event_user", "event_inherited",
+    // Debugging and execution
+    "show_debug_message", "variable_global_exists", "variable_local_exists",
+    "variable_global_get", "variable_global_array_get", "variable_global_array2_get",
+    "variable_local_get", "variable_local_array_get", "variable_local_array2_get",
+    "variable_global_set", "variable_global_array_set", "variable_global_array2_set",
+    "variable_local_set", "variable_local_array_set", "variable_local_array2_set",
+    "set_program_priority", "set_application_title", "execute_string", "execute_file",
+    "script_execute", "script_exists", "script_get_name", "script_get_text",
+    // Keyboard and mouse
+    "keyboard_set_map", "keyboard_get_map", "keyboard_unset_map", "keyboard_check",
+    "keyboard_check_pressed", "keyboard_check_released", "keyboard_check_direct",
+    "keyboard_get_numlock", "keyboard_set_numlock", "keyboard_key_press",
+    "keyboard_key_release", "keyboard_clear", "io_clear", "io_handle", "keyboard_wait",
+    "mouse_check_button", "mouse_check_button_pressed", "mouse_check_button_released",
+    "mouse_clear", "mouse_wait", "joystick_exists", "joystick_name", "joystick_axes",
+    "joystick_buttons", "joystick_has_pov", "joystick_direction", "joystick_check_button",
+    "joystick_xpos", "joystick_ypos", "joystick_zpos", "joystick_rpos", "joystick_upos",
+    "joystick_vpos", "joystick_pov",
+    // Drawing
+    "draw_sprite", "draw_sprite_stretched", "draw_sprite_tiled", "draw_sprite_part",
+    "draw_background", "draw_background_stretched", "draw_background_tiled",
+    "draw_background_part", "draw_sprite_ext", "draw_sprite_stretched_ext",
+    "draw_sprite_tiled_ext", "draw_sprite_part_ext", "draw_sprite_general",
+    "draw_background_ext", "draw_background_stretched_ext", "draw_background_tiled_ext",
+    "draw_background_part_ext", "draw_background_general", "draw_clear", "draw_clear_alpha",
+    "draw_point", "draw_line", "draw_line_width", "draw_rectangle", "draw_roundrect",
+    "draw_triangle", "draw_circle", "draw_ellipse", "draw_set_circle_precision", "draw_arrow",
+    "draw_button", "draw_path", "draw_healthbar", "draw_set_color", "draw_set_alpha",
+    "draw_get_color", "draw_get_alpha", "make_color_rgb", "make_color_hsv", "color_get_red",
+    "color_get_green", "color_get_blue", "color_get_hue", "color_get_saturation",
+    "color_get_value", "merge_color", "draw_getpixel", "screen_save", "screen_save_part",
+    "draw_set_font", "draw_set_halign", "draw_set_valign", "draw_text", "draw_text_ext",
+    "string_width", "string_height", "string_width_ext", "string_height_ext",
+    "draw_text_transformed", "draw_text_ext_transformed", "draw_text_color",
+    "draw_text_ext_color", "draw_text_transformed_color", "draw_text_ext_transformed_color",
+    "draw_point_color", "draw_line_color", "draw_line_width_color", "draw_rectangle_color",
+    "draw_roundrect_color", "draw_triangle_color", "draw_circle_color", "draw_ellipse_color",
+    "draw_primitive_begin", "draw_vertex", "draw_vertex_color", "draw_primitive_end",
+    "sprite_get_texture", "background_get_texture", "texture_preload",
+    "texture_set_priority", "texture_get_width", "texture_get_height",
+    "draw_primitive_begin_texture", "draw_vertex_texture", "draw_vertex_texture_color",
+    "texture_set_interpolation", "texture_set_blending", "texture_set_repeat",
+    "draw_set_blend_mode", "draw_set_blend_mode_ext", "surface_create", "surface_free",
+    "surface_exists", "surface_get_width", "surface_get_height", "surface_get_texture",
+    "surface_set_target", "surface_reset_target", "surface_getpixel", "surface_save",
+    "surface_save_part", "draw_surface", "draw_surface_stretched", "draw_surface_tiled",
+    "draw_surface_part", "draw_surface_ext", "draw_surface_stretched_ext",
+    "draw_surface_tiled_ext", "draw_surface_part_ext", "draw_surface_general",
+    "surface_copy", "surface_copy_part", "tile_add", "tile_delete", "tile_exists",
+    "tile_get_x", "tile_get_y", "tile_get_left", "tile_get_top", "tile_get_width",
+    "tile_get_height", "tile_get_depth", "tile_get_visible", "tile_get_xscale",
+    "tile_get_yscale", "tile_get_background", "tile_get_blend", "tile_get_alpha",
+    "tile_set_position", "tile_set_region", "tile_set_background", "tile_set_visible",
+    "tile_set_depth", "tile_set_scale", "tile_set_blend", "tile_set_alpha",
+    "tile_layer_hide", "tile_layer_show", "tile_layer_delete", "tile_layer_shift",
+    "tile_layer_find", "tile_layer_delete_at", "tile_layer_depth", "display_get_width",
+    "display_get_height", "display_get_colordepth", "display_get_frequency",
+    "display_set_size", "display_set_colordepth", "display_set_frequency", "display_set_all",
+    "display_test_all", "display_reset", "display_mouse_get_x", "display_mouse_get_y",
+    "display_mouse_set", "window_set_visible", "window_get_visible", "window_set_fullscreen",
+    "window_get_fullscreen", "window_set_showborder", "window_get_showborder",
+    "window_set_showicons", "window_get_showicons", "window_set_stayontop",
+    "window_get_stayontop", "window_set_sizeable", "window_get_sizeable", "window_set_caption",
+    "window_get_caption", "window_set_cursor", "window_get_cursor", "window_set_color",
+    "window_get_color", "window_set_region_scale", "window_get_region_scale",
+    "window_set_position", "window_set_size", "window_set_rectangle", "window_center",
+    "window_default", "window_get_x", "window_get_y", "window_get_width",
+    "window_get_height", "window_mouse_get_x", "window_mouse_get_y", "window_mouse_set",
+    "window_set_region_size", "window_get_region_width", "window_get_region_height",
+    "window_view_mouse_get_x", "window_view_mouse_get_y", "window_view_mouse_set",
+    "window_views_mouse_get_x", "window_views_mouse_get_y", "window_views_mouse_set",
+    "screen_redraw", "screen_refresh", "screen_wait_vsync", "set_automatic_draw",
+    "set_synchronization",
+    // Sound and music
+    "sound_play", "sound_loop", "sound_stop", "sound_stop_all", "sound_isplaying",
+    "sound_volume", "sound_global_volume", "sound_fade", "sound_pan", "sound_background_tempo",
+    "sound_set_search_directory", "sound_effect_set", "sound_effect_chorus",
+    "sound_effect_echo", "sound_effect_flanger", "sound_effect_gargle", "sound_effect_reverb",
+    "sound_effect_compressor", "sound_effect_equalizer", "sound_3d_set_sound_position",
+    "sound_3d_set_sound_velocity", "sound_3d_set_sound_distance",
+    "sound_3d_set_sound_cone", "cd_init", "cd_present", "cd_number", "cd_playing",
+    "cd_paused", "cd_track", "cd_length", "cd_track_length", "cd_position",
+    "cd_track_position", "cd_play", "cd_stop", "cd_pause", "cd_resume", "cd_set_position",
+    "cd_set_track_position", "cd_open_door", "cd_close_door", "MCI_command",
+    // Splash screens, highscores and other pop-ups
+    "splash_show_video", "splash_show_text", "splash_show_image", "splash_show_web",
+    "splash_set_main", "splash_set_scale", "splash_set_cursor", "splash_set_color",
+    "splash_set_caption", "splash_set_fullscreen", "splash_set_border", "splash_set_size",
+    "splash_set_position", "splash_set_adapt", "splash_set_top", "splash_set_interrupt",
+    "splash_set_stop_key", "splash_set_stop_mouse", "splash_set_close_button",
+    "show_info", "load_info", "show_message", "show_message_ext", "show_question",
+    "get_integer", "get_string", "message_background", "message_alpha", "message_button",
+    "message_text_font", "message_button_font", "message_input_font", "message_mouse_color",
+    "message_input_color", "message_caption", "message_position", "message_size",
+    "show_menu", "show_menu_pos", "get_color", "get_open_filename", "get_save_filename",
+    "get_directory", "get_directory_alt", "show_error", "highscore_show",
+    "highscore_set_background", "highscore_set_border", "highscore_set_font",
+    "highscore_set_colors", "highscore_set_strings", "highscore_show_ext", "highscore_clear",
+    "highscore_add", "highscore_add_current", "highscore_value", "highscore_name",
+    "draw_highscore",
+    // Resources
+    "sprite_exists", "sprite_get_name", "sprite_get_number", "sprite_get_width",
+    "sprite_get_height", "sprite_get_xoffset", "sprite_get_yoffset", "sprite_get_bbox_left",
+    "sprite_get_bbox_right", "sprite_get_bbox_top", "sprite_get_bbox_bottom",
+    "sprite_save", "sprite_save_strip", "sound_exists", "sound_get_name", "sound_get_kind",
+    "sound_get_preload", "sound_discard", "sound_restore", "background_exists",
+    "background_get_name", "background_get_width", "background_get_height",
+    "background_save", "font_exists", "font_get_name", "font_get_fontname",
+    "font_get_bold", "font_get_italic", "font_get_first", "font_get_last", "path_exists",
+    "path_get_name", "path_get_length", "path_get_kind", "path_get_closed",
+    "path_get_precision", "path_get_number", "path_get_point_x", "path_get_point_y",
+    "path_get_point_speed", "path_get_x", "path_get_y", "path_get_speed",
+    "timeline_exists", "timeline_get_name", "object_exists", "object_get_name",
+    "object_get_sprite", "object_get_solid", "object_get_visible", "object_get_depth",
+    "object_get_persistent", "object_get_mask", "object_get_parent", "object_is_ancestor",
+    "room_exists", "room_get_name",
+    // Changing resources
+    "sprite_set_offset", "sprite_duplicate", "sprite_assign", "sprite_merge", "sprite_add",
+    "sprite_replace", "sprite_add_sprite", "sprite_replace_sprite", "sprite_create_from_screen",
+    "sprite_add_from_screen", "sprite_create_from_surface", "sprite_add_from_surface",
+    "sprite_delete", "sprite_set_alpha_from_sprite", "sprite_collision_mask", "sound_add",
+    "sound_replace", "sound_delete", "background_duplicate", "background_assign",
+    "background_add", "background_replace", "background_add_background",
+    "background_replace_background", "background_create_color",
+    "background_create_gradient", "background_create_from_screen",
+    "background_create_from_surface", "background_delete",
+    "background_set_alpha_from_background", "font_add", "font_add_sprite",
+    "font_replace", "font_replace_sprite", "font_delete", "path_set_kind", "path_set_closed",
+    "path_set_precision", "path_add", "path_delete", "path_duplicate", "path_assign",
+    "path_append", "path_add_point", "path_insert_point", "path_change_point",
+    "path_delete_point", "path_clear_points", "path_reverse", "path_mirror", "path_flip",
+    "path_rotate", "path_scale", "path_shift", "timeline_add",
+    "timeline_delete", "timeline_clear", "object_set_sprite", "object_set_solid",
+    "object_set_visible", "object_set_depth", "object_set_persistent", "object_set_mask",
+    "object_set_parent", "object_add", "object_delete", "object_event_add",
+    "object_event_clear", "room_set_width", "room_set_height", "room_set_caption",
+    "room_set_persistent", "room_set_code", "room_set_background_color",
+    "room_set_background", "room_set_view", "room_set_view_enabled", "room_add",
+    "room_duplicate", "room_assign", "room_instance_add", "room_instance_clear",
+    "room_tile_add", "room_tile_add_ext", "room_tile_clear",
+    // Files, registry and executing programs
+    "file_text_open_read", "file_text_open_write", "file_text_open_append", "file_text_close",
+    "file_text_write_string", "file_text_write_real", "file_text_writeln",
+    "file_text_read_string", "file_text_read_real", "file_text_readln", "file_text_eof",
+    "file_text_eoln", "file_exists", "file_delete", "file_rename", "file_copy",
+    "directory_exists", "directory_create", "file_find_first", "file_find_next",
+    "file_find_close", "file_attributes", "filename_name", "filename_path", "filename_dir",
+    "filename_drive", "filename_ext", "filename_change_ext", "file_bin_open",
+    "file_bin_rewrite", "file_bin_close", "file_bin_size", "file_bin_position",
+    "file_bin_seek", "file_bin_write_byte", "file_bin_read_byte", "file_open_read",
+    "file_open_write", "file_open_append", "file_close", "file_write_string",
+    "file_write_real", "file_writeln", "file_read_string", "file_read_real", "file_readln",
+    "file_eof", "export_include_file", "export_include_file_location",
+    "discard_include_file", "parameter_count", "parameter_string", "environment_get_variable",
+    "disk_size", "disk_free", "registry_write_string", "registry_write_real",
+    "registry_read_string", "registry_read_real", "registry_exists",
+    "registry_write_string_ext", "registry_write_real_ext", "registry_read_string_ext",
+    "registry_read_real_ext", "registry_exists_ext", "registry_set_root", "ini_open",
+    "ini_close", "ini_read_string", "ini_read_real", "ini_write_string", "ini_write_real",
+    "ini_key_exists", "ini_section_exists", "ini_key_delete", "ini_section_delete",
+    "execute_program", "execute_shell", "secure_mode",
+    // Data structures
+    "ds_set_precision", "ds_stack_create", "ds_stack_destroy", "ds_stack_clear",
+    "ds_stack_copy", "ds_stack_size", "ds_stack_empty", "ds_stack_push", "ds_stack_pop",
+    "ds_stack_top", "ds_stack_write", "ds_stack_read", "ds_queue_create",
+    "ds_queue_destroy", "ds_queue_clear", "ds_queue_copy", "ds_queue_size",
+    "ds_queue_empty", "ds_queue_enqueue", "ds_queue_dequeue", "ds_queue_head",
+    "ds_queue_tail", "ds_queue_write", "ds_queue_read", "ds_list_create", "ds_list_destroy",
+    "ds_list_clear", "ds_list_copy", "ds_list_size", "ds_list_empty", "ds_list_add",
+    "ds_list_insert", "ds_list_replace", "ds_list_delete", "ds_list_find_index",
+    "ds_list_find_value", "ds_list_sort", "ds_list_shuffle", "ds_list_write",
+    "ds_list_read", "ds_map_create", "ds_map_destroy", "ds_map_clear", "ds_map_copy",
+    "ds_map_size", "ds_map_empty", "ds_map_add", "ds_map_replace", "ds_map_delete",
+    "ds_map_exists", "ds_map_find_value", "ds_map_find_previous", "ds_map_find_next",
+    "ds_map_find_first", "ds_map_find_last", "ds_map_write", "ds_map_read",
+    "ds_priority_create", "ds_priority_destroy", "ds_priority_clear", "ds_priority_copy",
+    "ds_priority_size", "ds_priority_empty", "ds_priority_add", "ds_priority_change_priority",
+    "ds_priority_find_priority", "ds_priority_delete_value", "ds_priority_delete_min",
+    "ds_priority_find_min", "ds_priority_delete_max", "ds_priority_find_max",
+    "ds_priority_write", "ds_priority_read", "ds_grid_create", "ds_grid_destroy",
+    "ds_grid_copy", "ds_grid_resize", "ds_grid_width", "ds_grid_height", "ds_grid_clear",
+    "ds_grid_set", "ds_grid_add", "ds_grid_multiply", "ds_grid_set_region",
+    "ds_grid_add_region", "ds_grid_multiply_region", "ds_grid_set_disk", "ds_grid_add_disk",
+    "ds_grid_multiply_disk", "ds_grid_set_grid_region", "ds_grid_add_grid_region",
+    "ds_grid_multiply_grid_region", "ds_grid_get", "ds_grid_get_sum", "ds_grid_get_max",
+    "ds_grid_get_min", "ds_grid_get_mean", "ds_grid_get_disk_sum", "ds_grid_get_disk_min",
+    "ds_grid_get_disk_max", "ds_grid_get_disk_mean", "ds_grid_value_exists",
+    "ds_grid_value_x", "ds_grid_value_y", "ds_grid_value_disk_exists",
+    "ds_grid_value_disk_x", "ds_grid_value_disk_y", "ds_grid_shuffle", "ds_grid_write",
+    "ds_grid_read",
+    // Particles
+    "effect_create_below", "effect_create_above", "effect_clear", "part_type_create",
+    "part_type_destroy", "part_type_exists", "part_type_clear", "part_type_shape",
+    "part_type_sprite", "part_type_size", "part_type_scale", "part_type_orientation",
+    "part_type_color1", "part_type_color2", "part_type_color3", "part_type_color_mix",
+    "part_type_color_rgb", "part_type_color_hsv", "part_type_alpha1", "part_type_alpha2",
+    "part_type_alpha3", "part_type_blend", "part_type_life", "part_type_step",
+    "part_type_death", "part_type_speed", "part_type_direction", "part_type_gravity",
+    "part_system_create", "part_system_destroy", "part_system_exists", "part_system_clear",
+    "part_system_draw_order", "part_system_depth", "part_system_position",
+    "part_system_automatic_update", "part_system_automatic_draw", "part_system_update",
+    "part_system_drawit", "part_particles_create", "part_particles_create_color",
+    "part_particles_clear", "part_particles_count", "part_emitter_create",
+    "part_emitter_destroy", "part_emitter_destroy_all", "part_emitter_exists",
+    "part_emitter_clear", "part_emitter_region", "part_emitter_burst",
+    "part_emitter_stream", "part_attractor_create", "part_attractor_destroy",
+    "part_attractor_destroy_all", "part_attractor_exists", "part_attractor_clear",
+    "part_attractor_position", "part_attractor_force", "part_destroyer_create",
+    "part_destroyer_destroy", "part_destroyer_destroy_all", "part_destroyer_exists",
+    "part_destroyer_clear", "part_destroyer_region", "part_deflector_create",
+    "part_deflector_destroy", "part_deflector_destroy_all", "part_deflector_exists",
+    "part_deflector_clear", "part_deflector_region", "part_deflector_kind",
+    "part_deflector_friction", "part_changer_create", "part_changer_destroy",
+    "part_changer_destroy_all", "part_changer_exists", "part_changer_clear",
+    "part_changer_region", "part_changer_types", "part_changer_kind",
+    // Multiplayer
+    "mplay_init_ipx", "mplay_init_tcpip", "mplay_init_modem", "mplay_init_serial",
+    "mplay_connect_status", "mplay_end", "mplay_ipaddress", "mplay_session_mode",
+    "mplay_session_create", "mplay_session_find", "mplay_session_name",
+    "mplay_session_join", "mplay_session_status", "mplay_session_end", "mplay_player_find",
+    "mplay_player_name", "mplay_player_id", "mplay_data_write", "mplay_data_read",
+    "mplay_data_mode", "mplay_message_send", "mplay_message_send_guaranteed",
+    "mplay_message_receive", "mplay_message_id", "mplay_message_value",
+    "mplay_message_player", "mplay_message_name", "mplay_message_count",
+    "mplay_message_clear",
+    // DLLs
+    "external_define", "external_call", "external_free", "window_handle",
+    // 3D graphics
+    "d3d_start", "d3d_end", "d3d_set_hidden", "d3d_set_perspective", "d3d_set_depth",
+    "d3d_set_zwriteenable", "d3d_primitive_begin", "d3d_vertex", "d3d_vertex_color",
+    "d3d_primitive_end", "d3d_primitive_begin_texture", "d3d_vertex_texture",
+    "d3d_vertex_texture_color", "d3d_draw_block", "d3d_draw_cylinder", "d3d_draw_cone",
+    "d3d_draw_ellipsoid", "d3d_draw_wall", "d3d_draw_floor", "d3d_set_projection",
+    "d3d_set_projection_ext", "d3d_set_projection_ortho", "d3d_set_projection_perspective",
+    "d3d_transform_set_identity", "d3d_transform_set_translation",
+    "d3d_transform_set_scaling", "d3d_transform_set_rotation_x",
+    "d3d_transform_set_rotation_y", "d3d_transform_set_rotation_z",
+    "d3d_transform_set_rotation_axis", "d3d_transform_add_translation",
+    "d3d_transform_add_scaling", "d3d_transform_add_rotation_x",
+    "d3d_transform_add_rotation_y", "d3d_transform_add_rotation_z",
+    "d3d_transform_add_rotation_axis", "d3d_transform_stack_clear",
+    "d3d_transform_stack_empty", "d3d_transform_stack_push", "d3d_transform_stack_pop",
+    "d3d_transform_stack_top", "d3d_transform_stack_discard", "d3d_set_fog",
+    "d3d_set_lighting", "d3d_set_shading", "d3d_set_culling", "d3d_light_define_direction",
+    "d3d_light_define_point", "d3d_light_enable", "d3d_vertex_normal",
+    "d3d_vertex_normal_color", "d3d_vertex_normal_texture", "d3d_vertex_normal_texture_color",
+    "d3d_light_define_ambient", "d3d_model_create", "d3d_model_destroy", "d3d_model_clear",
+    "d3d_model_save", "d3d_model_load", "d3d_model_draw", "d3d_model_primitive_begin",
+    "d3d_model_vertex", "d3d_model_vertex_color", "d3d_model_vertex_texture",
+    "d3d_model_vertex_texture_color", "d3d_model_vertex_normal",
+    "d3d_model_vertex_normal_color", "d3d_model_vertex_normal_texture",
+    "d3d_model_vertex_normal_texture_color", "d3d_model_primitive_end", "d3d_model_block",
+    "d3d_model_cylinder", "d3d_model_cone", "d3d_model_ellipsoid", "d3d_model_wall",
+    "d3d_model_floor",
+];
+
+pub fn is_function(name: &str) -> bool {
+    FUNCTIONS.contains(&name)
+}
+
+// Variables every instance has.
+#[rustfmt::skip]
+pub static INSTANCE_VARIABLES: &[&str] = &[
+    "x", "y", "xprevious", "yprevious", "xstart", "ystart", "hspeed", "vspeed", "direction",
+    "speed", "friction", "gravity", "gravity_direction", "path_index", "path_position",
+    "path_positionprevious", "path_speed", "path_scale", "path_orientation", "path_endaction",
+    "object_index", "id", "solid", "persistent", "mask_index", "alarm", "timeline_index",
+    "timeline_position", "timeline_speed", "timeline_running", "timeline_loop", "visible",
+    "sprite_index", "sprite_width", "sprite_height", "sprite_xoffset", "sprite_yoffset",
+    "image_number", "image_index", "image_speed", "depth", "image_xscale", "image_yscale",
+    "image_angle", "image_alpha", "image_blend", "bbox_left", "bbox_right", "bbox_top",
+    "bbox_bottom",
+];
+
+// Variables shared by the whole game, which don't need a `global.` prefix.
+#[rustfmt::skip]
+pub static GLOBAL_VARIABLES: &[&str] = &[
+    "argument", "argument0", "argument1", "argument2", "argument3", "argument4", "argument5",
+    "argument6", "argument7", "argument8", "argument9", "argument10", "argument11",
+    "argument12", "argument13", "argument14", "argument15", "argument_relative",
+    "score", "lives", "health", "show_score", "show_lives", "show_health", "caption_score",
+    "caption_lives", "caption_health", "room", "room_first", "room_last", "room_width",
+    "room_height", "room_caption", "room_persistent", "room_speed", "transition_kind",
+    "transition_steps", "background_color", "background_showcolor", "background_visible",
+    "background_foreground", "background_index", "background_x", "background_y",
+    "background_width", "background_height", "background_htiled", "background_vtiled",
+    "background_xscale", "background_yscale", "background_hspeed", "background_vspeed",
+    "background_blend", "background_alpha", "view_enabled", "view_current", "view_visible",
+    "view_xview", "view_yview", "view_wview", "view_hview", "view_xport", "view_yport",
+    "view_wport", "view_hport", "view_angle", "view_hborder", "view_vborder", "view_hspeed",
+    "view_vspeed", "view_object", "current_time", "current_year", "current_month",
+    "current_day", "current_weekday", "current_hour", "current_minute", "current_second",
+    "fps", "keyboard_key", "keyboard_lastkey", "keyboard_lastchar", "keyboard_string",
+    "mouse_x", "mouse_y", "mouse_button", "mouse_lastbutton", "cursor_sprite", "event_type",
+    "event_number", "event_object", "event_action", "instance_count", "instance_id",
+    "working_directory", "program_directory", "temp_directory", "error_occurred",
+    "error_last", "gamemaker_pro", "gamemaker_registered", "gamemaker_version",
+    "secure_mode", "debug_mode", "game_id", "transition_color",
+];
+
+#[rustfmt::skip]
+pub static CONSTANTS: &[(&str, f64)] = &[
+    ("true", 1.0), ("false", 0.0), ("pi", std::f64::consts::PI),
+    ("self", -1.0), ("other", -2.0), ("all", -3.0), ("noone", -4.0), ("global", -5.0),
+    // Colors
+    ("c_aqua", 16776960.0), ("c_black", 0.0), ("c_blue", 16711680.0), ("c_dkgray", 4210752.0),
+    ("c_fuchsia", 16711935.0), ("c_gray", 8421504.0), ("c_green", 32768.0),
+    ("c_lime", 65280.0), ("c_ltgray", 12632256.0), ("c_maroon", 128.0),
+    ("c_navy", 8388608.0), ("c_olive", 32896.0), ("c_orange", 4235519.0),
+    ("c_purple", 8388736.0), ("c_red", 255.0), ("c_silver", 12632256.0),
+    ("c_teal", 8421376.0), ("c_white", 16777215.0), ("c_yellow", 65535.0),
+    // Keys
+    ("vk_nokey", 0.0), ("vk_anykey", 1.0), ("vk_backspace", 8.0), ("vk_tab", 9.0),
+    ("vk_enter", 13.0), ("vk_return", 13.0), ("vk_shift", 16.0), ("vk_control", 17.0),
+    ("vk_alt", 18.0), ("vk_pause", 19.0), ("vk_escape", 27.0), ("vk_space", 32.0),
+    ("vk_pageup", 33.0), ("vk_pagedown", 34.0), ("vk_end", 35.0), ("vk_home", 36.0),
+    ("vk_left", 37.0), ("vk_up", 38.0), ("vk_right", 39.0), ("vk_down", 40.0),
+    ("vk_printscreen", 44.0), ("vk_insert", 45.0), ("vk_delete", 46.0),
+    ("vk_numpad0", 96.0), ("vk_numpad1", 97.0), ("vk_numpad2", 98.0), ("vk_numpad3", 99.0),
+    ("vk_numpad4", 100.0), ("vk_numpad5", 101.0), ("vk_numpad6", 102.0),
+    ("vk_numpad7", 103.0), ("vk_numpad8", 104.0), ("vk_numpad9", 105.0),
+    ("vk_multiply", 106.0), ("vk_add", 107.0), ("vk_subtract", 109.0), ("vk_decimal", 110.0),
+    ("vk_divide", 111.0), ("vk_f1", 112.0), ("vk_f2", 113.0), ("vk_f3", 114.0),
+    ("vk_f4", 115.0), ("vk_f5", 116.0), ("vk_f6", 117.0), ("vk_f7", 118.0), ("vk_f8", 119.0),
+    ("vk_f9", 120.0), ("vk_f10", 121.0), ("vk_f11", 122.0), ("vk_f12", 123.0),
+    ("vk_lshift", 160.0), ("vk_rshift", 161.0), ("vk_lcontrol", 162.0),
+    ("vk_rcontrol", 163.0), ("vk_lalt", 164.0), ("vk_ralt", 165.0),
+    // Mouse buttons
+    ("mb_any", -1.0), ("mb_none", 0.0), ("mb_left", 1.0), ("mb_right", 2.0), ("mb_middle", 3.0),
+    // Text alignment
+    ("fa_left", 0.0), ("fa_center", 1.0), ("fa_right", 2.0), ("fa_top", 0.0),
+    ("fa_middle", 1.0), ("fa_bottom", 2.0),
+    // File attributes
+    ("fa_readonly", 1.0), ("fa_hidden", 2.0), ("fa_sysfile", 4.0), ("fa_volumeid", 8.0),
+    ("fa_directory", 16.0), ("fa_archive", 32.0),
+    // Blend modes
+    ("bm_normal", 0.0), ("bm_add", 1.0), ("bm_max", 2.0), ("bm_subtract", 3.0),
+    ("bm_zero", 1.0), ("bm_one", 2.0), ("bm_src_color", 3.0), ("bm_inv_src_color", 4.0),
+    ("bm_src_alpha", 5.0), ("bm_inv_src_alpha", 6.0), ("bm_dest_alpha", 7.0),
+    ("bm_inv_dest_alpha", 8.0), ("bm_dest_color", 9.0), ("bm_inv_dest_color", 10.0),
+    ("bm_src_alpha_sat", 11.0),
+    // Primitives
+    ("pr_pointlist", 1.0), ("pr_linelist", 2.0), ("pr_linestrip", 3.0),
+    ("pr_trianglelist", 4.0), ("pr_trianglestrip", 5.0), ("pr_trianglefan", 6.0),
+    // Events
+    ("ev_create", 0.0), ("ev_destroy", 1.0), ("ev_alarm", 2.0), ("ev_step", 3.0),
+    ("ev_collision", 4.0), ("ev_keyboard", 5.0), ("ev_mouse", 6.0), ("ev_other", 7.0),
+    ("ev_draw", 8.0), ("ev_keypress", 9.0), ("ev_keyrelease", 10.0), ("ev_trigger", 11.0),
+    ("ev_step_normal", 0.0), ("ev_step_begin", 1.0), ("ev_step_end", 2.0),
+    ("ev_left_button", 0.0), ("ev_right_button", 1.0), ("ev_middle_button", 2.0),
+    ("ev_no_button", 3.0), ("ev_left_press", 4.0), ("ev_right_press", 5.0),
+    ("ev_middle_press", 6.0), ("ev_left_release", 7.0), ("ev_right_release", 8.0),
+    ("ev_middle_release", 9.0), ("ev_mouse_enter", 10.0), ("ev_mouse_leave", 11.0),
+    ("ev_global_left_button", 50.0), ("ev_global_right_button", 51.0),
+    ("ev_global_middle_button", 52.0), ("ev_global_left_press", 53.0),
+    ("ev_global_right_press", 54.0), ("ev_global_middle_press", 55.0),
+    ("ev_global_left_release", 56.0), ("ev_global_right_release", 57.0),
+    ("ev_global_middle_release", 58.0), ("ev_mouse_wheel_up", 60.0),
+    ("ev_mouse_wheel_down", 61.0), ("ev_outside", 0.0), ("ev_boundary", 1.0),
+    ("ev_game_start", 2.0), ("ev_game_end", 3.0), ("ev_room_start", 4.0),
+    ("ev_room_end", 5.0), ("ev_no_more_lives", 6.0), ("ev_animation_end", 7.0),
+    ("ev_end_of_path", 8.0), ("ev_no_more_health", 9.0), ("ev_close_button", 30.0),
+    ("ev_user0", 10.0), ("ev_user1", 11.0), ("ev_user2", 12.0), ("ev_user3", 13.0),
+    ("ev_user4", 14.0), ("ev_user5", 15.0), ("ev_user6", 16.0), ("ev_user7", 17.0),
+    ("ev_user8", 18.0), ("ev_user9", 19.0), ("ev_user10", 20.0), ("ev_user11", 21.0),
+    ("ev_user12", 22.0), ("ev_user13", 23.0), ("ev_user14", 24.0), ("ev_user15", 25.0),
+    // Effects
+    ("ef_explosion", 0.0), ("ef_ring", 1.0), ("ef_ellipse", 2.0), ("ef_firework", 3.0),
+    ("ef_smoke", 4.0), ("ef_smokeup", 5.0), ("ef_star", 6.0), ("ef_spark", 7.0),
+    ("ef_flare", 8.0), ("ef_cloud", 9.0), ("ef_rain", 10.0), ("ef_snow", 11.0),
+    // Sound effects
+    ("se_none", 0.0), ("se_chorus", 1.0), ("se_echo", 2.0), ("se_flanger", 4.0),
+    ("se_gargle", 8.0), ("se_reverb", 16.0), ("se_compressor", 32.0), ("se_equalizer", 64.0),
+    // Particle shapes
+    ("pt_shape_pixel", 0.0), ("pt_shape_disk", 1.0), ("pt_shape_square", 2.0),
+    ("pt_shape_line", 3.0), ("pt_shape_star", 4.0), ("pt_shape_circle", 5.0),
+    ("pt_shape_ring", 6.0), ("pt_shape_sphere", 7.0), ("pt_shape_flare", 8.0),
+    ("pt_shape_spark", 9.0), ("pt_shape_explosion", 10.0), ("pt_shape_cloud", 11.0),
+    ("pt_shape_smoke", 12.0), ("pt_shape_snow", 13.0),
+    // External calls
+    ("ty_real", 0.0), ("ty_string", 1.0), ("dll_cdecl", 0.0), ("dll_stdcall", 1.0),
+    // Cursors
+    ("cr_default", 0.0), ("cr_none", -1.0), ("cr_arrow", -2.0), ("cr_cross", -3.0),
+    ("cr_beam", -4.0), ("cr_size_nesw", -6.0), ("cr_size_ns", -7.0), ("cr_size_nwse", -8.0),
+    ("cr_size_we", -9.0), ("cr_uparrow", -10.0), ("cr_hourglass", -11.0), ("cr_drag", -12.0),
+    ("cr_handpoint", -21.0), ("cr_size_all", -22.0),
+];
+
+pub fn is_variable(name: &str) -> bool {
+    INSTANCE_VARIABLES.contains(&name) || GLOBAL_VARIABLES.contains(&name)
+}
+
+pub fn constant(name: &str) -> Option<f64> {
+    CONSTANTS
+        .iter()
+        .find(|(constant, _)| *constant == name)
+        .map(|&(_, value)| value)
+}
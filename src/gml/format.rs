@@ -0,0 +1,655 @@
+use super::ast::*;
+use super::lexer::{tokenize, Token};
+use super::{parse, ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStyle {
+    Braces,
+    BeginEnd,
+}
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub indent: String,
+    pub block_style: BlockStyle,
+    // End simple statements with a semicolon. Without this, existing semicolons are removed.
+    pub semicolons: bool,
+    // Put spaces around binary operators.
+    pub operator_spacing: bool,
+    // Wrap the conditions of if, while, with and so on in parentheses.
+    pub parenthesize_conditions: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: "    ".to_string(),
+            block_style: BlockStyle::Braces,
+            semicolons: true,
+            operator_spacing: true,
+            parenthesize_conditions: true,
+        }
+    }
+}
+
+struct Printer<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+    comments: Vec<Token>,
+    next_comment: usize,
+    // The end of the last thing printed, in the source.
+    last_end: usize,
+    depth: usize,
+    output: String,
+}
+
+impl<'a> Printer<'a> {
+    fn open(&self) -> &'static str {
+        match self.options.block_style {
+            BlockStyle::Braces => "{",
+            BlockStyle::BeginEnd => "begin",
+        }
+    }
+
+    fn close(&self) -> &'static str {
+        match self.options.block_style {
+            BlockStyle::Braces => "}",
+            BlockStyle::BeginEnd => "end",
+        }
+    }
+
+    // The semicolon for a statement that ends at `end`. Even without semicolons, one is needed
+    // when the next statement starts with something that would continue this one, like the `(`
+    // in `a = b; (c).d = 1` or the `-` in `a = b; -c.d = 1`.
+    fn semicolon(&self, end: usize) -> &'static str {
+        if self.options.semicolons {
+            return ";";
+        }
+        let mut rest = &self.source[end..];
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ';');
+            if rest.starts_with("//") {
+                rest = rest.find('\n').map_or("", |i| &rest[i..]);
+            } else if rest.starts_with("/*") {
+                rest = rest.find("*/").map_or("", |i| &rest[i + 2..]);
+            } else {
+                break;
+            }
+        }
+        if rest.starts_with(&['(', '[', '-', '+', '!', '~', '.'][..]) {
+            ";"
+        } else {
+            ""
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.depth {
+            self.output.push_str(&self.options.indent);
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    // Continues the previous line if it only closed a block, for `} else {` and `} until`.
+    fn join_close(&mut self, text: &str) -> bool {
+        let mut close = self.options.indent.repeat(self.depth);
+        close.push_str(self.close());
+        close.push('\n');
+        let line_start = self.output[..self.output.len().saturating_sub(1)]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        if self.output[line_start..] != close {
+            return false;
+        }
+        self.output.pop();
+        self.output.push(' ');
+        self.output.push_str(text);
+        self.output.push('\n');
+        true
+    }
+
+    // Keeps a single blank line where the source had one or more, except at the start of a block.
+    fn blank_line(&mut self, position: usize) {
+        let gap = &self.source[self.last_end.min(position)..position];
+        let at_start = self.output.is_empty()
+            || self.output.ends_with(&format!("{}\n", self.open()))
+            || self.output.ends_with("\n\n");
+        if !at_start && gap.matches('\n').count() >= 2 {
+            self.output.push('\n');
+        }
+    }
+
+    fn comment_text(&self, comment: Token) -> &'a str {
+        comment.text(self.source).trim_end()
+    }
+
+    // Prints the comments that come before `position` on lines of their own.
+    fn leading(&mut self, position: usize) {
+        while let Some(&comment) = self.comments.get(self.next_comment) {
+            if comment.span.start >= position {
+                break;
+            }
+            self.blank_line(comment.span.start);
+            let text = self.comment_text(comment);
+            self.line(text);
+            self.last_end = comment.span.end;
+            self.next_comment += 1;
+        }
+        self.blank_line(position);
+    }
+
+    // Appends a comment that followed `end` on the same line to the last printed line. A comment
+    // after other code, like the `{` of a following `else`, belongs with that code instead.
+    fn trailing(&mut self, end: usize) {
+        if let Some(&comment) = self.comments.get(self.next_comment) {
+            let gap = &self.source[end.min(comment.span.start)..comment.span.start];
+            if gap
+                .chars()
+                .all(|c| c == ';' || (c.is_whitespace() && c != '\n'))
+            {
+                let text = self.comment_text(comment);
+                self.output.pop();
+                self.output.push(' ');
+                self.output.push_str(text);
+                self.output.push('\n');
+                self.last_end = comment.span.end;
+                self.next_comment += 1;
+            }
+        }
+    }
+
+    fn expr(&self, expr: &Expr) -> String {
+        match &expr.kind {
+            // Literals are kept as written, so hex numbers and quote styles survive.
+            ExprKind::Real(_) | ExprKind::String(_) => {
+                self.source[expr.span.start..expr.span.end].to_string()
+            }
+            ExprKind::Identifier(name) => name.clone(),
+            ExprKind::Field(object, field) => format!("{}.{}", self.expr(object), field.name),
+            ExprKind::Index(target, indices) => {
+                let indices: Vec<String> = indices.iter().map(|e| self.expr(e)).collect();
+                format!("{}[{}]", self.expr(target), indices.join(", "))
+            }
+            ExprKind::Call(function, args) => {
+                let args: Vec<String> = args.iter().map(|e| self.expr(e)).collect();
+                format!("{}({})", function.name, args.join(", "))
+            }
+            ExprKind::Unary(op, operand) => {
+                let op = match op {
+                    UnaryOp::Not => "!",
+                    UnaryOp::Negate => "-",
+                    UnaryOp::Plus => "+",
+                    UnaryOp::BitNot => "~",
+                };
+                format!("{}{}", op, self.expr(operand))
+            }
+            ExprKind::Binary(op, left, right) => {
+                let symbol = op.symbol();
+                let spaced = self.options.operator_spacing
+                    || symbol.starts_with(|c: char| c.is_ascii_alphabetic());
+                if spaced {
+                    format!("{} {} {}", self.expr(left), symbol, self.expr(right))
+                } else {
+                    format!("{}{}{}", self.expr(left), symbol, self.expr(right))
+                }
+            }
+            ExprKind::Parenthesized(inner) => format!("({})", self.expr(inner)),
+            ExprKind::Error => String::new(),
+        }
+    }
+
+    fn condition(&self, expr: &Expr) -> String {
+        match expr.kind {
+            ExprKind::Parenthesized(_) => self.expr(expr),
+            _ if self.options.parenthesize_conditions => format!("({})", self.expr(expr)),
+            _ => self.expr(expr),
+        }
+    }
+
+    // The text of a statement that fits on one line, without its semicolon.
+    fn simple(&self, statement: &Stmt) -> Option<String> {
+        Some(match &statement.kind {
+            StmtKind::Var(names) | StmtKind::GlobalVar(names) => {
+                let keyword = match statement.kind {
+                    StmtKind::Var(_) => "var",
+                    _ => "globalvar",
+                };
+                let names: Vec<&str> = names.iter().map(|n| n.name.as_str()).collect();
+                format!("{} {}", keyword, names.join(", "))
+            }
+            StmtKind::Assign(target, op, value) => {
+                format!("{} {} {}", self.expr(target), op.symbol(), self.expr(value))
+            }
+            StmtKind::Expr(expr) => self.expr(expr),
+            StmtKind::Break => "break".to_string(),
+            StmtKind::Continue => "continue".to_string(),
+            StmtKind::Exit => "exit".to_string(),
+            StmtKind::Return(None) => "return".to_string(),
+            StmtKind::Return(Some(value)) => format!("return {}", self.expr(value)),
+            StmtKind::Empty => String::new(),
+            _ => return None,
+        })
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    // Prints the statements of a block and the comments before its end.
+    fn block_body(&mut self, block: &Stmt) {
+        self.depth += 1;
+        if let StmtKind::Block(statements) = &block.kind {
+            self.statements(statements);
+        }
+        self.flush_comments(block.span.end);
+        self.depth -= 1;
+    }
+
+    fn flush_comments(&mut self, position: usize) {
+        while let Some(&comment) = self.comments.get(self.next_comment) {
+            if comment.span.start >= position {
+                break;
+            }
+            let text = self.comment_text(comment);
+            self.line(text);
+            self.last_end = comment.span.end;
+            self.next_comment += 1;
+        }
+    }
+
+    // Prints `header` followed by the body of a compound statement.
+    fn body(&mut self, header: &str, join: bool, body: &Stmt) {
+        let open = self.open();
+        match &body.kind {
+            StmtKind::Block(_) => {
+                let text = format!("{} {}", header, open);
+                if !(join && self.join_close(&text)) {
+                    self.line(&text);
+                }
+                self.block_body(body);
+                let close = self.close();
+                self.line(close);
+                self.trailing(body.span.end);
+            }
+            StmtKind::Empty => {
+                let text = format!("{} {} {}", header, open, self.close());
+                if !(join && self.join_close(&text)) {
+                    self.line(&text);
+                }
+                self.trailing(body.span.end);
+            }
+            _ => {
+                if !(join && self.join_close(header)) {
+                    self.line(header);
+                }
+                self.last_end = self.last_end.max(body.span.start);
+                self.depth += 1;
+                self.statement(body);
+                self.depth -= 1;
+            }
+        }
+        self.last_end = self.last_end.max(body.span.end);
+    }
+
+    fn if_statement(&mut self, statement: &Stmt, join: bool) {
+        if let StmtKind::If(condition, then, otherwise) = &statement.kind {
+            let header = format!("if {}", self.condition(condition));
+            let header = if join {
+                format!("else {}", header)
+            } else {
+                header
+            };
+            self.body(&header, join, then);
+            match otherwise.as_deref() {
+                Some(otherwise) if matches!(otherwise.kind, StmtKind::If(..)) => {
+                    self.flush_comments(otherwise.span.start);
+                    self.if_statement(otherwise, true);
+                }
+                Some(otherwise) => {
+                    self.flush_comments(otherwise.span.start);
+                    self.body("else", true, otherwise);
+                }
+                None => (),
+            }
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        // Stray semicolons are dropped, but not the comments around them.
+        if statement.kind == StmtKind::Empty {
+            return;
+        }
+        self.leading(statement.span.start);
+
+        if let Some(text) = self.simple(statement) {
+            // Without its semicolon, a bare `return` would take the next line as its value.
+            let semicolon = if statement.kind == StmtKind::Return(None) {
+                ";"
+            } else {
+                self.semicolon(statement.span.end)
+            };
+            let text = format!("{}{}", text, semicolon);
+            self.line(&text);
+        } else {
+            match &statement.kind {
+                StmtKind::Block(_) => {
+                    let open = self.open();
+                    self.line(open);
+                    self.block_body(statement);
+                    let close = self.close();
+                    self.line(close);
+                }
+                StmtKind::If(..) => {
+                    // An else that follows a nested if on the next line isn't joined.
+                    self.if_statement(statement, false);
+                }
+                StmtKind::While(condition, body) => {
+                    let header = format!("while {}", self.condition(condition));
+                    self.body(&header, false, body);
+                }
+                StmtKind::With(target, body) => {
+                    let header = format!("with {}", self.condition(target));
+                    self.body(&header, false, body);
+                }
+                StmtKind::Repeat(count, body) => {
+                    let header = format!("repeat {}", self.condition(count));
+                    self.body(&header, false, body);
+                }
+                StmtKind::For(init, condition, step, body) => {
+                    let header = format!(
+                        "for ({}; {}; {})",
+                        self.simple(init).unwrap_or_default(),
+                        self.expr(condition),
+                        self.simple(step).unwrap_or_default()
+                    );
+                    self.body(&header, false, body);
+                }
+                StmtKind::DoUntil(body, condition) => {
+                    self.body("do", false, body);
+                    let until = format!(
+                        "until {}{}",
+                        self.condition(condition),
+                        self.semicolon(statement.span.end)
+                    );
+                    if !self.join_close(&until) {
+                        self.line(&until);
+                    }
+                }
+                StmtKind::Switch(value, body) => {
+                    let open = self.open();
+                    self.line(&format!("switch {} {}", self.condition(value), open));
+                    let depth = self.depth;
+                    for statement in body {
+                        let is_label =
+                            matches!(statement.kind, StmtKind::Case(_) | StmtKind::Default);
+                        self.depth = if is_label { depth + 1 } else { depth + 2 };
+                        self.statement(statement);
+                    }
+                    self.depth = depth + 1;
+                    self.flush_comments(statement.span.end);
+                    self.depth = depth;
+                    let close = self.close();
+                    self.line(close);
+                }
+                StmtKind::Case(value) => {
+                    let text = format!("case {}:", self.expr(value));
+                    self.line(&text);
+                }
+                StmtKind::Default => self.line("default:"),
+                _ => (),
+            }
+        }
+
+        self.last_end = self.last_end.max(statement.span.end);
+        self.flush_inner_comments(statement.span.end);
+        self.trailing(statement.span.end);
+    }
+
+    // Comments in the middle of a statement that wasn't broken up, such as between the arguments
+    // of a call, go after it.
+    fn flush_inner_comments(&mut self, end: usize) {
+        let start = self.comments.get(self.next_comment).map(|c| c.span.start);
+        if start.is_some_and(|start| start < end) {
+            self.flush_comments(end);
+        }
+    }
+}
+
+// Re-indents and normalizes GML. Code that doesn't parse is left alone and the errors returned.
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, Vec<ParseError>> {
+    let (program, errors) = parse(source);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let (tokens, _) = tokenize(source);
+    let mut printer = Printer {
+        source,
+        options,
+        comments: tokens.into_iter().filter(|t| t.is_comment()).collect(),
+        next_comment: 0,
+        last_end: 0,
+        depth: 0,
+        output: String::new(),
+    };
+    printer.statements(&program.statements);
+    printer.flush_comments(source.len());
+    Ok(printer.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gml::Span;
+
+    const SOURCES: &[&str] = &[
+        "x=1;y = 2\nif x>y{a=b}else if x<y then a=c else{a=d}",
+        "if (a) return; else b = 1;",
+        "return; x = 1",
+        "switch (k) { case 1: return; case 2: b = -x; break; default: exit }",
+        "while x < 10 do x += 1\nrepeat 3 { instance_create(x, y, obj_wall) }",
+        "for (i = 0; i < 10; i += 1) { arr[i, 0] = i * $1F }\ndo { i -= 1 } until i <= 0",
+        "with (other) { hp -= 1; if (hp <= 0) instance_destroy() }",
+        "var a, b; globalvar score_total;\na = 'single' + \"double\"; b = !a && ~a or a xor b",
+        "// leading comment\n\n\nx = 1; // trailing\n/* block */\nif (a) { // after brace\n    b = 1\n} else { // note\n}\n// at the end",
+        "if (a) {} else {\n// inside else\n}",
+        "begin x = 1; if a begin y = 2 end end",
+        "x = a mod 2 div 3 | b & c ^ d << 1 >> 2 != e == f <> g",
+        "x = a || b && c; y = (a || b) && c; z = a - (b - c)",
+        "a = b; (c).d = 1; e = f(g); (h).i = -2",
+    ];
+
+    fn variants() -> Vec<FormatOptions> {
+        let mut variants = Vec::new();
+        for &block_style in &[BlockStyle::Braces, BlockStyle::BeginEnd] {
+            for &semicolons in &[true, false] {
+                for &operator_spacing in &[true, false] {
+                    for &parenthesize_conditions in &[true, false] {
+                        variants.push(FormatOptions {
+                            block_style,
+                            semicolons,
+                            operator_spacing,
+                            parenthesize_conditions,
+                            ..FormatOptions::default()
+                        });
+                    }
+                }
+            }
+        }
+        variants
+    }
+
+    // The program with spans, parentheses and empty statements removed, which formatting is
+    // allowed to change.
+    fn meaning(source: &str) -> String {
+        let (program, errors) = parse(source);
+        assert_eq!(errors, vec![], "{:?}", source);
+        format!("{:?}", strip_statements(program.statements))
+    }
+
+    fn strip_statements(statements: Vec<Stmt>) -> Vec<Stmt> {
+        statements
+            .into_iter()
+            .filter(|s| s.kind != StmtKind::Empty)
+            .map(strip_statement)
+            .collect()
+    }
+
+    fn strip_boxed(statement: Box<Stmt>) -> Box<Stmt> {
+        Box::new(strip_statement(*statement))
+    }
+
+    fn strip_statement(statement: Stmt) -> Stmt {
+        let kind = match statement.kind {
+            StmtKind::Block(body) => StmtKind::Block(strip_statements(body)),
+            StmtKind::Var(names) => StmtKind::Var(names.into_iter().map(strip_name).collect()),
+            StmtKind::GlobalVar(names) => {
+                StmtKind::GlobalVar(names.into_iter().map(strip_name).collect())
+            }
+            StmtKind::Assign(target, op, value) => {
+                StmtKind::Assign(strip_expr(target), op, strip_expr(value))
+            }
+            StmtKind::Expr(e) => StmtKind::Expr(strip_expr(e)),
+            StmtKind::If(condition, then, otherwise) => StmtKind::If(
+                strip_expr(condition),
+                strip_boxed(then),
+                otherwise.map(strip_boxed),
+            ),
+            StmtKind::While(e, body) => StmtKind::While(strip_expr(e), strip_boxed(body)),
+            StmtKind::DoUntil(body, e) => StmtKind::DoUntil(strip_boxed(body), strip_expr(e)),
+            StmtKind::For(init, condition, step, body) => StmtKind::For(
+                strip_boxed(init),
+                strip_expr(condition),
+                strip_boxed(step),
+                strip_boxed(body),
+            ),
+            StmtKind::Repeat(e, body) => StmtKind::Repeat(strip_expr(e), strip_boxed(body)),
+            StmtKind::With(e, body) => StmtKind::With(strip_expr(e), strip_boxed(body)),
+            StmtKind::Switch(e, body) => StmtKind::Switch(strip_expr(e), strip_statements(body)),
+            StmtKind::Case(e) => StmtKind::Case(strip_expr(e)),
+            StmtKind::Return(e) => StmtKind::Return(e.map(strip_expr)),
+            kind => kind,
+        };
+        Stmt {
+            kind,
+            span: Span::new(0, 0),
+        }
+    }
+
+    fn strip_name(name: Identifier) -> Identifier {
+        Identifier {
+            name: name.name,
+            span: Span::new(0, 0),
+        }
+    }
+
+    fn strip_expr(expr: Expr) -> Expr {
+        let kind = match expr.kind {
+            ExprKind::Parenthesized(inner) => return strip_expr(*inner),
+            ExprKind::Field(object, field) => {
+                ExprKind::Field(Box::new(strip_expr(*object)), strip_name(field))
+            }
+            ExprKind::Index(target, indices) => ExprKind::Index(
+                Box::new(strip_expr(*target)),
+                indices.into_iter().map(strip_expr).collect(),
+            ),
+            ExprKind::Call(function, args) => ExprKind::Call(
+                strip_name(function),
+                args.into_iter().map(strip_expr).collect(),
+            ),
+            ExprKind::Unary(op, operand) => ExprKind::Unary(op, Box::new(strip_expr(*operand))),
+            ExprKind::Binary(op, left, right) => ExprKind::Binary(
+                op,
+                Box::new(strip_expr(*left)),
+                Box::new(strip_expr(*right)),
+            ),
+            kind => kind,
+        };
+        Expr {
+            kind,
+            span: Span::new(0, 0),
+        }
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for options in variants() {
+            for source in SOURCES {
+                let once = format(source, &options)
+                    .unwrap_or_else(|e| panic!("{:?} doesn't parse: {:?}", source, e));
+                let twice = format(&once, &options)
+                    .unwrap_or_else(|e| panic!("{:?} formatted as {:?}: {:?}", source, once, e));
+                assert_eq!(once, twice, "{:?} with {:?}", source, options);
+            }
+        }
+    }
+
+    #[test]
+    fn formatting_keeps_meaning() {
+        for options in variants() {
+            for source in SOURCES {
+                let formatted = format(source, &options).unwrap();
+                assert_eq!(
+                    meaning(&formatted),
+                    meaning(source),
+                    "{:?} with {:?} formatted as {:?}",
+                    source,
+                    options,
+                    formatted
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn semicolons_are_kept_before_a_continuation() {
+        let options = FormatOptions {
+            semicolons: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format("a = b; (c).d = 1; e = f; g.h = 2", &options).unwrap(),
+            "a = b;\n(c).d = 1\ne = f\ng.h = 2\n"
+        );
+        assert_eq!(
+            format("do x += 1 until x > 3 // done\n(o).y = 0", &options).unwrap(),
+            "do\n    x += 1\nuntil (x > 3); // done\n(o).y = 0\n"
+        );
+    }
+
+    #[test]
+    fn bare_return_keeps_its_semicolon() {
+        let options = FormatOptions {
+            semicolons: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format("if (a) return; else b = 1;", &options).unwrap(),
+            "if (a)\n    return;\nelse\n    b = 1\n"
+        );
+        assert_eq!(
+            format("return; x = 1", &options).unwrap(),
+            "return;\nx = 1\n"
+        );
+    }
+
+    #[test]
+    fn comments_stay_in_their_block() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            format("if (a) { b = 1 } else { // note\n}", &options).unwrap(),
+            "if (a) {\n    b = 1;\n} else {\n    // note\n}\n"
+        );
+        assert_eq!(
+            format("x = 1; // trailing\ny = 2", &options).unwrap(),
+            "x = 1; // trailing\ny = 2;\n"
+        );
+    }
+
+    #[test]
+    fn code_with_errors_is_not_formatted() {
+        assert!(format("x = ", &FormatOptions::default()).is_err());
+    }
+}
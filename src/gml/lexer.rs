@@ -0,0 +1,250 @@
+use super::{ParseError, Span};
+
+pub const KEYWORDS: [&str; 26] = [
+    "var",
+    "globalvar",
+    "if",
+    "then",
+    "else",
+    "while",
+    "do",
+    "until",
+    "for",
+    "repeat",
+    "switch",
+    "case",
+    "default",
+    "break",
+    "continue",
+    "exit",
+    "return",
+    "with",
+    "begin",
+    "end",
+    "and",
+    "or",
+    "xor",
+    "not",
+    "div",
+    "mod",
+];
+
+// Longest first, so the lexer can take the first match.
+const OPERATORS: [&str; 34] = [
+    ":=", "==", "!=", "<>", "<=", ">=", "&&", "||", "^^", "<<", ">>", "+=", "-=", "*=", "/=", "&=",
+    "|=", "^=", "+", "-", "*", "/", "=", "<", ">", "!", "~", "&", "|", "^", ".", ",", ";", ":",
+];
+
+const BRACKETS: [char; 6] = ['(', ')', '[', ']', '{', '}'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Keyword,
+    // Decimal numbers as well as hex numbers written with `$` or `#`.
+    Number,
+    // Either quote style; GML strings have no escape sequences.
+    String,
+    Operator,
+    Bracket,
+    LineComment,
+    BlockComment,
+    Unknown,
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.start..self.span.end]
+    }
+
+    pub fn is_comment(&self) -> bool {
+        self.kind == TokenKind::LineComment || self.kind == TokenKind::BlockComment
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+// Splits GML source into tokens, comments included, ending with a single `Eof` token. Problems
+// like unterminated strings are reported but still produce a token so parsing can continue.
+pub fn tokenize(source: &str) -> (Vec<Token>, Vec<ParseError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    let bytes = source.as_bytes();
+    let mut position = 0;
+
+    while position < source.len() {
+        let rest = &source[position..];
+        let c = rest.chars().next().unwrap();
+        if c.is_whitespace() {
+            position += c.len_utf8();
+            continue;
+        }
+
+        let start = position;
+        let (kind, length) = if rest.starts_with("//") {
+            (
+                TokenKind::LineComment,
+                rest.find('\n').unwrap_or(rest.len()),
+            )
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            match body.find("*/") {
+                Some(end) => (TokenKind::BlockComment, end + 4),
+                None => {
+                    errors.push(ParseError::new(
+                        "unterminated comment",
+                        Span::new(start, source.len()),
+                    ));
+                    (TokenKind::BlockComment, rest.len())
+                }
+            }
+        } else if c == '"' || c == '\'' {
+            match rest[1..].find(c) {
+                Some(end) => (TokenKind::String, end + 2),
+                None => {
+                    errors.push(ParseError::new(
+                        "unterminated string",
+                        Span::new(start, source.len()),
+                    ));
+                    (TokenKind::String, rest.len())
+                }
+            }
+        } else if (c == '$' || c == '#') && rest[1..].starts_with(|c: char| c.is_ascii_hexdigit()) {
+            let digits = rest[1..]
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(rest.len() - 1);
+            (TokenKind::Number, digits + 1)
+        } else if c.is_ascii_digit()
+            || (c == '.' && bytes.get(position + 1).is_some_and(u8::is_ascii_digit))
+        {
+            let mut length = 0;
+            let mut seen_dot = false;
+            for c in rest.chars() {
+                if c == '.' && !seen_dot {
+                    seen_dot = true;
+                } else if !c.is_ascii_digit() {
+                    break;
+                }
+                length += 1;
+            }
+            (TokenKind::Number, length)
+        } else if is_identifier_start(c) {
+            let length = rest
+                .find(|c: char| !is_identifier_char(c))
+                .unwrap_or(rest.len());
+            let kind = if KEYWORDS.contains(&&rest[..length]) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Identifier
+            };
+            (kind, length)
+        } else if BRACKETS.contains(&c) {
+            (TokenKind::Bracket, 1)
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            (TokenKind::Operator, op.len())
+        } else {
+            errors.push(ParseError::new(
+                format!("unexpected character {:?}", c),
+                Span::new(start, start + c.len_utf8()),
+            ));
+            (TokenKind::Unknown, c.len_utf8())
+        };
+
+        position += length;
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, position),
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span::new(source.len(), source.len()),
+    });
+    (tokens, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        let (tokens, errors) = tokenize(source);
+        assert_eq!(errors, vec![]);
+        tokens.iter().map(|t| (t.kind, t.text(source))).collect()
+    }
+
+    #[test]
+    fn splits_tokens() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("if x>=$1F {y:=.5 div 2}"),
+            vec![
+                (Keyword, "if"),
+                (Identifier, "x"),
+                (Operator, ">="),
+                (Number, "$1F"),
+                (Bracket, "{"),
+                (Identifier, "y"),
+                (Operator, ":="),
+                (Number, ".5"),
+                (Keyword, "div"),
+                (Number, "2"),
+                (Bracket, "}"),
+                (Eof, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_strings() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("a = 'it''s' // note\n/* multi\nline */ \"x\""),
+            vec![
+                (Identifier, "a"),
+                (Operator, "="),
+                (String, "'it'"),
+                (String, "'s'"),
+                (LineComment, "// note"),
+                (BlockComment, "/* multi\nline */"),
+                (String, "\"x\""),
+                (Eof, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_problems_but_keeps_going() {
+        let source = "a = @ \"open";
+        let (tokens, errors) = tokenize(source);
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["unexpected character '@'", "unterminated string"]
+        );
+        assert_eq!(tokens[2].kind, TokenKind::Unknown);
+        assert_eq!(tokens[3].kind, TokenKind::String);
+        assert_eq!(tokens[3].span, Span::new(6, source.len()));
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Eof);
+    }
+
+    #[test]
+    fn reports_unterminated_comments() {
+        let (tokens, errors) = tokenize("x /* never closed");
+        assert_eq!(errors[0].message, "unterminated comment");
+        assert_eq!(tokens[1].kind, TokenKind::BlockComment);
+    }
+}
@@ -0,0 +1,756 @@
+use super::ast::*;
+use super::lexer::{tokenize, Token, TokenKind};
+use super::{ParseError, Span};
+
+// Keywords that can only start a statement, used to resynchronize after an error.
+const STATEMENT_KEYWORDS: [&str; 16] = [
+    "var",
+    "globalvar",
+    "if",
+    "while",
+    "do",
+    "for",
+    "repeat",
+    "switch",
+    "case",
+    "default",
+    "break",
+    "continue",
+    "exit",
+    "return",
+    "with",
+    "begin",
+];
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> (Parser<'a>, Vec<ParseError>) {
+        let (tokens, errors) = tokenize(source);
+        // Unknown characters were already reported by the lexer.
+        let tokens = tokens
+            .into_iter()
+            .filter(|t| !t.is_comment() && t.kind != TokenKind::Unknown)
+            .collect();
+        let parser = Parser {
+            source,
+            tokens,
+            position: 0,
+            errors: Vec::new(),
+        };
+        (parser, errors)
+    }
+
+    fn peek(&self) -> Token {
+        self.tokens[self.position]
+    }
+
+    fn peek_at(&self, offset: usize) -> Token {
+        let index = (self.position + offset).min(self.tokens.len() - 1);
+        self.tokens[index]
+    }
+
+    fn text(&self, token: Token) -> &'a str {
+        token.text(self.source)
+    }
+
+    fn at(&self, text: &str) -> bool {
+        let token = self.peek();
+        token.kind != TokenKind::String && self.text(token) == text
+    }
+
+    fn at_eof(&self) -> bool {
+        self.peek().kind == TokenKind::Eof
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek();
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.at(text) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error<S: Into<String>>(&mut self, message: S, span: Span) {
+        self.errors.push(ParseError::new(message, span));
+    }
+
+    fn expect(&mut self, text: &str) -> bool {
+        if self.eat(text) {
+            return true;
+        }
+        let token = self.peek();
+        let found = match token.kind {
+            TokenKind::Eof => "end of code".to_string(),
+            _ => format!("`{}`", self.text(token)),
+        };
+        self.error(format!("expected `{}`, found {}", text, found), token.span);
+        false
+    }
+
+    // The end of the last consumed token, for building spans.
+    fn previous_end(&self) -> usize {
+        if self.position == 0 {
+            0
+        } else {
+            self.tokens[self.position - 1].span.end
+        }
+    }
+
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.previous_end().max(start))
+    }
+
+    fn at_block_end(&self) -> bool {
+        self.at("}") || self.at("end") || self.at_eof()
+    }
+
+    // Skips to what looks like the start of the next statement: after a semicolon, at a new line
+    // or at a statement keyword.
+    fn synchronize(&mut self) {
+        if self.position > 0 && self.text(self.tokens[self.position - 1]) == ";" {
+            return;
+        }
+        while !self.at_eof() {
+            if self.eat(";") {
+                return;
+            }
+            let token = self.peek();
+            let gap = &self.source[self.previous_end().min(token.span.start)..token.span.start];
+            if gap.contains('\n')
+                || self.at_block_end()
+                || (token.kind == TokenKind::Keyword
+                    && STATEMENT_KEYWORDS.contains(&self.text(token)))
+            {
+                return;
+            }
+            self.advance();
+        }
+    }
+
+    fn identifier(&mut self) -> Option<Identifier> {
+        let token = self.peek();
+        if token.kind == TokenKind::Identifier {
+            self.advance();
+            Some(Identifier {
+                name: self.text(token).to_string(),
+                span: token.span,
+            })
+        } else {
+            self.error("expected a name", token.span);
+            None
+        }
+    }
+
+    // Parses statements until `}`, `end` or the end of the code, recovering from errors.
+    fn statements(&mut self) -> Vec<Stmt> {
+        let mut statements = Vec::new();
+        while !self.at_block_end() {
+            let position = self.position;
+            let errors = self.errors.len();
+            statements.push(self.statement());
+            if self.errors.len() > errors {
+                self.synchronize();
+            }
+            if self.position == position {
+                let token = self.advance();
+                self.error(format!("unexpected `{}`", self.text(token)), token.span);
+            }
+        }
+        statements
+    }
+
+    fn block(&mut self, start: usize) -> Stmt {
+        let body = self.statements();
+        if !self.eat("}") && !self.eat("end") {
+            let span = self.peek().span;
+            self.error("unclosed block", span);
+        }
+        Stmt {
+            kind: StmtKind::Block(body),
+            span: self.span_from(start),
+        }
+    }
+
+    fn statement(&mut self) -> Stmt {
+        let token = self.peek();
+        let start = token.span.start;
+        let keyword = if token.kind == TokenKind::Keyword || token.kind == TokenKind::Bracket {
+            self.text(token)
+        } else {
+            ""
+        };
+
+        let kind = match keyword {
+            "{" | "begin" => {
+                self.advance();
+                return self.block(start);
+            }
+            "var" | "globalvar" => {
+                self.advance();
+                let mut names = Vec::new();
+                while let Some(name) = self.identifier() {
+                    names.push(name);
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                if keyword == "var" {
+                    StmtKind::Var(names)
+                } else {
+                    StmtKind::GlobalVar(names)
+                }
+            }
+            "if" => {
+                self.advance();
+                let condition = self.expression();
+                self.eat("then");
+                let then = Box::new(self.statement());
+                let otherwise = if self.eat("else") {
+                    Some(Box::new(self.statement()))
+                } else {
+                    None
+                };
+                return Stmt {
+                    kind: StmtKind::If(condition, then, otherwise),
+                    span: self.span_from(start),
+                };
+            }
+            "while" | "with" => {
+                self.advance();
+                let value = self.expression();
+                self.eat("do");
+                let body = Box::new(self.statement());
+                let kind = if keyword == "while" {
+                    StmtKind::While(value, body)
+                } else {
+                    StmtKind::With(value, body)
+                };
+                return Stmt {
+                    kind,
+                    span: self.span_from(start),
+                };
+            }
+            "repeat" => {
+                self.advance();
+                let count = self.expression();
+                let body = Box::new(self.statement());
+                return Stmt {
+                    kind: StmtKind::Repeat(count, body),
+                    span: self.span_from(start),
+                };
+            }
+            "do" => {
+                self.advance();
+                let body = Box::new(self.statement());
+                self.expect("until");
+                StmtKind::DoUntil(body, self.expression())
+            }
+            "for" => {
+                self.advance();
+                self.expect("(");
+                let init = Box::new(self.simple_statement());
+                let condition = self.expression();
+                self.eat(";");
+                let step = if self.at(")") {
+                    let span = Span::new(self.peek().span.start, self.peek().span.start);
+                    Stmt {
+                        kind: StmtKind::Empty,
+                        span,
+                    }
+                } else {
+                    self.simple_statement()
+                };
+                let step = Box::new(step);
+                self.expect(")");
+                let body = Box::new(self.statement());
+                return Stmt {
+                    kind: StmtKind::For(init, condition, step, body),
+                    span: self.span_from(start),
+                };
+            }
+            "switch" => {
+                self.advance();
+                let value = self.expression();
+                let body = if self.eat("{") || self.eat("begin") {
+                    match self.block(start).kind {
+                        StmtKind::Block(body) => body,
+                        _ => Vec::new(),
+                    }
+                } else {
+                    let span = self.peek().span;
+                    self.error("expected `{` after switch", span);
+                    Vec::new()
+                };
+                return Stmt {
+                    kind: StmtKind::Switch(value, body),
+                    span: self.span_from(start),
+                };
+            }
+            "case" => {
+                self.advance();
+                let value = self.expression();
+                self.expect(":");
+                return Stmt {
+                    kind: StmtKind::Case(value),
+                    span: self.span_from(start),
+                };
+            }
+            "default" => {
+                self.advance();
+                self.expect(":");
+                return Stmt {
+                    kind: StmtKind::Default,
+                    span: self.span_from(start),
+                };
+            }
+            "break" | "continue" | "exit" => {
+                self.advance();
+                match keyword {
+                    "break" => StmtKind::Break,
+                    "continue" => StmtKind::Continue,
+                    _ => StmtKind::Exit,
+                }
+            }
+            "return" => {
+                self.advance();
+                if self.at(";") || self.at_block_end() {
+                    StmtKind::Return(None)
+                } else {
+                    StmtKind::Return(Some(self.expression()))
+                }
+            }
+            _ => return self.simple_statement(),
+        };
+        self.eat(";");
+        Stmt {
+            kind,
+            span: self.span_from(start),
+        }
+    }
+
+    // An assignment, call or empty statement, with its optional semicolon.
+    fn simple_statement(&mut self) -> Stmt {
+        let start = self.peek().span.start;
+        if self.eat(";") {
+            return Stmt {
+                kind: StmtKind::Empty,
+                span: self.span_from(start),
+            };
+        }
+
+        let target = self.postfix();
+        let op = match self.text(self.peek()) {
+            _ if self.peek().kind != TokenKind::Operator => None,
+            "=" | ":=" => Some(AssignOp::Assign),
+            "+=" => Some(AssignOp::Add),
+            "-=" => Some(AssignOp::Subtract),
+            "*=" => Some(AssignOp::Multiply),
+            "/=" => Some(AssignOp::Divide),
+            "&=" => Some(AssignOp::BitAnd),
+            "|=" => Some(AssignOp::BitOr),
+            "^=" => Some(AssignOp::BitXor),
+            _ => None,
+        };
+
+        let kind = match op {
+            Some(op) => {
+                if !matches!(
+                    target.kind,
+                    ExprKind::Identifier(_)
+                        | ExprKind::Field(..)
+                        | ExprKind::Index(..)
+                        | ExprKind::Error
+                ) {
+                    self.error("cannot assign to this expression", target.span);
+                }
+                self.advance();
+                StmtKind::Assign(target, op, self.expression())
+            }
+            None => {
+                let expr = self.binary(target, 0);
+                if !matches!(expr.kind, ExprKind::Call(..) | ExprKind::Error) {
+                    self.error("expected an assignment or function call", expr.span);
+                }
+                StmtKind::Expr(expr)
+            }
+        };
+        self.eat(";");
+        Stmt {
+            kind,
+            span: self.span_from(start),
+        }
+    }
+
+    fn expression(&mut self) -> Expr {
+        let left = self.unary();
+        self.binary(left, 0)
+    }
+
+    fn binary_op(&self) -> Option<BinaryOp> {
+        let token = self.peek();
+        if token.kind != TokenKind::Operator && token.kind != TokenKind::Keyword {
+            return None;
+        }
+        Some(match self.text(token) {
+            "+" => BinaryOp::Add,
+            "-" => BinaryOp::Subtract,
+            "*" => BinaryOp::Multiply,
+            "/" => BinaryOp::Divide,
+            "div" => BinaryOp::IntDivide,
+            "mod" => BinaryOp::Modulo,
+            // `=` compares when it appears inside an expression.
+            "==" | "=" => BinaryOp::Equal,
+            "!=" | "<>" => BinaryOp::NotEqual,
+            "<" => BinaryOp::Less,
+            "<=" => BinaryOp::LessEqual,
+            ">" => BinaryOp::Greater,
+            ">=" => BinaryOp::GreaterEqual,
+            "&&" | "and" => BinaryOp::And,
+            "||" | "or" => BinaryOp::Or,
+            "^^" | "xor" => BinaryOp::Xor,
+            "&" => BinaryOp::BitAnd,
+            "|" => BinaryOp::BitOr,
+            "^" => BinaryOp::BitXor,
+            "<<" => BinaryOp::ShiftLeft,
+            ">>" => BinaryOp::ShiftRight,
+            _ => return None,
+        })
+    }
+
+    // Precedence climbing: keeps combining `left` with operators that bind tighter than
+    // `min_precedence`.
+    fn binary(&mut self, mut left: Expr, min_precedence: u8) -> Expr {
+        while let Some(op) = self.binary_op() {
+            if op.precedence() <= min_precedence {
+                break;
+            }
+            self.advance();
+            let right = self.unary();
+            let right = self.binary(right, op.precedence());
+            let span = left.span.to(right.span);
+            left = Expr {
+                kind: ExprKind::Binary(op, Box::new(left), Box::new(right)),
+                span,
+            };
+        }
+        left
+    }
+
+    fn unary(&mut self) -> Expr {
+        let token = self.peek();
+        let op = match self.text(token) {
+            _ if token.kind != TokenKind::Operator && token.kind != TokenKind::Keyword => None,
+            "!" | "not" => Some(UnaryOp::Not),
+            "-" => Some(UnaryOp::Negate),
+            "+" => Some(UnaryOp::Plus),
+            "~" => Some(UnaryOp::BitNot),
+            _ => None,
+        };
+        match op {
+            Some(op) => {
+                self.advance();
+                let operand = self.unary();
+                let span = token.span.to(operand.span);
+                Expr {
+                    kind: ExprKind::Unary(op, Box::new(operand)),
+                    span,
+                }
+            }
+            None => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Expr {
+        let mut expr = self.primary();
+        loop {
+            if self.at(".") && self.peek_at(1).kind == TokenKind::Identifier {
+                self.advance();
+                let field = self.identifier().unwrap();
+                let span = expr.span.to(field.span);
+                expr = Expr {
+                    kind: ExprKind::Field(Box::new(expr), field),
+                    span,
+                };
+            } else if self.at("[") {
+                self.advance();
+                let mut indices = vec![self.expression()];
+                while self.eat(",") {
+                    indices.push(self.expression());
+                }
+                self.expect("]");
+                let span = self.span_from(expr.span.start);
+                expr = Expr {
+                    kind: ExprKind::Index(Box::new(expr), indices),
+                    span,
+                };
+            } else {
+                return expr;
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Expr {
+        let token = self.peek();
+        let text = self.text(token);
+        let kind = match token.kind {
+            TokenKind::Number => {
+                self.advance();
+                let value = if text.starts_with('$') || text.starts_with('#') {
+                    u64::from_str_radix(&text[1..], 16).map(|v| v as f64).ok()
+                } else {
+                    text.parse().ok()
+                };
+                match value {
+                    Some(value) => ExprKind::Real(value),
+                    None => {
+                        self.error("invalid number", token.span);
+                        ExprKind::Error
+                    }
+                }
+            }
+            TokenKind::String => {
+                self.advance();
+                // An unterminated string has no closing quote.
+                let end = if text.len() > 1 && text.ends_with(&text[..1]) {
+                    text.len() - 1
+                } else {
+                    text.len()
+                };
+                ExprKind::String(text[1..end].to_string())
+            }
+            TokenKind::Identifier => {
+                self.advance();
+                if self.at("(") {
+                    self.advance();
+                    let mut args = Vec::new();
+                    if !self.at(")") {
+                        loop {
+                            args.push(self.expression());
+                            if !self.eat(",") {
+                                break;
+                            }
+                        }
+                    }
+                    self.expect(")");
+                    let name = Identifier {
+                        name: text.to_string(),
+                        span: token.span,
+                    };
+                    ExprKind::Call(name, args)
+                } else {
+                    ExprKind::Identifier(text.to_string())
+                }
+            }
+            TokenKind::Bracket if text == "(" => {
+                self.advance();
+                let inner = self.expression();
+                self.expect(")");
+                ExprKind::Parenthesized(Box::new(inner))
+            }
+            _ => {
+                let found = match token.kind {
+                    TokenKind::Eof => "end of code".to_string(),
+                    _ => format!("`{}`", text),
+                };
+                self.error(
+                    format!("expected an expression, found {}", found),
+                    token.span,
+                );
+                return Expr {
+                    kind: ExprKind::Error,
+                    span: Span::new(token.span.start, token.span.start),
+                };
+            }
+        };
+        Expr {
+            kind,
+            span: self.span_from(token.span.start),
+        }
+    }
+}
+
+// Parses GM8 GML. Parsing always produces a program; anything that couldn't be parsed is
+// reported in the errors and left out or replaced with an error node.
+pub fn parse(source: &str) -> (Program, Vec<ParseError>) {
+    let (mut parser, mut errors) = Parser::new(source);
+    let mut statements = Vec::new();
+    while !parser.at_eof() {
+        statements.extend(parser.statements());
+        // A stray `}` or `end` at the top level.
+        if !parser.at_eof() {
+            let token = parser.advance();
+            let message = format!("unexpected `{}`", parser.text(token));
+            parser.error(message, token.span);
+        }
+    }
+
+    errors.extend(parser.errors);
+    errors.sort_by_key(|e| e.span.start);
+    (Program { statements }, errors)
+}
+
+// Parses a single expression, like a constant's value or an action argument.
+pub fn parse_expression(source: &str) -> (Expr, Vec<ParseError>) {
+    let (mut parser, mut errors) = Parser::new(source);
+    let expr = parser.expression();
+    if !parser.at_eof() {
+        let token = parser.advance();
+        let message = format!("unexpected `{}`", parser.text(token));
+        parser.error(message, token.span);
+    }
+
+    errors.extend(parser.errors);
+    errors.sort_by_key(|e| e.span.start);
+    (expr, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes expressions in prefix form, e.g. `(+ a (* b c))`, so tests can compare shapes.
+    fn sexpr(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Real(n) => n.to_string(),
+            ExprKind::String(s) => format!("{:?}", s),
+            ExprKind::Identifier(name) => name.clone(),
+            ExprKind::Field(object, field) => format!("(. {} {})", sexpr(object), field.name),
+            ExprKind::Index(target, indices) => {
+                let indices: Vec<_> = indices.iter().map(sexpr).collect();
+                format!("([] {} {})", sexpr(target), indices.join(" "))
+            }
+            ExprKind::Call(function, args) => {
+                let args: Vec<_> = args.iter().map(sexpr).collect();
+                format!("(call {} {})", function.name, args.join(" "))
+            }
+            ExprKind::Unary(op, operand) => format!("({:?} {})", op, sexpr(operand)),
+            ExprKind::Binary(op, left, right) => {
+                format!("({} {} {})", op.symbol(), sexpr(left), sexpr(right))
+            }
+            ExprKind::Parenthesized(inner) => sexpr(inner),
+            ExprKind::Error => "error".to_string(),
+        }
+    }
+
+    fn expression(source: &str) -> String {
+        let (expr, errors) = parse_expression(source);
+        assert_eq!(errors, vec![], "{:?}", source);
+        sexpr(&expr)
+    }
+
+    fn statements(source: &str) -> (Vec<Stmt>, Vec<String>) {
+        let (program, errors) = parse(source);
+        let messages = errors.into_iter().map(|e| e.message).collect();
+        (program.statements, messages)
+    }
+
+    #[test]
+    fn parses_precedence() {
+        assert_eq!(expression("a + b * c"), "(+ a (* b c))");
+        assert_eq!(expression("a - b - c"), "(- (- a b) c)");
+        assert_eq!(expression("a or b and c = d"), "(&& (|| a b) (== c d))");
+        assert_eq!(expression("(a | b) & c << 2"), "(& (| a b) (<< c 2))");
+        assert_eq!(expression("x mod 2 div 3"), "(div (mod x 2) 3)");
+        assert_eq!(expression("-a.b[1, 2]"), "(Negate ([] (. a b) 1 2))");
+        assert_eq!(expression("not f(1, 'a')"), "(Not (call f 1 \"a\"))");
+        assert_eq!(expression("$ff + 1.5"), "(+ 255 1.5)");
+    }
+
+    #[test]
+    fn parses_mixed_operators_left_to_right() {
+        assert_eq!(expression("a || b && c"), "(&& (|| a b) c)");
+        assert_eq!(expression("a && b || c"), "(|| (&& a b) c)");
+        assert_eq!(expression("a ^^ b || c && d"), "(&& (|| (^^ a b) c) d)");
+        assert_eq!(expression("a | b & c"), "(& (| a b) c)");
+        assert_eq!(expression("a & b ^ c | d"), "(| (^ (& a b) c) d)");
+        assert_eq!(expression("a | b == c & d"), "(== (| a b) (& c d))");
+        assert_eq!(expression("a || b | c"), "(|| a (| b c))");
+    }
+
+    #[test]
+    fn parses_statements() {
+        let (program, errors) = statements(
+            "var a, b; a = 1 b += 2\nif a then b = 3 else { exit }\nwith obj_wall instance_destroy()",
+        );
+        assert_eq!(errors, Vec::<String>::new());
+        let kinds: Vec<_> = program
+            .iter()
+            .map(|s| match &s.kind {
+                StmtKind::Var(names) => format!("var {}", names.len()),
+                StmtKind::Assign(_, op, _) => op.symbol().to_string(),
+                StmtKind::If(_, _, otherwise) => format!("if else={}", otherwise.is_some()),
+                StmtKind::With(..) => "with".to_string(),
+                kind => format!("{:?}", kind),
+            })
+            .collect();
+        assert_eq!(kinds, vec!["var 2", "=", "+=", "if else=true", "with"]);
+    }
+
+    #[test]
+    fn parses_loops_and_switch() {
+        let (program, errors) = statements(
+            "for (i = 0; i < 3; i += 1) {}\ndo i -= 1 until i < 0\nswitch (i) { case 1: break; default: return 2 }",
+        );
+        assert_eq!(errors, Vec::<String>::new());
+        assert!(matches!(program[0].kind, StmtKind::For(..)));
+        assert!(matches!(program[1].kind, StmtKind::DoUntil(..)));
+        match &program[2].kind {
+            StmtKind::Switch(_, body) => assert_eq!(body.len(), 4),
+            kind => panic!("expected a switch, found {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn recovers_after_errors() {
+        let (program, errors) = statements("a = ;\nb = 2\nc + 1\nd = 4");
+        assert_eq!(
+            errors,
+            vec![
+                "expected an expression, found `;`",
+                "expected an assignment or function call",
+            ]
+        );
+        let targets: Vec<_> = program
+            .iter()
+            .filter_map(|s| match &s.kind {
+                StmtKind::Assign(target, _, _) => Some(sexpr(target)),
+                _ => None,
+            })
+            .collect();
+        assert!(targets.contains(&"b".to_string()));
+        assert!(targets.contains(&"d".to_string()));
+    }
+
+    #[test]
+    fn reports_unclosed_and_stray_blocks() {
+        let (_, errors) = statements("if (a) { b = 1");
+        assert_eq!(errors, vec!["unclosed block"]);
+        let (program, errors) = statements("a = 1 } b = 2");
+        assert_eq!(errors, vec!["unexpected `}`"]);
+        assert_eq!(program.len(), 2);
+    }
+
+    #[test]
+    fn rejects_trailing_input_after_an_expression() {
+        let (_, errors) = parse_expression("1 + 2 3");
+        assert_eq!(errors[0].message, "unexpected `3`");
+        let (expr, errors) = parse_expression("1 +");
+        assert!(!errors.is_empty());
+        assert_eq!(sexpr(&expr), "(+ 1 error)");
+    }
+}
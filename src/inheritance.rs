@@ -0,0 +1,161 @@
+use crate::event::Event;
+use crate::game::{Game, Object, ObjectEvent, ObjectId, Sprite};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Every object above `id` in its inheritance chain, nearest first. Stops at a missing parent or
+// when the chain loops back on itself.
+pub fn ancestors(game: &Game, id: ObjectId) -> Vec<&Object> {
+    let mut ancestors: Vec<&Object> = vec![];
+    let mut parent = game.object(id).and_then(|object| object.parent);
+    while let Some(object) = game.object(parent) {
+        if object.id == id || ancestors.iter().any(|a| a.id == object.id) {
+            break;
+        }
+        ancestors.push(object);
+        parent = object.parent;
+    }
+    ancestors
+}
+
+// Every object that has `id` somewhere in its inheritance chain.
+pub fn descendants(game: &Game, id: ObjectId) -> BTreeSet<ObjectId> {
+    game.objects
+        .iter()
+        .filter(|object| ancestors(game, object.id).iter().any(|a| a.id == id))
+        .map(|object| object.id)
+        .collect()
+}
+
+// Whether `id` is `ancestor` or inherits from it. This is how collision events and `with` match
+// objects.
+pub fn is_a(game: &Game, id: ObjectId, ancestor: ObjectId) -> bool {
+    id == ancestor || ancestors(game, id).iter().any(|a| a.id == ancestor)
+}
+
+// Each inheritance cycle once, as the objects in it starting from the lowest id. The editor
+// doesn't allow these, but a modified game file can still contain them.
+pub fn cycles(game: &Game) -> Vec<Vec<ObjectId>> {
+    let mut cycles = vec![];
+    for object in &game.objects {
+        let mut chain = vec![object.id];
+        let mut parent = object.parent;
+        while let Some(next) = game.object(parent) {
+            if let Some(start) = chain.iter().position(|&id| id == next.id) {
+                // Only report the cycle from the object with the lowest id in it.
+                let cycle = &chain[start..];
+                if start == 0 && cycle.iter().all(|&id| id >= object.id) {
+                    cycles.push(cycle.to_vec());
+                }
+                break;
+            }
+            chain.push(next.id);
+            parent = next.parent;
+        }
+    }
+    cycles
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InheritedEvent<'a> {
+    pub event: &'a ObjectEvent,
+    // The object the event is defined on, which is either the object itself or an ancestor.
+    pub defined_by: &'a Object,
+}
+
+// The events an instance of `id` responds to, ordered by event type and number. An event on a
+// child replaces the parent's event with the same type and number.
+pub fn effective_events(game: &Game, id: ObjectId) -> Vec<InheritedEvent<'_>> {
+    let mut events = BTreeMap::new();
+    let chain = game.object(id).into_iter().chain(ancestors(game, id));
+    for object in chain {
+        for event in &object.events {
+            events
+                .entry((event.event_type, event.event_number))
+                .or_insert(InheritedEvent {
+                    event,
+                    defined_by: object,
+                });
+        }
+    }
+    events.into_values().collect()
+}
+
+// The collision events of `id` that fire when it touches an instance of `other`. A collision
+// event with a parent object also fires for every object that inherits from it.
+pub fn collision_events(game: &Game, id: ObjectId, other: ObjectId) -> Vec<InheritedEvent<'_>> {
+    effective_events(game, id)
+        .into_iter()
+        .filter(|inherited| match inherited.event.event() {
+            Event::Collision(target) => target.is_some_and(|target| is_a(game, other, target)),
+            _ => false,
+        })
+        .collect()
+}
+
+// Sprites and masks aren't inherited, so these only look at the object itself. An object without
+// a mask collides using its sprite.
+pub fn effective_sprite(game: &Game, id: ObjectId) -> Option<&Sprite> {
+    game.sprite(game.object(id)?.sprite)
+}
+
+pub fn effective_mask(game: &Game, id: ObjectId) -> Option<&Sprite> {
+    let object = game.object(id)?;
+    game.sprite(object.mask.or(object.sprite))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Objects with ids 0.. and the given parents.
+    fn game(parents: &[Option<u32>]) -> Game {
+        let mut game = Game::default();
+        for (i, parent) in parents.iter().enumerate() {
+            game.objects.push(Object {
+                id: ObjectId(i as u32),
+                name: format!("obj_{}", i),
+                parent: parent.map(ObjectId),
+                ..Object::default()
+            });
+        }
+        game
+    }
+
+    fn ids(ids: &[u32]) -> Vec<ObjectId> {
+        ids.iter().map(|&id| ObjectId(id)).collect()
+    }
+
+    #[test]
+    fn no_cycles_in_a_tree() {
+        let game = game(&[None, Some(0), Some(1), Some(0)]);
+        assert_eq!(cycles(&game), Vec::<Vec<ObjectId>>::new());
+        let chain: Vec<_> = ancestors(&game, ObjectId(2)).iter().map(|o| o.id).collect();
+        assert_eq!(chain, ids(&[1, 0]));
+        assert_eq!(
+            descendants(&game, ObjectId(0)),
+            ids(&[1, 2, 3]).into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn reports_each_cycle_once() {
+        // 0 -> 1 -> 2 -> 0, 3 on its own parent, and 4 hanging off the first cycle.
+        let game = game(&[Some(1), Some(2), Some(0), Some(3), Some(1)]);
+        assert_eq!(cycles(&game), vec![ids(&[0, 1, 2]), ids(&[3])]);
+    }
+
+    #[test]
+    fn cycle_reported_from_lowest_id() {
+        let game = game(&[None, Some(2), Some(1)]);
+        assert_eq!(cycles(&game), vec![ids(&[1, 2])]);
+    }
+
+    #[test]
+    fn lookups_stop_at_cycles() {
+        let game = game(&[Some(1), Some(0), Some(0)]);
+        let chain: Vec<_> = ancestors(&game, ObjectId(2)).iter().map(|o| o.id).collect();
+        assert_eq!(chain, ids(&[0, 1]));
+        assert!(is_a(&game, ObjectId(2), ObjectId(1)));
+        assert!(!is_a(&game, ObjectId(0), ObjectId(2)));
+    }
+}
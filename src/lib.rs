@@ -1,4 +1,22 @@
+pub mod callgraph;
+pub mod code;
+pub mod constants;
+pub mod dependency;
+pub mod dnd;
+pub mod event;
+pub mod export;
 pub mod game;
+pub mod gml;
+pub mod inheritance;
+pub mod library;
+pub mod path;
+pub mod reference;
+pub mod render;
+pub mod roomgraph;
+pub mod rtf;
+pub mod search;
+pub mod sound;
+pub mod variables;
 
 mod decoder;
 
@@ -0,0 +1,458 @@
+use crate::game::{Action, Game};
+use crate::reference::ResourceKind;
+
+// The argument types Game Maker stores in `Action::parameters`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArgumentKind {
+    Expression,
+    Text,
+    Both,
+    Boolean,
+    Menu,
+    Sprite,
+    Sound,
+    Background,
+    Path,
+    Script,
+    Object,
+    Room,
+    Font,
+    Color,
+    Timeline,
+    FontString,
+    Unknown(u32),
+}
+
+impl From<u32> for ArgumentKind {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => ArgumentKind::Expression,
+            1 => ArgumentKind::Text,
+            2 => ArgumentKind::Both,
+            3 => ArgumentKind::Boolean,
+            4 => ArgumentKind::Menu,
+            5 => ArgumentKind::Sprite,
+            6 => ArgumentKind::Sound,
+            7 => ArgumentKind::Background,
+            8 => ArgumentKind::Path,
+            9 => ArgumentKind::Script,
+            10 => ArgumentKind::Object,
+            11 => ArgumentKind::Room,
+            12 => ArgumentKind::Font,
+            13 => ArgumentKind::Color,
+            14 => ArgumentKind::Timeline,
+            15 => ArgumentKind::FontString,
+            value => ArgumentKind::Unknown(value),
+        }
+    }
+}
+
+impl ArgumentKind {
+    pub fn is_resource(self) -> bool {
+        self.resource_kind().is_some()
+    }
+
+    pub fn resource_kind(self) -> Option<ResourceKind> {
+        match self {
+            ArgumentKind::Sprite => Some(ResourceKind::Sprite),
+            ArgumentKind::Sound => Some(ResourceKind::Sound),
+            ArgumentKind::Background => Some(ResourceKind::Background),
+            ArgumentKind::Path => Some(ResourceKind::Path),
+            ArgumentKind::Script => Some(ResourceKind::Script),
+            ArgumentKind::Object => Some(ResourceKind::Object),
+            ArgumentKind::Room => Some(ResourceKind::Room),
+            ArgumentKind::Font => Some(ResourceKind::Font),
+            ArgumentKind::Timeline => Some(ResourceKind::Timeline),
+            _ => None,
+        }
+    }
+}
+
+// What `Action::action_kind` says about how the runner treats an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionKind {
+    Normal,
+    BeginGroup,
+    EndGroup,
+    Else,
+    Exit,
+    Repeat,
+    Variable,
+    Code,
+    Placeholder,
+    Separator,
+    Label,
+    Unknown(u32),
+}
+
+impl From<u32> for ActionKind {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => ActionKind::Normal,
+            1 => ActionKind::BeginGroup,
+            2 => ActionKind::EndGroup,
+            3 => ActionKind::Else,
+            4 => ActionKind::Exit,
+            5 => ActionKind::Repeat,
+            6 => ActionKind::Variable,
+            7 => ActionKind::Code,
+            8 => ActionKind::Placeholder,
+            9 => ActionKind::Separator,
+            10 => ActionKind::Label,
+            value => ActionKind::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Move,
+    Main1,
+    Main2,
+    Control,
+    Score,
+    Extra,
+    Draw,
+}
+
+#[derive(Debug)]
+pub struct LibraryAction {
+    pub library_id: u32,
+    pub action_id: u32,
+    pub tab: Tab,
+    pub name: &'static str,
+    // The runner function the action calls, if any.
+    pub function: &'static str,
+    pub description: &'static str,
+    pub arguments: &'static [(&'static str, ArgumentKind)],
+}
+
+// All of the standard libraries share library id 1.
+const fn action(
+    action_id: u32,
+    tab: Tab,
+    name: &'static str,
+    function: &'static str,
+    description: &'static str,
+    arguments: &'static [(&'static str, ArgumentKind)],
+) -> LibraryAction {
+    LibraryAction {
+        library_id: 1,
+        action_id,
+        tab,
+        name,
+        function,
+        description,
+        arguments,
+    }
+}
+
+use ArgumentKind as K;
+
+const XY: &[(&str, ArgumentKind)] = &[("x", K::Expression), ("y", K::Expression)];
+const RECT: &[(&str, ArgumentKind)] = &[
+    ("x1", K::Expression),
+    ("y1", K::Expression),
+    ("x2", K::Expression),
+    ("y2", K::Expression),
+];
+const COMPARE: &[(&str, ArgumentKind)] = &[("value", K::Expression), ("operation", K::Menu)];
+const CAPTION: &[(&str, ArgumentKind)] = &[
+    ("x", K::Expression),
+    ("y", K::Expression),
+    ("caption", K::Text),
+];
+const GRADIENT: &[(&str, ArgumentKind)] = &[
+    ("x1", K::Expression),
+    ("y1", K::Expression),
+    ("x2", K::Expression),
+    ("y2", K::Expression),
+    ("color1", K::Color),
+    ("color2", K::Color),
+];
+const SHAPE: &[(&str, ArgumentKind)] = &[
+    ("x1", K::Expression),
+    ("y1", K::Expression),
+    ("x2", K::Expression),
+    ("y2", K::Expression),
+    ("filled", K::Menu),
+];
+
+#[rustfmt::skip]
+pub static ACTIONS: &[LibraryAction] = &[
+    // Move
+    action(101, Tab::Move, "Start moving in a direction", "action_move", "Start moving in one of the chosen directions", &[("directions", K::Text), ("speed", K::Expression)]),
+    action(102, Tab::Move, "Set direction and speed of motion", "action_set_motion", "Set the direction and speed of motion", &[("direction", K::Expression), ("speed", K::Expression)]),
+    action(105, Tab::Move, "Move towards point", "action_move_point", "Start moving towards a point", &[("x", K::Expression), ("y", K::Expression), ("speed", K::Expression)]),
+    action(103, Tab::Move, "Set the horizontal speed", "action_set_hspeed", "Set the horizontal speed", &[("hor speed", K::Expression)]),
+    action(104, Tab::Move, "Set the vertical speed", "action_set_vspeed", "Set the vertical speed", &[("vert speed", K::Expression)]),
+    action(107, Tab::Move, "Set the gravity", "action_set_gravity", "Set the gravity direction and strength", &[("direction", K::Expression), ("gravity", K::Expression)]),
+    action(113, Tab::Move, "Reverse horizontal direction", "action_reverse_xdir", "Reverse the horizontal direction", &[]),
+    action(114, Tab::Move, "Reverse vertical direction", "action_reverse_ydir", "Reverse the vertical direction", &[]),
+    action(108, Tab::Move, "Set the friction", "action_set_friction", "Set the friction", &[("friction", K::Expression)]),
+    action(109, Tab::Move, "Jump to position", "action_move_to", "Jump to a given position", XY),
+    action(110, Tab::Move, "Jump to start", "action_move_start", "Jump to the start position", &[]),
+    action(111, Tab::Move, "Jump to random", "action_move_random", "Jump to a random position", &[("snap hor", K::Expression), ("snap vert", K::Expression)]),
+    action(117, Tab::Move, "Snap to grid", "action_snap", "Snap to a grid", &[("snap hor", K::Expression), ("snap vert", K::Expression)]),
+    action(112, Tab::Move, "Wrap screen", "action_wrap", "Wrap around when leaving the room", &[("direction", K::Menu)]),
+    action(116, Tab::Move, "Move to contact", "action_move_contact", "Move in a direction until a contact position", &[("direction", K::Expression), ("maximum", K::Expression), ("against", K::Menu)]),
+    action(115, Tab::Move, "Bounce", "action_bounce", "Bounce against objects", &[("precise", K::Boolean), ("against", K::Menu)]),
+    action(119, Tab::Move, "Set path", "action_path", "Start moving along a path", &[("path", K::Path), ("speed", K::Expression), ("at end", K::Menu), ("relative", K::Menu)]),
+    action(124, Tab::Move, "End path", "action_path_end", "End moving along the path", &[]),
+    action(122, Tab::Move, "Path position", "action_path_position", "Set the position on the path", &[("position", K::Expression)]),
+    action(123, Tab::Move, "Path speed", "action_path_speed", "Set the speed along the path", &[("speed", K::Expression)]),
+    action(120, Tab::Move, "Step towards point", "action_linear_step", "Take a step towards a point", &[("x", K::Expression), ("y", K::Expression), ("speed", K::Expression), ("stop at", K::Menu)]),
+    action(121, Tab::Move, "Step avoiding", "action_potential_step", "Take a step towards a point avoiding objects", &[("x", K::Expression), ("y", K::Expression), ("speed", K::Expression), ("avoid", K::Menu)]),
+    // Main1
+    action(201, Tab::Main1, "Create instance", "action_create_object", "Create an instance of an object", &[("object", K::Object), ("x", K::Expression), ("y", K::Expression)]),
+    action(206, Tab::Main1, "Create moving", "action_create_object_motion", "Create an instance of an object with a speed and direction", &[("object", K::Object), ("x", K::Expression), ("y", K::Expression), ("speed", K::Expression), ("direction", K::Expression)]),
+    action(207, Tab::Main1, "Create random", "action_create_object_random", "Create an instance of one of four objects", &[("object 1", K::Object), ("object 2", K::Object), ("object 3", K::Object), ("object 4", K::Object), ("x", K::Expression), ("y", K::Expression)]),
+    action(202, Tab::Main1, "Change instance", "action_change_object", "Change the instance into another object", &[("change into", K::Object), ("perform events", K::Menu)]),
+    action(203, Tab::Main1, "Destroy the instance", "action_kill_object", "Destroy the instance", &[]),
+    action(204, Tab::Main1, "Destroy at position", "action_kill_position", "Destroy instances at a position", XY),
+    action(541, Tab::Main1, "Change sprite", "action_sprite_set", "Change the sprite", &[("sprite", K::Sprite), ("subimage", K::Expression), ("speed", K::Expression)]),
+    action(542, Tab::Main1, "Transform sprite", "action_sprite_transform", "Scale, rotate and mirror the sprite", &[("xscale", K::Expression), ("yscale", K::Expression), ("angle", K::Expression), ("mirror", K::Menu)]),
+    action(543, Tab::Main1, "Color sprite", "action_sprite_color", "Set the blending color and alpha of the sprite", &[("color", K::Color), ("alpha", K::Expression)]),
+    action(211, Tab::Main1, "Play sound", "action_sound", "Play a sound", &[("sound", K::Sound), ("loop", K::Boolean)]),
+    action(212, Tab::Main1, "Stop sound", "action_end_sound", "Stop a sound", &[("sound", K::Sound)]),
+    action(213, Tab::Main1, "Check sound", "action_if_sound", "If a sound is playing", &[("sound", K::Sound)]),
+    action(221, Tab::Main1, "Previous room", "action_previous_room", "Go to the previous room", &[("transition", K::Menu)]),
+    action(222, Tab::Main1, "Next room", "action_next_room", "Go to the next room", &[("transition", K::Menu)]),
+    action(223, Tab::Main1, "Restart room", "action_current_room", "Restart the current room", &[("transition", K::Menu)]),
+    action(224, Tab::Main1, "Different room", "action_another_room", "Go to a different room", &[("new room", K::Room), ("transition", K::Menu)]),
+    action(225, Tab::Main1, "Check previous", "action_if_previous_room", "If a previous room exists", &[]),
+    action(226, Tab::Main1, "Check next", "action_if_next_room", "If a next room exists", &[]),
+    // Main2
+    action(301, Tab::Main2, "Set alarm", "action_set_alarm", "Set an alarm clock", &[("number of steps", K::Expression), ("in alarm no", K::Menu)]),
+    action(302, Tab::Main2, "Sleep", "action_sleep", "Sleep for a number of milliseconds", &[("milliseconds", K::Expression), ("redraw", K::Boolean)]),
+    action(303, Tab::Main2, "Set time line", "action_set_timeline", "Set a time line", &[("time line", K::Timeline), ("position", K::Expression), ("start", K::Menu), ("loop", K::Menu)]),
+    action(304, Tab::Main2, "Time line position", "action_set_timeline_position", "Set the position of the time line", &[("position", K::Expression)]),
+    action(305, Tab::Main2, "Time line speed", "action_set_timeline_speed", "Set the speed of the time line", &[("speed", K::Expression)]),
+    action(306, Tab::Main2, "Start time line", "action_timeline_start", "Start the time line", &[]),
+    action(307, Tab::Main2, "Pause time line", "action_timeline_pause", "Pause the time line", &[]),
+    action(308, Tab::Main2, "Stop time line", "action_timeline_stop", "Stop and reset the time line", &[]),
+    action(321, Tab::Main2, "Display message", "action_message", "Display a message", &[("message", K::Text)]),
+    action(322, Tab::Main2, "Show game information", "action_show_info", "Show the game information", &[]),
+    action(323, Tab::Main2, "Show video", "action_show_video", "Show a video file", &[("video file", K::Text), ("full screen", K::Boolean), ("loop", K::Boolean)]),
+    action(331, Tab::Main2, "Restart game", "action_restart_game", "Restart the game", &[]),
+    action(332, Tab::Main2, "End game", "action_end_game", "End the game", &[]),
+    action(333, Tab::Main2, "Save game", "action_save_game", "Save the game", &[("file name", K::Text)]),
+    action(334, Tab::Main2, "Load game", "action_load_game", "Load the game", &[("file name", K::Text)]),
+    action(803, Tab::Main2, "Replace sprite", "action_replace_sprite", "Replace a sprite from a file", &[("sprite", K::Sprite), ("file name", K::Text), ("images", K::Expression)]),
+    action(804, Tab::Main2, "Replace sound", "action_replace_sound", "Replace a sound from a file", &[("sound", K::Sound), ("file name", K::Text)]),
+    action(805, Tab::Main2, "Replace background", "action_replace_background", "Replace a background from a file", &[("background", K::Background), ("file name", K::Text)]),
+    // Control
+    action(401, Tab::Control, "Check empty", "action_if_empty", "If a position is collision free", &[("x", K::Expression), ("y", K::Expression), ("objects", K::Menu)]),
+    action(402, Tab::Control, "Check collision", "action_if_collision", "If there is a collision at a position", &[("x", K::Expression), ("y", K::Expression), ("objects", K::Menu)]),
+    action(403, Tab::Control, "Check object", "action_if_object", "If there is an object at a position", &[("object", K::Object), ("x", K::Expression), ("y", K::Expression)]),
+    action(404, Tab::Control, "Test instance count", "action_if_number", "If the number of instances is a value", &[("object", K::Object), ("number", K::Expression), ("operation", K::Menu)]),
+    action(405, Tab::Control, "Test chance", "action_if_dice", "With a chance perform the next action", &[("sides", K::Expression)]),
+    action(407, Tab::Control, "Check question", "action_if_question", "If the user answers yes to a question", &[("question", K::Text)]),
+    action(408, Tab::Control, "Test expression", "action_if", "If an expression is true", &[("expression", K::Expression)]),
+    action(409, Tab::Control, "Check mouse", "action_if_mouse", "If a mouse button is pressed", &[("button", K::Menu)]),
+    action(410, Tab::Control, "Check grid", "action_if_aligned", "If the instance is aligned with a grid", &[("snap hor", K::Expression), ("snap vert", K::Expression)]),
+    action(422, Tab::Control, "Start block", "", "Start of a block", &[]),
+    action(421, Tab::Control, "Else", "", "Else", &[]),
+    action(425, Tab::Control, "Exit event", "", "Exit the event", &[]),
+    action(424, Tab::Control, "End block", "", "End of a block", &[]),
+    action(423, Tab::Control, "Repeat", "", "Repeat the next action", &[("times", K::Expression)]),
+    action(604, Tab::Control, "Call parent event", "action_inherited", "Call the inherited event of the parent object", &[]),
+    action(603, Tab::Control, "Execute code", "", "Execute a piece of code", &[("code", K::Text)]),
+    action(601, Tab::Control, "Execute script", "action_execute_script", "Execute a script", &[("script", K::Script), ("argument0", K::Expression), ("argument1", K::Expression), ("argument2", K::Expression), ("argument3", K::Expression), ("argument4", K::Expression)]),
+    action(605, Tab::Control, "Comment", "", "Comment", &[("comment", K::Text)]),
+    action(611, Tab::Control, "Set variable", "", "Set the value of a variable", &[("variable", K::Text), ("value", K::Expression)]),
+    action(612, Tab::Control, "Test variable", "action_if_variable", "If a variable has a value", &[("variable", K::Text), ("value", K::Expression), ("operation", K::Menu)]),
+    action(613, Tab::Control, "Draw variable", "action_draw_variable", "Draw the value of a variable", &[("variable", K::Text), ("x", K::Expression), ("y", K::Expression)]),
+    // Score
+    action(701, Tab::Score, "Set score", "action_set_score", "Set the score", &[("new score", K::Expression)]),
+    action(702, Tab::Score, "Test score", "action_if_score", "If score has a value", COMPARE),
+    action(703, Tab::Score, "Draw score", "action_draw_score", "Draw the value of score", CAPTION),
+    action(707, Tab::Score, "Show highscore", "action_highscore_show", "Show the highscore table", &[("background", K::Background), ("border", K::Menu), ("new color", K::Color), ("other color", K::Color), ("font", K::FontString)]),
+    action(708, Tab::Score, "Clear highscore", "action_highscore_clear", "Clear the highscore table", &[]),
+    action(711, Tab::Score, "Set lives", "action_set_life", "Set the number of lives", &[("new lives", K::Expression)]),
+    action(712, Tab::Score, "Test lives", "action_if_life", "If lives is a value", COMPARE),
+    action(713, Tab::Score, "Draw lives", "action_draw_life", "Draw the number of lives", CAPTION),
+    action(714, Tab::Score, "Draw life images", "action_draw_life_images", "Draw the lives as images", &[("x", K::Expression), ("y", K::Expression), ("image", K::Sprite)]),
+    action(721, Tab::Score, "Set health", "action_set_health", "Set the health", &[("value", K::Expression)]),
+    action(722, Tab::Score, "Test health", "action_if_health", "If health is a value", COMPARE),
+    action(723, Tab::Score, "Draw health", "action_draw_health", "Draw the health bar", &[("x1", K::Expression), ("y1", K::Expression), ("x2", K::Expression), ("y2", K::Expression), ("back color", K::Menu), ("bar color", K::Menu)]),
+    action(731, Tab::Score, "Score caption", "action_set_caption", "Set the information in the window caption", &[("show score", K::Menu), ("score caption", K::Text), ("show lives", K::Menu), ("lives caption", K::Text), ("show health", K::Menu), ("health caption", K::Text)]),
+    // Extra
+    action(820, Tab::Extra, "Create particle system", "action_partsyst_create", "Create the particle system", &[("depth", K::Expression)]),
+    action(821, Tab::Extra, "Destroy particle system", "action_partsyst_destroy", "Destroy the particle system", &[]),
+    action(822, Tab::Extra, "Clear particles", "action_partsyst_clear", "Clear all particles in the system", &[]),
+    action(823, Tab::Extra, "Create particle type", "action_parttype_create", "Create a type of particle", &[("type id", K::Menu), ("shape", K::Menu), ("sprite", K::Sprite), ("min size", K::Expression), ("max size", K::Expression), ("size increment", K::Expression)]),
+    action(824, Tab::Extra, "Particle color", "action_parttype_color", "Set the color of a particle type", &[("type id", K::Menu), ("color mix", K::Menu), ("color1", K::Color), ("color2", K::Color), ("start alpha", K::Expression), ("end alpha", K::Expression)]),
+    action(825, Tab::Extra, "Particle life", "action_parttype_life", "Set the life time of a particle type", &[("type id", K::Menu), ("min life", K::Expression), ("max life", K::Expression)]),
+    action(826, Tab::Extra, "Particle speed", "action_parttype_speed", "Set the motion of a particle type", &[("type id", K::Menu), ("min speed", K::Expression), ("max speed", K::Expression), ("min dir", K::Expression), ("max dir", K::Expression), ("friction", K::Expression)]),
+    action(827, Tab::Extra, "Particle gravity", "action_parttype_gravity", "Set the gravity of a particle type", &[("type id", K::Menu), ("amount", K::Expression), ("direction", K::Expression)]),
+    action(828, Tab::Extra, "Particle secondary", "action_parttype_secondary", "Create particles from other particles", &[("type id", K::Menu), ("step type", K::Menu), ("step count", K::Expression), ("death type", K::Menu), ("death count", K::Expression)]),
+    action(829, Tab::Extra, "Create emitter", "action_partemit_create", "Create a particle emitter", &[("emitter id", K::Menu), ("shape", K::Menu), ("xmin", K::Expression), ("xmax", K::Expression), ("ymin", K::Expression), ("ymax", K::Expression)]),
+    action(830, Tab::Extra, "Destroy emitter", "action_partemit_destroy", "Destroy a particle emitter", &[("emitter id", K::Menu)]),
+    action(831, Tab::Extra, "Burst from emitter", "action_partemit_burst", "Burst a number of particles from an emitter", &[("emitter id", K::Menu), ("particle type", K::Menu), ("number", K::Expression)]),
+    action(832, Tab::Extra, "Stream from emitter", "action_partemit_stream", "Stream particles from an emitter", &[("emitter id", K::Menu), ("particle type", K::Menu), ("number", K::Expression)]),
+    action(808, Tab::Extra, "Play CD", "action_cd_play", "Play a CD", &[("start track", K::Expression), ("final track", K::Expression)]),
+    action(809, Tab::Extra, "Stop CD", "action_cd_stop", "Stop the CD", &[]),
+    action(810, Tab::Extra, "Pause CD", "action_cd_pause", "Pause the CD", &[]),
+    action(811, Tab::Extra, "Resume CD", "action_cd_resume", "Resume the CD", &[]),
+    action(812, Tab::Extra, "Check CD", "action_if_cd", "If a CD exists in the drive", &[]),
+    action(813, Tab::Extra, "Check CD playing", "action_if_cd_playing", "If the CD is playing", &[]),
+    action(801, Tab::Extra, "Set cursor", "action_set_cursor", "Set the mouse cursor", &[("sprite", K::Sprite), ("cursor", K::Menu)]),
+    action(807, Tab::Extra, "Open webpage", "action_webpage", "Open a webpage in a browser", &[("address", K::Text)]),
+    // Draw
+    action(501, Tab::Draw, "Draw sprite", "action_draw_sprite", "Draw a sprite", &[("sprite", K::Sprite), ("x", K::Expression), ("y", K::Expression), ("subimage", K::Expression)]),
+    action(502, Tab::Draw, "Draw background", "action_draw_background", "Draw a background", &[("background", K::Background), ("x", K::Expression), ("y", K::Expression), ("tiled", K::Boolean)]),
+    action(514, Tab::Draw, "Draw text", "action_draw_text", "Draw a text", &[("text", K::Both), ("x", K::Expression), ("y", K::Expression)]),
+    action(519, Tab::Draw, "Draw scaled text", "action_draw_text_transformed", "Draw a scaled and rotated text", &[("text", K::Both), ("x", K::Expression), ("y", K::Expression), ("xscale", K::Expression), ("yscale", K::Expression), ("angle", K::Expression)]),
+    action(511, Tab::Draw, "Draw rectangle", "action_draw_rectangle", "Draw a rectangle", SHAPE),
+    action(516, Tab::Draw, "Horizontal gradient", "action_draw_gradient_hor", "Draw a horizontal gradient", GRADIENT),
+    action(517, Tab::Draw, "Vertical gradient", "action_draw_gradient_vert", "Draw a vertical gradient", GRADIENT),
+    action(512, Tab::Draw, "Draw ellipse", "action_draw_ellipse", "Draw an ellipse", SHAPE),
+    action(518, Tab::Draw, "Gradient ellipse", "action_draw_ellipse_gradient", "Draw a gradient ellipse", GRADIENT),
+    action(513, Tab::Draw, "Draw line", "action_draw_line", "Draw a line", RECT),
+    action(515, Tab::Draw, "Draw arrow", "action_draw_arrow", "Draw an arrow", &[("x1", K::Expression), ("y1", K::Expression), ("x2", K::Expression), ("y2", K::Expression), ("tip size", K::Expression)]),
+    action(524, Tab::Draw, "Set color", "action_color", "Set the color for drawing", &[("color", K::Color)]),
+    action(526, Tab::Draw, "Set font", "action_font", "Set the font for drawing text", &[("font", K::Font), ("align", K::Menu)]),
+    action(525, Tab::Draw, "Change full screen", "action_fullscreen", "Change the full screen mode", &[("action", K::Menu)]),
+    action(527, Tab::Draw, "Take snapshot", "action_snapshot", "Save a snapshot image of the game", &[("file name", K::Text)]),
+    action(528, Tab::Draw, "Create effect", "action_effect", "Create an effect", &[("type", K::Menu), ("x", K::Expression), ("y", K::Expression), ("size", K::Menu), ("color", K::Color), ("where", K::Menu)]),
+];
+
+pub fn find_action(library_id: u32, action_id: u32) -> Option<&'static LibraryAction> {
+    ACTIONS
+        .iter()
+        .find(|a| a.library_id == library_id && a.action_id == action_id)
+}
+
+// An action argument interpreted according to its kind.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentValue<'a> {
+    Expression(&'a str),
+    Text(&'a str),
+    Boolean(bool),
+    Menu(u32),
+    // -1 means no resource was chosen.
+    Resource(ArgumentKind, i32),
+    Color(u32),
+}
+
+impl<'a> ArgumentValue<'a> {
+    pub fn parse(kind: ArgumentKind, value: &'a str) -> ArgumentValue<'a> {
+        let number = || value.trim().parse::<i64>().ok();
+        match kind {
+            ArgumentKind::Text | ArgumentKind::FontString => ArgumentValue::Text(value),
+            ArgumentKind::Boolean => ArgumentValue::Boolean(number().is_some_and(|n| n != 0)),
+            ArgumentKind::Menu => ArgumentValue::Menu(number().unwrap_or(0).max(0) as u32),
+            ArgumentKind::Color => ArgumentValue::Color(number().unwrap_or(0) as u32),
+            kind if kind.is_resource() => {
+                ArgumentValue::Resource(kind, number().unwrap_or(-1) as i32)
+            }
+            _ => ArgumentValue::Expression(value),
+        }
+    }
+
+    // Formats the value the way it would appear in the action list, naming resources from `game`.
+    pub fn display(&self, game: &Game) -> String {
+        match *self {
+            ArgumentValue::Expression(s) => s.to_string(),
+            ArgumentValue::Text(s) => format!("{:?}", s),
+            ArgumentValue::Boolean(b) => b.to_string(),
+            ArgumentValue::Menu(n) => n.to_string(),
+            ArgumentValue::Color(c) => format!("${:06X}", c),
+            ArgumentValue::Resource(kind, id) => {
+                resource_name(game, kind, id).unwrap_or_else(|| {
+                    if id < 0 {
+                        "<undefined>".to_string()
+                    } else {
+                        id.to_string()
+                    }
+                })
+            }
+        }
+    }
+}
+
+pub(crate) fn resource_name(game: &Game, kind: ArgumentKind, id: i32) -> Option<String> {
+    let name = game.resource_name(kind.resource_kind()?, id)?;
+    Some(name.to_string())
+}
+
+impl Action {
+    pub fn kind(&self) -> ActionKind {
+        self.action_kind.into()
+    }
+
+    // The function name is stored with the action, so prefer it over the ids when it's there.
+    pub fn library_action(&self) -> Option<&'static LibraryAction> {
+        ACTIONS
+            .iter()
+            .find(|a| !self.name.is_empty() && a.function == self.name)
+            .or_else(|| find_action(self.library_id, self.action_id))
+    }
+
+    // The argument kinds are stored with every action, so this works for custom libraries too.
+    pub fn argument_kinds(&self) -> Vec<ArgumentKind> {
+        self.parameters
+            .iter()
+            .take(self.parameters_used as usize)
+            .map(|&p| ArgumentKind::from(p))
+            .collect()
+    }
+
+    pub fn typed_arguments(&self) -> Vec<ArgumentValue<'_>> {
+        self.argument_kinds()
+            .into_iter()
+            .zip(&self.arguments)
+            .map(|(kind, value)| ArgumentValue::parse(kind, value))
+            .collect()
+    }
+
+    // A one-line summary such as "Jump to position (x=16, y=0) relative".
+    pub fn describe(&self, game: &Game) -> String {
+        let library_action = self.library_action();
+        let mut description = match (library_action, self.kind()) {
+            (Some(action), _) => action.name.to_string(),
+            (None, ActionKind::Code) => "Execute code".to_string(),
+            (None, _) if !self.name.is_empty() => self.name.clone(),
+            (None, _) => format!("Library {} action {}", self.library_id, self.action_id),
+        };
+
+        // Code and comments are too long to show inline.
+        if self.kind() != ActionKind::Code && library_action.is_none_or(|a| a.action_id != 605) {
+            let arguments: Vec<String> = self
+                .typed_arguments()
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let name = library_action
+                        .and_then(|a| a.arguments.get(i))
+                        .map(|(name, _)| name.to_string())
+                        .unwrap_or_else(|| format!("arg{}", i));
+                    format!("{}={}", name, value.display(game))
+                })
+                .collect();
+            if !arguments.is_empty() {
+                description.push_str(&format!(" ({})", arguments.join(", ")));
+            }
+        }
+
+        if self.has_relative && self.relative {
+            description.push_str(" relative");
+        }
+        if self.has_target {
+            match self.target {
+                -1 => (),
+                -2 => description.push_str(" for other"),
+                id => {
+                    let name = resource_name(game, ArgumentKind::Object, id)
+                        .unwrap_or_else(|| id.to_string());
+                    description.push_str(&format!(" for all {}", name));
+                }
+            }
+        }
+        if self.is_question && self.negate {
+            description.insert_str(0, "NOT ");
+        }
+        description
+    }
+}
@@ -1,10 +1,19 @@
+use gm_reader::export::extract::{extract_all, ExtractOptions};
+use gm_reader::gml::FormatOptions;
+use gm_reader::search::{search, Query};
 use std::fs::File;
 use std::io::BufReader;
 use std::{env, process};
 
+enum Command {
+    Dump { output: Option<String> },
+    Extract { output: String, format: bool },
+    Search { pattern: String, identifier: bool },
+}
+
 struct Config {
     input: String,
-    output: Option<String>,
+    command: Command,
 }
 
 impl Config {
@@ -13,9 +22,37 @@ impl Config {
             return Err("Not enough arguments");
         }
 
+        if args[1] == "extract" {
+            if args.len() < 4 {
+                return Err("Usage: extract <input> <output directory> [--format]");
+            }
+            return Ok(Config {
+                input: args[2].clone(),
+                command: Command::Extract {
+                    output: args[3].clone(),
+                    format: args[4..].iter().any(|arg| arg == "--format"),
+                },
+            });
+        }
+
+        if args[1] == "search" {
+            if args.len() < 4 {
+                return Err("Usage: search <input> <regex or identifier> [--identifier]");
+            }
+            return Ok(Config {
+                input: args[2].clone(),
+                command: Command::Search {
+                    pattern: args[3].clone(),
+                    identifier: args[4..].iter().any(|arg| arg == "--identifier"),
+                },
+            });
+        }
+
         Ok(Config {
             input: args[1].clone(),
-            output: args.get(2).cloned(),
+            command: Command::Dump {
+                output: args.get(2).cloned(),
+            },
         })
     }
 }
@@ -35,11 +72,46 @@ fn main() -> Result<(), std::io::Error> {
     let project = gm_reader::decode(file)?;
     println!("Read game with version {:?}", project.version);
 
-    if let Some(output) = config.output {
-        println!("Writing MessagePack to {}.", output);
-        let mut f = std::fs::File::create(output)?;
-        rmp_serde::encode::write(&mut f, &project).unwrap();
-        println!("Done.");
+    match config.command {
+        Command::Dump {
+            output: Some(output),
+        } => {
+            println!("Writing MessagePack to {}.", output);
+            let mut f = std::fs::File::create(output)?;
+            rmp_serde::encode::write(&mut f, &project).unwrap();
+            println!("Done.");
+        }
+        Command::Dump { output: None } => (),
+        Command::Extract { output, format } => {
+            println!("Extracting to {}.", output);
+            let options = ExtractOptions {
+                format: if format {
+                    Some(FormatOptions::default())
+                } else {
+                    None
+                },
+            };
+            extract_all(&project, output, &options)?;
+            println!("Done.");
+        }
+        Command::Search {
+            pattern,
+            identifier,
+        } => {
+            let query = if identifier {
+                Query::Identifier(pattern)
+            } else {
+                Query::regex(&pattern).unwrap_or_else(|err| {
+                    eprintln!("Invalid pattern: {}", err);
+                    process::exit(1);
+                })
+            };
+            let hits = search(&project, &query);
+            for hit in &hits {
+                println!("{}", hit);
+            }
+            println!("{} matches.", hits.len());
+        }
     }
 
     Ok(())
@@ -0,0 +1,230 @@
+use crate::game::{Path, PathPoint};
+
+// Evaluates a path into the polyline that Game Maker actually moves instances along.
+#[derive(Debug, Clone, Default)]
+pub struct PathGeometry {
+    nodes: Vec<PathPoint>,
+    // Distance along the path at each node.
+    distances: Vec<f64>,
+}
+
+fn midpoint(a: &PathPoint, b: &PathPoint) -> PathPoint {
+    PathPoint {
+        x: (a.x + b.x) / 2.0,
+        y: (a.y + b.y) / 2.0,
+        speed: (a.speed + b.speed) / 2.0,
+    }
+}
+
+fn lerp(a: &PathPoint, b: &PathPoint, t: f64) -> PathPoint {
+    PathPoint {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+        speed: a.speed + (b.speed - a.speed) * t,
+    }
+}
+
+// Adds a quadratic Bezier curve from `start` to `end` with `control` as its control point. The
+// end point itself is left for the next curve.
+fn add_curve(
+    nodes: &mut Vec<PathPoint>,
+    precision: u32,
+    start: &PathPoint,
+    control: &PathPoint,
+    end: &PathPoint,
+) {
+    let steps = 1 << precision.clamp(1, 8);
+    for i in 0..steps {
+        let t = f64::from(i) / f64::from(steps);
+        nodes.push(lerp(&lerp(start, control, t), &lerp(control, end, t), t));
+    }
+}
+
+impl PathGeometry {
+    pub fn new(path: &Path) -> Self {
+        let points = &path.points;
+        let mut nodes = Vec::new();
+        if path.connection_type == 1 && points.len() > 2 {
+            // Smooth paths are made of curves between the midpoints of each edge, using the
+            // point in between as the control point.
+            let n = points.len();
+            if path.closed {
+                for i in 0..n {
+                    let (a, b, c) = (&points[i], &points[(i + 1) % n], &points[(i + 2) % n]);
+                    add_curve(
+                        &mut nodes,
+                        path.precision,
+                        &midpoint(a, b),
+                        b,
+                        &midpoint(b, c),
+                    );
+                }
+                nodes.push(nodes[0]);
+            } else {
+                for i in 0..n - 2 {
+                    let (a, b, c) = (&points[i], &points[i + 1], &points[i + 2]);
+                    let start = if i == 0 { *a } else { midpoint(a, b) };
+                    let end = if i == n - 3 { *c } else { midpoint(b, c) };
+                    add_curve(&mut nodes, path.precision, &start, b, &end);
+                }
+                nodes.push(points[n - 1]);
+            }
+        } else {
+            nodes.extend_from_slice(points);
+            if path.closed && points.len() > 1 {
+                nodes.push(points[0]);
+            }
+        }
+
+        let mut distances = Vec::with_capacity(nodes.len());
+        let mut total = 0.0;
+        for (i, node) in nodes.iter().enumerate() {
+            if i > 0 {
+                let previous = &nodes[i - 1];
+                total += (node.x - previous.x).hypot(node.y - previous.y);
+            }
+            distances.push(total);
+        }
+        PathGeometry { nodes, distances }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.distances.last().cloned().unwrap_or(0.0)
+    }
+
+    // The polyline through every generated node, ending back at the start for closed paths.
+    pub fn nodes(&self) -> &[PathPoint] {
+        &self.nodes
+    }
+
+    // Position and speed at `t`, the fraction of the total length from 0 to 1, like
+    // `path_position` in GML.
+    pub fn position_at(&self, t: f64) -> Option<PathPoint> {
+        let first = self.nodes.first()?;
+        let length = self.length();
+        if self.nodes.len() == 1 || length <= 0.0 {
+            return Some(*first);
+        }
+
+        let distance = t.clamp(0.0, 1.0) * length;
+        let index = match self.distances.binary_search_by(|d| d.total_cmp(&distance)) {
+            Ok(index) => return Some(self.nodes[index]),
+            Err(index) => index.clamp(1, self.nodes.len() - 1),
+        };
+        let (start, end) = (self.distances[index - 1], self.distances[index]);
+        let fraction = if end > start {
+            (distance - start) / (end - start)
+        } else {
+            0.0
+        };
+        Some(lerp(&self.nodes[index - 1], &self.nodes[index], fraction))
+    }
+
+    // `count` positions evenly spaced along the path, including both ends.
+    pub fn sample(&self, count: usize) -> Vec<PathPoint> {
+        match count {
+            0 => vec![],
+            1 => self.position_at(0.0).into_iter().collect(),
+            _ => (0..count)
+                .filter_map(|i| self.position_at(i as f64 / (count - 1) as f64))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f64, y: f64, speed: f64) -> PathPoint {
+        PathPoint { x, y, speed }
+    }
+
+    fn path(smooth: bool, closed: bool, points: &[(f64, f64)]) -> Path {
+        Path {
+            connection_type: smooth as u32,
+            closed,
+            precision: 1,
+            points: points.iter().map(|&(x, y)| point(x, y, 100.0)).collect(),
+            ..Path::default()
+        }
+    }
+
+    fn coordinates(geometry: &PathGeometry) -> Vec<(f64, f64)> {
+        geometry.nodes().iter().map(|n| (n.x, n.y)).collect()
+    }
+
+    const SQUARE: [(f64, f64); 4] = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)];
+
+    #[test]
+    fn open_smooth_paths_start_and_end_at_their_points() {
+        let geometry = PathGeometry::new(&path(true, false, &SQUARE));
+        assert_eq!(
+            coordinates(&geometry),
+            vec![
+                (0.0, 0.0),
+                (75.0, 12.5),
+                (100.0, 50.0),
+                (75.0, 87.5),
+                (0.0, 100.0),
+            ]
+        );
+        let three = PathGeometry::new(&path(true, false, &SQUARE[..3]));
+        assert_eq!(
+            coordinates(&three),
+            vec![(0.0, 0.0), (75.0, 25.0), (100.0, 100.0)]
+        );
+    }
+
+    #[test]
+    fn closed_smooth_paths_start_and_end_between_points() {
+        let geometry = PathGeometry::new(&path(true, true, &SQUARE));
+        assert_eq!(
+            coordinates(&geometry),
+            vec![
+                (50.0, 0.0),
+                (87.5, 12.5),
+                (100.0, 50.0),
+                (87.5, 87.5),
+                (50.0, 100.0),
+                (12.5, 87.5),
+                (0.0, 50.0),
+                (12.5, 12.5),
+                (50.0, 0.0),
+            ]
+        );
+        assert_eq!(geometry.position_at(0.0), geometry.position_at(1.0));
+    }
+
+    #[test]
+    fn straight_paths_and_short_smooth_paths_use_the_points() {
+        let closed = PathGeometry::new(&path(false, true, &SQUARE));
+        assert_eq!(coordinates(&closed).len(), 5);
+        assert_eq!(closed.length(), 400.0);
+        let open = PathGeometry::new(&path(false, false, &SQUARE));
+        assert_eq!(open.length(), 300.0);
+        let short = PathGeometry::new(&path(true, true, &SQUARE[..2]));
+        assert_eq!(
+            coordinates(&short),
+            vec![(0.0, 0.0), (100.0, 0.0), (0.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn positions_interpolate_speed() {
+        let mut path = path(false, false, &SQUARE[..2]);
+        path.points[1].speed = 50.0;
+        let geometry = PathGeometry::new(&path);
+        assert_eq!(geometry.position_at(0.25), Some(point(25.0, 0.0, 87.5)));
+        assert_eq!(geometry.position_at(2.0), Some(point(100.0, 0.0, 50.0)));
+        assert_eq!(
+            geometry.sample(3),
+            vec![
+                point(0.0, 0.0, 100.0),
+                point(50.0, 0.0, 75.0),
+                point(100.0, 0.0, 50.0),
+            ]
+        );
+        assert_eq!(PathGeometry::new(&Path::default()).position_at(0.5), None);
+    }
+}
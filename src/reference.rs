@@ -0,0 +1,523 @@
+use crate::event::Event;
+use crate::game::{
+    Action, Background, BackgroundId, Font, FontId, Game, Object, ObjectId, Path, PathId,
+    ResourceId, Room, RoomId, Script, ScriptId, Sound, SoundId, Sprite, SpriteId, Timeline,
+    TimelineId, Trigger,
+};
+use crate::library::ArgumentValue;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceKind {
+    Sprite,
+    Sound,
+    Background,
+    Path,
+    Script,
+    Font,
+    Timeline,
+    Object,
+    Room,
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ResourceKind::Sprite => "sprite",
+            ResourceKind::Sound => "sound",
+            ResourceKind::Background => "background",
+            ResourceKind::Path => "path",
+            ResourceKind::Script => "script",
+            ResourceKind::Font => "font",
+            ResourceKind::Timeline => "timeline",
+            ResourceKind::Object => "object",
+            ResourceKind::Room => "room",
+        })
+    }
+}
+
+// Deleted resources are skipped when decoding, so an id isn't necessarily a position in the
+// resource's vector.
+fn by_id<T, I: PartialEq>(items: &[T], id: Option<I>, item_id: impl Fn(&T) -> I) -> Option<&T> {
+    let id = id?;
+    items.iter().find(|item| item_id(item) == id)
+}
+
+fn by_name<'a, T>(items: &'a [T], name: &str, item_name: impl Fn(&T) -> &str) -> Option<&'a T> {
+    items.iter().find(|item| item_name(item) == name)
+}
+
+impl Game {
+    pub fn sprite(&self, id: impl Into<Option<SpriteId>>) -> Option<&Sprite> {
+        by_id(&self.sprites, id.into(), |r| r.id)
+    }
+
+    pub fn sound(&self, id: impl Into<Option<SoundId>>) -> Option<&Sound> {
+        by_id(&self.sounds, id.into(), |r| r.id)
+    }
+
+    pub fn background(&self, id: impl Into<Option<BackgroundId>>) -> Option<&Background> {
+        by_id(&self.backgrounds, id.into(), |r| r.id)
+    }
+
+    pub fn path(&self, id: impl Into<Option<PathId>>) -> Option<&Path> {
+        by_id(&self.paths, id.into(), |r| r.id)
+    }
+
+    pub fn script(&self, id: impl Into<Option<ScriptId>>) -> Option<&Script> {
+        by_id(&self.scripts, id.into(), |r| r.id)
+    }
+
+    pub fn font(&self, id: impl Into<Option<FontId>>) -> Option<&Font> {
+        by_id(&self.fonts, id.into(), |r| r.id)
+    }
+
+    pub fn timeline(&self, id: impl Into<Option<TimelineId>>) -> Option<&Timeline> {
+        by_id(&self.timelines, id.into(), |r| r.id)
+    }
+
+    pub fn object(&self, id: impl Into<Option<ObjectId>>) -> Option<&Object> {
+        by_id(&self.objects, id.into(), |r| r.id)
+    }
+
+    pub fn room(&self, id: impl Into<Option<RoomId>>) -> Option<&Room> {
+        by_id(&self.rooms, id.into(), |r| r.id)
+    }
+
+    pub fn trigger(&self, id: u32) -> Option<&Trigger> {
+        self.triggers.iter().find(|t| t.id == id)
+    }
+
+    pub fn sprite_by_name(&self, name: &str) -> Option<&Sprite> {
+        by_name(&self.sprites, name, |r| &r.name)
+    }
+
+    pub fn sound_by_name(&self, name: &str) -> Option<&Sound> {
+        by_name(&self.sounds, name, |r| &r.name)
+    }
+
+    pub fn background_by_name(&self, name: &str) -> Option<&Background> {
+        by_name(&self.backgrounds, name, |r| &r.name)
+    }
+
+    pub fn path_by_name(&self, name: &str) -> Option<&Path> {
+        by_name(&self.paths, name, |r| &r.name)
+    }
+
+    pub fn script_by_name(&self, name: &str) -> Option<&Script> {
+        by_name(&self.scripts, name, |r| &r.name)
+    }
+
+    pub fn font_by_name(&self, name: &str) -> Option<&Font> {
+        by_name(&self.fonts, name, |r| &r.name)
+    }
+
+    pub fn timeline_by_name(&self, name: &str) -> Option<&Timeline> {
+        by_name(&self.timelines, name, |r| &r.name)
+    }
+
+    pub fn object_by_name(&self, name: &str) -> Option<&Object> {
+        by_name(&self.objects, name, |r| &r.name)
+    }
+
+    pub fn room_by_name(&self, name: &str) -> Option<&Room> {
+        by_name(&self.rooms, name, |r| &r.name)
+    }
+
+    // Looks up a reference stored as a raw id, like an action argument.
+    pub fn resource_name(&self, kind: ResourceKind, id: i32) -> Option<&str> {
+        let name = match kind {
+            ResourceKind::Sprite => &self.sprite(SpriteId::from_raw(id))?.name,
+            ResourceKind::Sound => &self.sound(SoundId::from_raw(id))?.name,
+            ResourceKind::Background => &self.background(BackgroundId::from_raw(id))?.name,
+            ResourceKind::Path => &self.path(PathId::from_raw(id))?.name,
+            ResourceKind::Script => &self.script(ScriptId::from_raw(id))?.name,
+            ResourceKind::Font => &self.font(FontId::from_raw(id))?.name,
+            ResourceKind::Timeline => &self.timeline(TimelineId::from_raw(id))?.name,
+            ResourceKind::Object => &self.object(ObjectId::from_raw(id))?.name,
+            ResourceKind::Room => &self.room(RoomId::from_raw(id))?.name,
+        };
+        Some(name)
+    }
+
+    // Finds a resource of any kind by name. GML shares one namespace between all resources, so
+    // there should be at most one match.
+    pub fn resource_by_name(&self, name: &str) -> Option<(ResourceKind, u32)> {
+        self.sprite_by_name(name)
+            .map(|r| (ResourceKind::Sprite, r.id.0))
+            .or_else(|| {
+                self.sound_by_name(name)
+                    .map(|r| (ResourceKind::Sound, r.id.0))
+            })
+            .or_else(|| {
+                self.background_by_name(name)
+                    .map(|r| (ResourceKind::Background, r.id.0))
+            })
+            .or_else(|| {
+                self.path_by_name(name)
+                    .map(|r| (ResourceKind::Path, r.id.0))
+            })
+            .or_else(|| {
+                self.script_by_name(name)
+                    .map(|r| (ResourceKind::Script, r.id.0))
+            })
+            .or_else(|| {
+                self.font_by_name(name)
+                    .map(|r| (ResourceKind::Font, r.id.0))
+            })
+            .or_else(|| {
+                self.timeline_by_name(name)
+                    .map(|r| (ResourceKind::Timeline, r.id.0))
+            })
+            .or_else(|| {
+                self.object_by_name(name)
+                    .map(|r| (ResourceKind::Object, r.id.0))
+            })
+            .or_else(|| {
+                self.room_by_name(name)
+                    .map(|r| (ResourceKind::Room, r.id.0))
+            })
+    }
+}
+
+fn positions<T, I: Eq + Hash>(items: &[T], item_id: impl Fn(&T) -> I) -> HashMap<I, usize> {
+    let mut positions = HashMap::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        positions.entry(item_id(item)).or_insert(i);
+    }
+    positions
+}
+
+fn add_names<'a, T>(
+    names: &mut HashMap<&'a str, (ResourceKind, u32)>,
+    kind: ResourceKind,
+    items: &'a [T],
+    name_id: impl Fn(&'a T) -> (&'a str, u32),
+) {
+    for item in items {
+        let (name, id) = name_id(item);
+        names.entry(name).or_insert((kind, id));
+    }
+}
+
+fn indexed<'a, T, I: Eq + Hash>(
+    items: &'a [T],
+    positions: &HashMap<I, usize>,
+    id: Option<I>,
+) -> Option<&'a T> {
+    items.get(*positions.get(&id?)?)
+}
+
+// The lookups above scan the resource vectors, which is fine for a one-off lookup. Code that looks
+// up resources over and over, like once per instance or identifier, should build this index once
+// and look them up through it instead.
+pub struct ResourceIndex<'a> {
+    game: &'a Game,
+    sprites: HashMap<SpriteId, usize>,
+    sounds: HashMap<SoundId, usize>,
+    backgrounds: HashMap<BackgroundId, usize>,
+    paths: HashMap<PathId, usize>,
+    scripts: HashMap<ScriptId, usize>,
+    fonts: HashMap<FontId, usize>,
+    timelines: HashMap<TimelineId, usize>,
+    objects: HashMap<ObjectId, usize>,
+    rooms: HashMap<RoomId, usize>,
+    names: HashMap<&'a str, (ResourceKind, u32)>,
+}
+
+impl<'a> ResourceIndex<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        // In the same order as `Game::resource_by_name`, so the same resource wins a name clash.
+        let mut names = HashMap::new();
+        add_names(&mut names, ResourceKind::Sprite, &game.sprites, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(&mut names, ResourceKind::Sound, &game.sounds, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(
+            &mut names,
+            ResourceKind::Background,
+            &game.backgrounds,
+            |r| (&r.name, r.id.0),
+        );
+        add_names(&mut names, ResourceKind::Path, &game.paths, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(&mut names, ResourceKind::Script, &game.scripts, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(&mut names, ResourceKind::Font, &game.fonts, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(&mut names, ResourceKind::Timeline, &game.timelines, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(&mut names, ResourceKind::Object, &game.objects, |r| {
+            (&r.name, r.id.0)
+        });
+        add_names(&mut names, ResourceKind::Room, &game.rooms, |r| {
+            (&r.name, r.id.0)
+        });
+
+        ResourceIndex {
+            game,
+            sprites: positions(&game.sprites, |r| r.id),
+            sounds: positions(&game.sounds, |r| r.id),
+            backgrounds: positions(&game.backgrounds, |r| r.id),
+            paths: positions(&game.paths, |r| r.id),
+            scripts: positions(&game.scripts, |r| r.id),
+            fonts: positions(&game.fonts, |r| r.id),
+            timelines: positions(&game.timelines, |r| r.id),
+            objects: positions(&game.objects, |r| r.id),
+            rooms: positions(&game.rooms, |r| r.id),
+            names,
+        }
+    }
+
+    pub fn sprite(&self, id: impl Into<Option<SpriteId>>) -> Option<&'a Sprite> {
+        indexed(&self.game.sprites, &self.sprites, id.into())
+    }
+
+    pub fn sound(&self, id: impl Into<Option<SoundId>>) -> Option<&'a Sound> {
+        indexed(&self.game.sounds, &self.sounds, id.into())
+    }
+
+    pub fn background(&self, id: impl Into<Option<BackgroundId>>) -> Option<&'a Background> {
+        indexed(&self.game.backgrounds, &self.backgrounds, id.into())
+    }
+
+    pub fn path(&self, id: impl Into<Option<PathId>>) -> Option<&'a Path> {
+        indexed(&self.game.paths, &self.paths, id.into())
+    }
+
+    pub fn script(&self, id: impl Into<Option<ScriptId>>) -> Option<&'a Script> {
+        indexed(&self.game.scripts, &self.scripts, id.into())
+    }
+
+    pub fn font(&self, id: impl Into<Option<FontId>>) -> Option<&'a Font> {
+        indexed(&self.game.fonts, &self.fonts, id.into())
+    }
+
+    pub fn timeline(&self, id: impl Into<Option<TimelineId>>) -> Option<&'a Timeline> {
+        indexed(&self.game.timelines, &self.timelines, id.into())
+    }
+
+    pub fn object(&self, id: impl Into<Option<ObjectId>>) -> Option<&'a Object> {
+        indexed(&self.game.objects, &self.objects, id.into())
+    }
+
+    pub fn room(&self, id: impl Into<Option<RoomId>>) -> Option<&'a Room> {
+        indexed(&self.game.rooms, &self.rooms, id.into())
+    }
+
+    // Like `Game::resource_name`.
+    pub fn resource_name(&self, kind: ResourceKind, id: i32) -> Option<&'a str> {
+        let name = match kind {
+            ResourceKind::Sprite => &self.sprite(SpriteId::from_raw(id))?.name,
+            ResourceKind::Sound => &self.sound(SoundId::from_raw(id))?.name,
+            ResourceKind::Background => &self.background(BackgroundId::from_raw(id))?.name,
+            ResourceKind::Path => &self.path(PathId::from_raw(id))?.name,
+            ResourceKind::Script => &self.script(ScriptId::from_raw(id))?.name,
+            ResourceKind::Font => &self.font(FontId::from_raw(id))?.name,
+            ResourceKind::Timeline => &self.timeline(TimelineId::from_raw(id))?.name,
+            ResourceKind::Object => &self.object(ObjectId::from_raw(id))?.name,
+            ResourceKind::Room => &self.room(RoomId::from_raw(id))?.name,
+        };
+        Some(name)
+    }
+
+    // Like `Game::resource_by_name`.
+    pub fn resource_by_name(&self, name: &str) -> Option<(ResourceKind, u32)> {
+        self.names.get(name).copied()
+    }
+}
+
+// A reference to a resource that isn't in the game, e.g. an object whose sprite was deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingReference {
+    // Where the reference is, e.g. "object obj_player: sprite".
+    pub location: String,
+    pub kind: ResourceKind,
+    pub id: i32,
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} refers to missing {} {}",
+            self.location, self.kind, self.id
+        )
+    }
+}
+
+struct Validator<'a> {
+    game: &'a Game,
+    dangling: Vec<DanglingReference>,
+}
+
+impl Validator<'_> {
+    fn check(&mut self, location: impl FnOnce() -> String, kind: ResourceKind, id: i32) {
+        if id >= 0 && self.game.resource_name(kind, id).is_none() {
+            self.dangling.push(DanglingReference {
+                location: location(),
+                kind,
+                id,
+            });
+        }
+    }
+
+    fn actions(&mut self, location: &str, actions: &[Action]) {
+        for (i, action) in actions.iter().enumerate() {
+            if action.has_target {
+                let target = || format!("{} action {} target", location, i);
+                self.check(target, ResourceKind::Object, action.target);
+            }
+            for (j, argument) in action.typed_arguments().into_iter().enumerate() {
+                if let ArgumentValue::Resource(kind, id) = argument {
+                    if let Some(kind) = kind.resource_kind() {
+                        let argument = || format!("{} action {} argument {}", location, i, j);
+                        self.check(argument, kind, id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Lists every reference to a resource that doesn't exist.
+pub fn dangling_references(game: &Game) -> Vec<DanglingReference> {
+    let mut validator = Validator {
+        game,
+        dangling: vec![],
+    };
+
+    for object in &game.objects {
+        let location = |field: &str| format!("object {}: {}", object.name, field);
+        validator.check(
+            || location("sprite"),
+            ResourceKind::Sprite,
+            ResourceId::to_raw(object.sprite),
+        );
+        validator.check(
+            || location("parent"),
+            ResourceKind::Object,
+            ResourceId::to_raw(object.parent),
+        );
+        validator.check(
+            || location("mask"),
+            ResourceKind::Sprite,
+            ResourceId::to_raw(object.mask),
+        );
+        for event in &object.events {
+            let event_location = location(&event.event().to_string());
+            if let Event::Collision(id) = event.event() {
+                let id = ResourceId::to_raw(id);
+                validator.check(|| event_location.clone(), ResourceKind::Object, id);
+            }
+            validator.actions(&event_location, &event.actions);
+        }
+    }
+
+    for timeline in &game.timelines {
+        for moment in &timeline.moments {
+            let location = format!("timeline {}: moment {}", timeline.name, moment.position);
+            validator.actions(&location, &moment.actions);
+        }
+    }
+
+    for room in &game.rooms {
+        let location = |what: String| format!("room {}: {}", room.name, what);
+        for (i, background) in room.backgrounds.iter().enumerate() {
+            let what = || location(format!("background {}", i));
+            validator.check(
+                what,
+                ResourceKind::Background,
+                ResourceId::to_raw(background.background),
+            );
+        }
+        for (i, view) in room.views.iter().enumerate() {
+            let what = || location(format!("view {}", i));
+            validator.check(
+                what,
+                ResourceKind::Object,
+                ResourceId::to_raw(view.target_object),
+            );
+        }
+        for instance in &room.instances {
+            let what = || location(format!("instance {}", instance.id));
+            validator.check(
+                what,
+                ResourceKind::Object,
+                ResourceId::to_raw(instance.object),
+            );
+        }
+        for tile in &room.tiles {
+            let what = || location(format!("tile {}", tile.id));
+            validator.check(
+                what,
+                ResourceKind::Background,
+                ResourceId::to_raw(tile.background),
+            );
+        }
+    }
+
+    for (i, &id) in game.room_order.iter().enumerate() {
+        let location = || format!("room order: position {}", i);
+        validator.check(location, ResourceKind::Room, id.0 as i32);
+    }
+
+    validator.dangling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> Game {
+        let mut game = Game::default();
+        // Sprite 1 was deleted, so ids and positions differ.
+        for (id, name) in [(0, "spr_a"), (2, "shared")] {
+            game.sprites.push(Sprite {
+                id: SpriteId(id),
+                name: name.to_string(),
+                ..Sprite::default()
+            });
+        }
+        for (id, name) in [(0, "obj_a"), (3, "shared")] {
+            game.objects.push(Object {
+                id: ObjectId(id),
+                name: name.to_string(),
+                ..Object::default()
+            });
+        }
+        game
+    }
+
+    #[test]
+    fn index_agrees_with_lookups() {
+        let game = game();
+        let index = ResourceIndex::new(&game);
+        for id in -1..5 {
+            for &kind in &[
+                ResourceKind::Sprite,
+                ResourceKind::Object,
+                ResourceKind::Room,
+            ] {
+                assert_eq!(index.resource_name(kind, id), game.resource_name(kind, id));
+            }
+        }
+        for name in ["spr_a", "obj_a", "shared", "missing"] {
+            assert_eq!(index.resource_by_name(name), game.resource_by_name(name));
+        }
+        assert_eq!(
+            index.sprite(SpriteId(2)).map(|s| s.name.as_str()),
+            Some("shared")
+        );
+        assert!(index.sprite(SpriteId(1)).is_none());
+        assert!(index.object(None).is_none());
+    }
+}
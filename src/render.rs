@@ -0,0 +1,177 @@
+use crate::game::{BackgroundId, Game, Room, RoomBackground, Sprite};
+use crate::reference::ResourceIndex;
+use image::imageops::FilterType;
+use image::{Pixel, Rgba, RgbaImage};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    // Also draw instances of objects that aren't visible.
+    pub show_invisible: bool,
+}
+
+// Game Maker stores colors as 0x00BBGGRR.
+pub fn color_to_rgba(color: u32) -> Rgba<u8> {
+    Rgba([
+        (color & 0xFF) as u8,
+        ((color >> 8) & 0xFF) as u8,
+        ((color >> 16) & 0xFF) as u8,
+        255,
+    ])
+}
+
+// Alpha blends `image` onto `canvas` with its top-left corner at (x, y), clipping as needed.
+pub fn draw_image(canvas: &mut RgbaImage, image: &RgbaImage, x: i32, y: i32) {
+    let (canvas_width, canvas_height) = (canvas.width() as i64, canvas.height() as i64);
+    for (ix, iy, pixel) in image.enumerate_pixels() {
+        let cx = x as i64 + ix as i64;
+        let cy = y as i64 + iy as i64;
+        if cx < 0 || cy < 0 || cx >= canvas_width || cy >= canvas_height {
+            continue;
+        }
+        canvas.get_pixel_mut(cx as u32, cy as u32).blend(pixel);
+    }
+}
+
+fn tile_positions(offset: i32, size: u32, limit: u32, tiled: bool) -> Vec<i32> {
+    if !tiled {
+        return vec![offset];
+    }
+    if size == 0 {
+        return vec![];
+    }
+    let remainder = offset.rem_euclid(size as i32);
+    let start = if remainder > 0 {
+        remainder - size as i32
+    } else {
+        0
+    };
+    (start..limit as i32).step_by(size as usize).collect()
+}
+
+fn draw_room_background(
+    canvas: &mut RgbaImage,
+    index: &ResourceIndex<'_>,
+    background: &RoomBackground,
+) {
+    let image = match index.background(background.background) {
+        Some(b) if b.image.width > 0 && b.image.height > 0 => RgbaImage::from(&b.image),
+        _ => return,
+    };
+    let image = if background.stretch {
+        image::imageops::resize(&image, canvas.width(), canvas.height(), FilterType::Nearest)
+    } else {
+        image
+    };
+
+    let xs = tile_positions(
+        background.x,
+        image.width(),
+        canvas.width(),
+        background.tile_h,
+    );
+    let ys = tile_positions(
+        background.y,
+        image.height(),
+        canvas.height(),
+        background.tile_v,
+    );
+    for &y in &ys {
+        for &x in &xs {
+            draw_image(canvas, &image, x, y);
+        }
+    }
+}
+
+enum DepthItem<'a> {
+    Tile(usize),
+    Instance(&'a Sprite, i32, i32),
+}
+
+pub fn render_room(game: &Game, room: &Room, options: &RenderOptions) -> RgbaImage {
+    let index = ResourceIndex::new(game);
+    let mut canvas = RgbaImage::new(room.width, room.height);
+    if room.clear {
+        for pixel in canvas.pixels_mut() {
+            *pixel = color_to_rgba(room.clear_color);
+        }
+    }
+
+    for background in room
+        .backgrounds
+        .iter()
+        .filter(|b| b.visible && !b.foreground)
+    {
+        draw_room_background(&mut canvas, &index, background);
+    }
+
+    // Collect tiles and instances and draw them from the highest depth to the lowest.
+    let mut items = Vec::with_capacity(room.tiles.len() + room.instances.len());
+    for (i, tile) in room.tiles.iter().enumerate() {
+        items.push((tile.depth, DepthItem::Tile(i)));
+    }
+    for instance in &room.instances {
+        let object = match index.object(instance.object) {
+            Some(object) => object,
+            None => continue,
+        };
+        if !object.visible && !options.show_invisible {
+            continue;
+        }
+        if let Some(sprite) = index.sprite(object.sprite) {
+            items.push((
+                object.depth,
+                DepthItem::Instance(sprite, instance.x, instance.y),
+            ));
+        }
+    }
+    items.sort_by_key(|item| Reverse(item.0));
+
+    let mut tile_images: HashMap<Option<BackgroundId>, RgbaImage> = HashMap::new();
+    for (_, item) in items {
+        match item {
+            DepthItem::Tile(i) => {
+                let tile = &room.tiles[i];
+                let image = match index.background(tile.background) {
+                    Some(background) => tile_images
+                        .entry(tile.background)
+                        .or_insert_with(|| RgbaImage::from(&background.image)),
+                    None => continue,
+                };
+                if tile.tile_x < 0 || tile.tile_y < 0 {
+                    continue;
+                }
+                let (tile_x, tile_y) = (tile.tile_x as u32, tile.tile_y as u32);
+                if tile_x >= image.width() || tile_y >= image.height() {
+                    continue;
+                }
+                let width = tile.width.min(image.width() - tile_x);
+                let height = tile.height.min(image.height() - tile_y);
+                let part = image::imageops::crop_imm(&*image, tile_x, tile_y, width, height);
+                draw_image(&mut canvas, &part.to_image(), tile.x, tile.y);
+            }
+            DepthItem::Instance(sprite, x, y) => {
+                if let Some(frame) = sprite.frames.first() {
+                    let image = RgbaImage::from(frame);
+                    draw_image(
+                        &mut canvas,
+                        &image,
+                        x - sprite.origin.0,
+                        y - sprite.origin.1,
+                    );
+                }
+            }
+        }
+    }
+
+    for background in room
+        .backgrounds
+        .iter()
+        .filter(|b| b.visible && b.foreground)
+    {
+        draw_room_background(&mut canvas, &index, background);
+    }
+
+    canvas
+}
@@ -0,0 +1,464 @@
+use crate::callgraph::CallGraph;
+use crate::code::{action_lists, code_bodies, ActionOwner, CodeLocation};
+use crate::dependency::dot_string;
+use crate::event::Event;
+use crate::game::{Game, ObjectId, ResourceId, RoomId};
+use crate::gml;
+use crate::gml::ast::{walk_exprs, Expr, ExprKind};
+use crate::inheritance::effective_events;
+use crate::library::{ArgumentKind, ArgumentValue};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TransitionKind {
+    // One room to the next in the room order. This is where `room_goto_next` goes.
+    Order,
+    Goto,
+    Next,
+    Previous,
+}
+
+impl TransitionKind {
+    fn name(self) -> &'static str {
+        match self {
+            TransitionKind::Order => "order",
+            TransitionKind::Goto => "goto",
+            TransitionKind::Next => "next",
+            TransitionKind::Previous => "previous",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    // `None` when the code that changes room doesn't run in a known room, e.g. an object that's
+    // only ever created by code.
+    pub from: Option<RoomId>,
+    // `None` when the room is computed at runtime.
+    pub to: Option<RoomId>,
+    pub kind: TransitionKind,
+    // The code or action that changes room. Order transitions don't have one.
+    pub location: Option<CodeLocation>,
+}
+
+pub struct RoomGraph<'a> {
+    pub game: &'a Game,
+    pub transitions: Vec<Transition>,
+}
+
+// A room change found in code or an action, before working out which rooms it happens in.
+struct RoomChange {
+    location: CodeLocation,
+    kind: TransitionKind,
+    to: Option<RoomId>,
+}
+
+fn change_kind(function: &str) -> Option<TransitionKind> {
+    match function {
+        "room_goto" | "action_another_room" => Some(TransitionKind::Goto),
+        "room_goto_next" | "action_next_room" => Some(TransitionKind::Next),
+        "room_goto_previous" | "action_previous_room" => Some(TransitionKind::Previous),
+        _ => None,
+    }
+}
+
+// The room `expr` names, if it's a room name or a literal id.
+fn room_argument(game: &Game, expr: &Expr) -> Option<RoomId> {
+    match &expr.kind {
+        ExprKind::Identifier(name) => game.room_by_name(name).map(|r| r.id),
+        ExprKind::Real(n) if n.fract() == 0.0 => {
+            game.room(RoomId::from_raw(*n as i32)).map(|r| r.id)
+        }
+        ExprKind::Parenthesized(inner) => room_argument(game, inner),
+        _ => None,
+    }
+}
+
+fn room_changes(game: &Game) -> Vec<RoomChange> {
+    let mut changes = vec![];
+    for body in code_bodies(game) {
+        let (program, _) = gml::parse(body.code);
+        walk_exprs(&program.statements, &mut |expr| {
+            if let ExprKind::Call(function, args) = &expr.kind {
+                if let Some(kind) = change_kind(&function.name) {
+                    let to = match kind {
+                        TransitionKind::Goto => args.first().and_then(|a| room_argument(game, a)),
+                        _ => None,
+                    };
+                    changes.push(RoomChange {
+                        location: body.location,
+                        kind,
+                        to,
+                    });
+                }
+            }
+        });
+    }
+    for (owner, actions) in action_lists(game) {
+        for (i, action) in actions.iter().enumerate() {
+            let function = action.library_action().map_or("", |a| a.function);
+            let kind = match change_kind(function) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let to = action
+                .typed_arguments()
+                .into_iter()
+                .find_map(|argument| match argument {
+                    ArgumentValue::Resource(ArgumentKind::Room, id) => {
+                        game.room(RoomId::from_raw(id))
+                    }
+                    _ => None,
+                })
+                .map(|r| r.id);
+            changes.push(RoomChange {
+                location: CodeLocation::Action {
+                    owner,
+                    action: i,
+                    argument: 0,
+                },
+                kind,
+                to,
+            });
+        }
+    }
+    changes
+}
+
+// Works out which rooms code runs in, following scripts back to the code that calls them.
+struct RoomFinder {
+    calls: CallGraph,
+    instance_rooms: HashMap<ObjectId, BTreeSet<RoomId>>,
+    // The objects that run each object's events: the object itself and the children that inherit
+    // them without defining their own.
+    runners: HashMap<(ObjectId, Event), Vec<ObjectId>>,
+    scripts_visiting: Vec<CodeLocation>,
+}
+
+impl RoomFinder {
+    fn object_rooms(&self, id: ObjectId, event: Event) -> BTreeSet<RoomId> {
+        self.runners
+            .get(&(id, event))
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.instance_rooms.get(id))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn rooms(&mut self, location: CodeLocation) -> BTreeSet<RoomId> {
+        match location {
+            CodeLocation::RoomCreation(room) | CodeLocation::InstanceCreation(room, _) => {
+                std::iter::once(room).collect()
+            }
+            CodeLocation::Action {
+                owner: ActionOwner::Object(id, event),
+                ..
+            } => self.object_rooms(id, event),
+            CodeLocation::Script(id) => {
+                if self.scripts_visiting.contains(&location) {
+                    return BTreeSet::new();
+                }
+                self.scripts_visiting.push(location);
+                let callers: Vec<_> = self.calls.callers(id).map(|c| c.location).collect();
+                let rooms = callers
+                    .into_iter()
+                    .flat_map(|caller| self.rooms(caller))
+                    .collect();
+                self.scripts_visiting.pop();
+                rooms
+            }
+            CodeLocation::Action {
+                owner: ActionOwner::Timeline(..),
+                ..
+            }
+            | CodeLocation::Trigger(_)
+            | CodeLocation::LibraryInit(_) => BTreeSet::new(),
+        }
+    }
+}
+
+impl<'a> RoomGraph<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        let mut transitions = vec![];
+        for pair in game.room_order.windows(2) {
+            transitions.push(Transition {
+                from: Some(pair[0]),
+                to: Some(pair[1]),
+                kind: TransitionKind::Order,
+                location: None,
+            });
+        }
+
+        let mut instance_rooms: HashMap<ObjectId, BTreeSet<RoomId>> = HashMap::new();
+        for room in &game.rooms {
+            for object in room.instances.iter().filter_map(|i| i.object) {
+                instance_rooms.entry(object).or_default().insert(room.id);
+            }
+        }
+        let mut runners: HashMap<_, Vec<_>> = HashMap::new();
+        for object in &game.objects {
+            for inherited in effective_events(game, object.id) {
+                let event = inherited.event.event();
+                runners
+                    .entry((inherited.defined_by.id, event))
+                    .or_default()
+                    .push(object.id);
+            }
+        }
+        let mut finder = RoomFinder {
+            calls: CallGraph::new(game),
+            instance_rooms,
+            runners,
+            scripts_visiting: vec![],
+        };
+
+        let position = |room| game.room_order.iter().position(|&r| r == room);
+        for change in room_changes(game) {
+            let rooms = finder.rooms(change.location);
+            if rooms.is_empty() {
+                transitions.push(Transition {
+                    from: None,
+                    to: change.to,
+                    kind: change.kind,
+                    location: Some(change.location),
+                });
+            }
+            for from in rooms {
+                let to = match change.kind {
+                    TransitionKind::Next => position(from).and_then(|i| game.room_order.get(i + 1)),
+                    TransitionKind::Previous => position(from)
+                        .and_then(|i| i.checked_sub(1))
+                        .and_then(|i| game.room_order.get(i)),
+                    _ => change.to.as_ref(),
+                };
+                transitions.push(Transition {
+                    from: Some(from),
+                    to: to.copied(),
+                    kind: change.kind,
+                    location: Some(change.location),
+                });
+            }
+        }
+
+        RoomGraph { game, transitions }
+    }
+
+    pub fn from(&self, room: RoomId) -> impl Iterator<Item = &Transition> {
+        self.transitions
+            .iter()
+            .filter(move |t| t.from == Some(room))
+    }
+
+    pub fn to(&self, room: RoomId) -> impl Iterator<Item = &Transition> {
+        self.transitions.iter().filter(move |t| t.to == Some(room))
+    }
+
+    fn room_name(&self, room: Option<RoomId>) -> &str {
+        match room {
+            Some(room) => self.game.room(room).map_or("", |r| r.name.as_str()),
+            None => "?",
+        }
+    }
+
+    // Transitions with an unknown end go to or from a node named "?".
+    pub fn to_dot(&self) -> String {
+        let node = |room: Option<RoomId>| match room {
+            Some(room) => format!("room{}", room),
+            None => "unknown".to_string(),
+        };
+        let mut out = String::from("digraph rooms {\n");
+        for room in &self.game.rooms {
+            let shape = if self.game.room_order.first() == Some(&room.id) {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            let _ = writeln!(
+                out,
+                "    {} [label={}, shape={}];",
+                node(Some(room.id)),
+                dot_string(&room.name),
+                shape
+            );
+        }
+        let unknown = self
+            .transitions
+            .iter()
+            .any(|t| t.from.is_none() || t.to.is_none());
+        if unknown {
+            out.push_str("    unknown [label=\"?\", shape=plaintext];\n");
+        }
+        let edges: BTreeSet<_> = self
+            .transitions
+            .iter()
+            .map(|t| (node(t.from), node(t.to), t.kind))
+            .collect();
+        for (from, to, kind) in edges {
+            let style = if kind == TransitionKind::Order {
+                "dashed"
+            } else {
+                "solid"
+            };
+            let _ = writeln!(
+                out,
+                "    {} -> {} [label={}, style={}];",
+                from,
+                to,
+                dot_string(kind.name()),
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_json(&self) -> Value {
+        let room = |room: Option<RoomId>| match room {
+            Some(id) => json!({ "id": id.0, "name": self.room_name(Some(id)) }),
+            None => Value::Null,
+        };
+        json!({
+            "rooms": self.game.rooms.iter().map(|r| json!({
+                "id": r.id.0,
+                "name": r.name,
+                "order": self.game.room_order.iter().position(|&id| id == r.id),
+            })).collect::<Vec<_>>(),
+            "transitions": self.transitions.iter().map(|t| json!({
+                "from": room(t.from),
+                "to": room(t.to),
+                "kind": t.kind.name(),
+                "location": t.location.map(|l| l.display_name(self.game)),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Action, Object, ObjectEvent, Room, RoomInstance, Script, ScriptId};
+
+    fn code(code: &str) -> Action {
+        Action {
+            library_id: 1,
+            action_id: 603,
+            action_kind: 7,
+            parameters_used: 1,
+            parameters: vec![1],
+            target: -1,
+            arguments: vec![code.to_string()],
+            ..Action::default()
+        }
+    }
+
+    fn object(id: u32, parent: Option<u32>, create: Option<&str>) -> Object {
+        Object {
+            id: ObjectId(id),
+            name: format!("obj_{}", id),
+            parent: parent.map(ObjectId),
+            events: create
+                .map(|create| ObjectEvent {
+                    actions: vec![code(create)],
+                    ..ObjectEvent::default()
+                })
+                .into_iter()
+                .collect(),
+            ..Object::default()
+        }
+    }
+
+    fn room(id: u32, objects: &[u32], creation_code: &str) -> Room {
+        Room {
+            id: RoomId(id),
+            name: format!("rm_{}", id),
+            creation_code: creation_code.to_string(),
+            instances: objects
+                .iter()
+                .map(|&id| RoomInstance {
+                    object: Some(ObjectId(id)),
+                    ..RoomInstance::default()
+                })
+                .collect(),
+            ..Room::default()
+        }
+    }
+
+    fn edges(game: &Game) -> Vec<(Option<u32>, Option<u32>, TransitionKind)> {
+        let mut edges: Vec<_> = RoomGraph::new(game)
+            .transitions
+            .iter()
+            .map(|t| (t.from.map(|r| r.0), t.to.map(|r| r.0), t.kind))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn room_order_makes_a_chain() {
+        let mut game = Game::default();
+        game.rooms = vec![room(0, &[], ""), room(1, &[], ""), room(2, &[], "")];
+        game.room_order = vec![RoomId(2), RoomId(0), RoomId(1)];
+        assert_eq!(
+            edges(&game),
+            vec![
+                (Some(0), Some(1), TransitionKind::Order),
+                (Some(2), Some(0), TransitionKind::Order),
+            ]
+        );
+    }
+
+    #[test]
+    fn next_and_previous_follow_the_room_order() {
+        let mut game = Game::default();
+        game.objects = vec![
+            object(0, None, Some("room_goto_next()")),
+            // Inherits the parent's Create event.
+            object(1, Some(0), None),
+            // Has its own Create event, so the parent's doesn't run.
+            object(2, Some(0), Some("x = 1")),
+        ];
+        game.rooms = vec![
+            room(0, &[0, 2], ""),
+            room(1, &[2], "room_goto_previous()"),
+            room(2, &[1], ""),
+        ];
+        game.room_order = vec![RoomId(0), RoomId(1), RoomId(2)];
+        assert_eq!(
+            edges(&game),
+            vec![
+                (Some(0), Some(1), TransitionKind::Order),
+                (Some(0), Some(1), TransitionKind::Next),
+                (Some(1), Some(0), TransitionKind::Previous),
+                (Some(1), Some(2), TransitionKind::Order),
+                (Some(2), None, TransitionKind::Next),
+            ]
+        );
+    }
+
+    #[test]
+    fn goto_through_scripts_and_unplaced_objects() {
+        let mut game = Game::default();
+        game.objects = vec![
+            object(0, None, Some("scr_leave()")),
+            object(1, None, Some("room_goto(rm_0)")),
+        ];
+        game.scripts.push(Script {
+            id: ScriptId(0),
+            name: "scr_leave".to_string(),
+            script: "room_goto(rm_1); room_goto(global.target)".to_string(),
+        });
+        game.rooms = vec![room(0, &[0], ""), room(1, &[], "")];
+        assert_eq!(
+            edges(&game),
+            vec![
+                (None, Some(0), TransitionKind::Goto),
+                (Some(0), None, TransitionKind::Goto),
+                (Some(0), Some(1), TransitionKind::Goto),
+            ]
+        );
+    }
+}
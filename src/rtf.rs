@@ -0,0 +1,572 @@
+use crate::export::escape_xml;
+use crate::game::Help;
+use crate::render::color_to_rgba;
+
+// Windows-1252 characters for bytes 0x80-0x9F, which differ from Latin-1.
+const CP1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+// Destinations whose contents aren't part of the visible text.
+const SKIPPED_DESTINATIONS: [&str; 12] = [
+    "fonttbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "object",
+    "header",
+    "footer",
+    "filetbl",
+    "listtable",
+    "listoverridetable",
+    "revtbl",
+    "xmlnstbl",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    // None is the default (auto) color.
+    pub color: Option<[u8; 3]>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Paragraph {
+    pub spans: Vec<Span>,
+    pub alignment: Alignment,
+}
+
+impl Paragraph {
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    fn push(&mut self, c: char, style: TextStyle) {
+        match self.spans.last_mut() {
+            Some(span) if span.style == style => span.text.push(c),
+            _ => self.spans.push(Span {
+                text: c.to_string(),
+                style,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    pub paragraphs: Vec<Paragraph>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GroupState {
+    style: TextStyle,
+    color_index: usize,
+    alignment: Alignment,
+    skip: bool,
+    unicode_skip: usize,
+}
+
+impl Default for GroupState {
+    fn default() -> Self {
+        GroupState {
+            style: TextStyle::default(),
+            color_index: 0,
+            alignment: Alignment::Left,
+            skip: false,
+            unicode_skip: 1,
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    state: GroupState,
+    stack: Vec<GroupState>,
+    colors: Vec<Option<[u8; 3]>>,
+    // The color table entry being built while inside \colortbl.
+    color: [u8; 3],
+    color_set: bool,
+    in_color_table: Option<usize>,
+    // Characters still to be skipped after a \u escape.
+    pending_skip: usize,
+    document: Document,
+    paragraph: Paragraph,
+}
+
+impl<'a> Parser<'a> {
+    fn push_char(&mut self, c: char) {
+        if self.pending_skip > 0 {
+            self.pending_skip -= 1;
+            return;
+        }
+        if self.state.skip {
+            return;
+        }
+        if self.in_color_table.is_some() {
+            if c == ';' {
+                let color = if self.color_set {
+                    Some(self.color)
+                } else {
+                    None
+                };
+                self.colors.push(color);
+                self.color = [0; 3];
+                self.color_set = false;
+            }
+            return;
+        }
+        let mut style = self.state.style;
+        style.color = self.colors.get(self.state.color_index).copied().flatten();
+        self.paragraph.alignment = self.state.alignment;
+        self.paragraph.push(c, style);
+    }
+
+    fn end_paragraph(&mut self) {
+        self.paragraph.alignment = self.state.alignment;
+        let paragraph = std::mem::take(&mut self.paragraph);
+        self.document.paragraphs.push(paragraph);
+    }
+
+    fn read_word(&mut self) -> (String, Option<i32>) {
+        let mut word = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            word.push(c);
+            self.chars.next();
+        }
+        let mut number = String::new();
+        if self.chars.peek() == Some(&'-') {
+            number.push('-');
+            self.chars.next();
+        }
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            number.push(c);
+            self.chars.next();
+        }
+        // A single space delimits the control word and isn't part of the text.
+        if self.chars.peek() == Some(&' ') {
+            self.chars.next();
+        }
+        (word, number.parse().ok())
+    }
+
+    fn control_word(&mut self, word: &str, param: Option<i32>, group_start: bool) {
+        let on = param != Some(0);
+        if group_start && SKIPPED_DESTINATIONS.contains(&word) {
+            self.state.skip = true;
+            return;
+        }
+        if let Some(depth) = self.in_color_table {
+            if depth == self.stack.len() {
+                let value = param.unwrap_or(0).clamp(0, 255) as u8;
+                match word {
+                    "red" => self.color[0] = value,
+                    "green" => self.color[1] = value,
+                    "blue" => self.color[2] = value,
+                    _ => return,
+                }
+                self.color_set = true;
+                return;
+            }
+        }
+        match word {
+            "colortbl" => {
+                self.in_color_table = Some(self.stack.len());
+                self.colors.clear();
+            }
+            "par" | "sect" | "page" if !self.state.skip => self.end_paragraph(),
+            "line" => self.push_char('\n'),
+            "tab" => self.push_char('\t'),
+            "emdash" => self.push_char('\u{2014}'),
+            "endash" => self.push_char('\u{2013}'),
+            "lquote" => self.push_char('\u{2018}'),
+            "rquote" => self.push_char('\u{2019}'),
+            "ldblquote" => self.push_char('\u{201C}'),
+            "rdblquote" => self.push_char('\u{201D}'),
+            "bullet" => self.push_char('\u{2022}'),
+            "b" => self.state.style.bold = on,
+            "i" => self.state.style.italic = on,
+            "ul" => self.state.style.underline = on,
+            "ulnone" => self.state.style.underline = false,
+            "cf" => self.state.color_index = param.unwrap_or(0).max(0) as usize,
+            "plain" => {
+                self.state.style = TextStyle::default();
+                self.state.color_index = 0;
+            }
+            "pard" => self.state.alignment = Alignment::Left,
+            "ql" => self.state.alignment = Alignment::Left,
+            "qc" => self.state.alignment = Alignment::Center,
+            "qr" => self.state.alignment = Alignment::Right,
+            "qj" => self.state.alignment = Alignment::Justify,
+            "uc" => self.state.unicode_skip = param.unwrap_or(1).max(0) as usize,
+            "u" => {
+                if let Some(param) = param {
+                    // Values above 32767 are written as negative numbers.
+                    let code = if param < 0 { param + 65536 } else { param };
+                    let c = std::char::from_u32(code as u32).unwrap_or('\u{FFFD}');
+                    self.push_char(c);
+                    self.pending_skip = self.state.unicode_skip;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn parse(mut self) -> Document {
+        let mut group_start = false;
+        while let Some(c) = self.chars.next() {
+            let at_group_start = group_start;
+            group_start = false;
+            match c {
+                '{' => {
+                    self.stack.push(self.state);
+                    group_start = true;
+                }
+                '}' => {
+                    if self.in_color_table == Some(self.stack.len()) {
+                        self.in_color_table = None;
+                    }
+                    if let Some(state) = self.stack.pop() {
+                        self.state = state;
+                    }
+                }
+                '\\' => match self.chars.peek().copied() {
+                    Some(c) if c.is_ascii_alphabetic() => {
+                        let (word, param) = self.read_word();
+                        self.control_word(&word, param, at_group_start);
+                    }
+                    Some('\'') => {
+                        self.chars.next();
+                        let hex: String = self.chars.by_ref().take(2).collect();
+                        if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                            self.push_char(match byte {
+                                0x80..=0x9F => CP1252_HIGH[byte as usize - 0x80],
+                                byte => byte as char,
+                            });
+                        }
+                    }
+                    Some('*') => {
+                        self.chars.next();
+                        // Ignorable destinations are only there for readers that understand them.
+                        if at_group_start {
+                            self.state.skip = true;
+                        }
+                    }
+                    Some('~') => {
+                        self.chars.next();
+                        self.push_char('\u{A0}');
+                    }
+                    Some('_') => {
+                        self.chars.next();
+                        self.push_char('\u{2011}');
+                    }
+                    Some('\n') | Some('\r') => {
+                        self.chars.next();
+                        if !self.state.skip {
+                            self.end_paragraph();
+                        }
+                    }
+                    Some(c) => {
+                        self.chars.next();
+                        if c != '-' {
+                            self.push_char(c);
+                        }
+                    }
+                    None => (),
+                },
+                '\r' | '\n' => (),
+                c => self.push_char(c),
+            }
+        }
+        if !self.paragraph.spans.is_empty() {
+            // The closing brace has already reset the state, so keep the alignment the text had.
+            let paragraph = std::mem::take(&mut self.paragraph);
+            self.document.paragraphs.push(paragraph);
+        }
+        // Game Maker's editor ends the text with an empty paragraph.
+        while self
+            .document
+            .paragraphs
+            .last()
+            .is_some_and(|p| p.text().trim().is_empty())
+        {
+            self.document.paragraphs.pop();
+        }
+        self.document
+    }
+}
+
+pub fn parse(rtf: &str) -> Document {
+    // Game information from older versions can be plain text.
+    if !rtf.trim_start().starts_with("{\\rtf") {
+        return Document {
+            paragraphs: rtf
+                .lines()
+                .map(|line| Paragraph {
+                    spans: vec![Span {
+                        text: line.to_string(),
+                        style: TextStyle::default(),
+                    }],
+                    alignment: Alignment::Left,
+                })
+                .collect(),
+        };
+    }
+    Parser {
+        chars: rtf.chars().peekable(),
+        state: GroupState::default(),
+        stack: Vec::new(),
+        colors: Vec::new(),
+        color: [0; 3],
+        color_set: false,
+        in_color_table: None,
+        pending_skip: 0,
+        document: Document::default(),
+        paragraph: Paragraph::default(),
+    }
+    .parse()
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '*' | '_' | '`' | '#' | '[' | ']' | '<' | '>' | '|' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("  \n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn html_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+impl Document {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for paragraph in &self.paragraphs {
+            text.push_str(&paragraph.text());
+            text.push('\n');
+        }
+        text
+    }
+
+    // Markdown has no way to express colors or alignment, so only emphasis is kept.
+    pub fn to_markdown(&self) -> String {
+        let mut blocks = Vec::new();
+        for paragraph in &self.paragraphs {
+            // Spans that only differ in color or underline would otherwise be written as
+            // `**a****b**`, so join them first.
+            let mut runs: Vec<(String, (bool, bool))> = Vec::new();
+            for span in &paragraph.spans {
+                let emphasis = (span.style.bold, span.style.italic);
+                match runs.last_mut() {
+                    Some((text, last)) if *last == emphasis => text.push_str(&span.text),
+                    _ => runs.push((span.text.clone(), emphasis)),
+                }
+            }
+            let mut block = String::new();
+            for (text, emphasis) in &runs {
+                let marker = match emphasis {
+                    (true, true) => "***",
+                    (true, false) => "**",
+                    (false, true) => "*",
+                    (false, false) => "",
+                };
+                // Emphasis can't start or end with whitespace, so keep it outside the markers.
+                let trimmed = text.trim();
+                if marker.is_empty() || trimmed.is_empty() {
+                    block.push_str(&escape_markdown(text));
+                    continue;
+                }
+                let start = text.len() - text.trim_start().len();
+                let end = text.trim_end().len();
+                block.push_str(&escape_markdown(&text[..start]));
+                block.push_str(marker);
+                block.push_str(&escape_markdown(trimmed));
+                block.push_str(marker);
+                block.push_str(&escape_markdown(&text[end..]));
+            }
+            blocks.push(block);
+        }
+        // Empty paragraphs only add spacing, which Markdown collapses anyway.
+        let blocks: Vec<_> = blocks
+            .into_iter()
+            .filter(|b| !b.trim().is_empty())
+            .collect();
+        let mut markdown = blocks.join("\n\n");
+        markdown.push('\n');
+        markdown
+    }
+
+    pub fn to_html(&self, background: Option<[u8; 3]>) -> String {
+        let mut html = match background {
+            Some(color) => format!("<div style=\"background-color: {}\">\n", html_color(color)),
+            None => "<div>\n".to_string(),
+        };
+        for paragraph in &self.paragraphs {
+            match paragraph.alignment {
+                Alignment::Left => html.push_str("<p>"),
+                Alignment::Center => html.push_str("<p style=\"text-align: center\">"),
+                Alignment::Right => html.push_str("<p style=\"text-align: right\">"),
+                Alignment::Justify => html.push_str("<p style=\"text-align: justify\">"),
+            }
+            if paragraph.text().is_empty() {
+                html.push_str("<br>");
+            }
+            for span in &paragraph.spans {
+                let mut open = String::new();
+                let mut close = String::new();
+                if let Some(color) = span.style.color {
+                    open.push_str(&format!("<span style=\"color: {}\">", html_color(color)));
+                    close.insert_str(0, "</span>");
+                }
+                for (enabled, tag) in &[
+                    (span.style.bold, "b"),
+                    (span.style.italic, "i"),
+                    (span.style.underline, "u"),
+                ] {
+                    if *enabled {
+                        open.push_str(&format!("<{}>", tag));
+                        close.insert_str(0, &format!("</{}>", tag));
+                    }
+                }
+                let lines: Vec<_> = span.text.split('\n').map(escape_xml).collect();
+                html.push_str(&open);
+                html.push_str(&lines.join("<br>"));
+                html.push_str(&close);
+            }
+            html.push_str("</p>\n");
+        }
+        html.push_str("</div>\n");
+        html
+    }
+}
+
+impl Help {
+    pub fn document(&self) -> Document {
+        parse(&self.content)
+    }
+
+    pub fn to_text(&self) -> String {
+        self.document().to_text()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.document().to_markdown()
+    }
+
+    pub fn to_html(&self) -> String {
+        let color = color_to_rgba(self.background_color);
+        self.document()
+            .to_html(Some([color[0], color[1], color[2]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rtf(body: &str) -> String {
+        format!(
+            "{{\\rtf1\\ansi{{\\fonttbl{{\\f0 Arial;}}}}{{\\colortbl ;\\red255\\green0\\blue0;\\red0\\green0\\blue255;}}{}}}",
+            body
+        )
+    }
+
+    #[test]
+    fn plain_text_is_one_paragraph_per_line() {
+        let document = parse("first\nsecond");
+        assert_eq!(document.to_text(), "first\nsecond\n");
+    }
+
+    #[test]
+    fn skips_tables_and_reads_paragraphs() {
+        let document = parse(&rtf("\\pard Hello\\par World\\par\\par"));
+        assert_eq!(document.to_text(), "Hello\nWorld\n");
+    }
+
+    #[test]
+    fn reads_styles_and_colors() {
+        let document = parse(&rtf("\\cf1\\b Red\\b0\\cf0  plain"));
+        let spans = &document.paragraphs[0].spans;
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].text, "Red");
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[0].style.color, Some([255, 0, 0]));
+        assert_eq!(spans[1].text, " plain");
+        assert_eq!(spans[1].style, TextStyle::default());
+    }
+
+    #[test]
+    fn decodes_escapes() {
+        let document = parse(&rtf("caf\\'e9 \\'80 \\u8364?x\\~y\\{\\}"));
+        assert_eq!(
+            document.to_text(),
+            "caf\u{e9} \u{20ac} \u{20ac}x\u{a0}y{}\n"
+        );
+    }
+
+    #[test]
+    fn groups_restore_style() {
+        let document = parse(&rtf("a{\\i b}c"));
+        let spans = &document.paragraphs[0].spans;
+        assert_eq!(spans.len(), 3);
+        assert!(spans[1].style.italic);
+        assert!(!spans[2].style.italic);
+    }
+
+    #[test]
+    fn markdown_keeps_whitespace_outside_emphasis() {
+        let document = parse(&rtf("\\b bold \\b0 and \\i italic\\i0  *stars*"));
+        assert_eq!(
+            document.to_markdown(),
+            "**bold** and *italic* \\*stars\\*\n"
+        );
+    }
+
+    #[test]
+    fn markdown_merges_spans_with_the_same_emphasis() {
+        let document = parse(&rtf("\\b\\cf1 red\\cf2 blue\\ul under\\b0  done"));
+        assert_eq!(document.to_markdown(), "**redblueunder** done\n");
+    }
+
+    #[test]
+    fn html_escapes_and_styles() {
+        let document = parse(&rtf("\\qc\\cf1\\b a<b\\line c"));
+        assert_eq!(
+            document.to_html(None),
+            "<div>\n<p style=\"text-align: center\"><span style=\"color: #ff0000\"><b>a&lt;b<br>c</b></span></p>\n</div>\n"
+        );
+    }
+}
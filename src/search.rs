@@ -0,0 +1,170 @@
+use crate::code::{action_lists, code_bodies, CodeLocation};
+use crate::game::Game;
+use crate::gml::lexer::{tokenize, TokenKind};
+use crate::gml::Span;
+use crate::library::{ActionKind, ArgumentValue};
+use crate::reference::ResourceIndex;
+use regex::Regex;
+use std::fmt;
+
+pub enum Query {
+    Regex(Regex),
+    // Matches identifier tokens with exactly this name, so not in strings, comments or longer
+    // names. Resource arguments of actions match when they name the resource.
+    Identifier(String),
+}
+
+impl Query {
+    pub fn regex(pattern: &str) -> Result<Query, regex::Error> {
+        Regex::new(pattern).map(Query::Regex)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    // Where the text is, e.g. "objects/obj_player/Step (Normal)/action 3".
+    pub path: String,
+    pub span: Span,
+    pub line: usize,
+    pub column: usize,
+    // The whole line the match starts on.
+    pub line_text: String,
+}
+
+impl SearchHit {
+    // The path down to the line, e.g. "objects/obj_player/Step (Normal)/action 3/line 12".
+    pub fn location(&self) -> String {
+        format!("{}/line {}", self.path, self.line)
+    }
+}
+
+impl fmt::Display for SearchHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location(), self.line_text.trim())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TextKind {
+    Code,
+    Text,
+    // The name of a resource an action argument refers to.
+    ResourceName,
+}
+
+struct Source<'a> {
+    path: String,
+    text: &'a str,
+    kind: TextKind,
+}
+
+fn sources(game: &Game) -> Vec<Source<'_>> {
+    let mut out: Vec<_> = code_bodies(game)
+        .into_iter()
+        .map(|body| Source {
+            path: body.location.path(game),
+            text: body.code,
+            kind: TextKind::Code,
+        })
+        .collect();
+
+    let index = ResourceIndex::new(game);
+    // Code is covered above, so only text and resource arguments are left.
+    for (owner, actions) in action_lists(game) {
+        for (i, action) in actions.iter().enumerate() {
+            if action.kind() == ActionKind::Code {
+                continue;
+            }
+            for (j, argument) in action.typed_arguments().into_iter().enumerate() {
+                let (text, kind) = match argument {
+                    ArgumentValue::Text(text) => (text, TextKind::Text),
+                    ArgumentValue::Resource(kind, id) => {
+                        let name = kind
+                            .resource_kind()
+                            .and_then(|kind| index.resource_name(kind, id));
+                        match name {
+                            Some(name) => (name, TextKind::ResourceName),
+                            None => continue,
+                        }
+                    }
+                    _ => continue,
+                };
+                let location = CodeLocation::Action {
+                    owner,
+                    action: i,
+                    argument: j,
+                };
+                out.push(Source {
+                    path: location.path(game),
+                    text,
+                    kind,
+                });
+            }
+        }
+    }
+
+    let constant = |path: String, text| Source {
+        path,
+        text,
+        kind: TextKind::Code,
+    };
+    for c in &game.constants {
+        out.push(constant(format!("constants/{}", c.name), &c.value));
+    }
+    for extension in &game.extensions {
+        for file in &extension.files {
+            for c in &file.constants {
+                let path = format!(
+                    "extensions/{}/{}/constants/{}",
+                    extension.name, file.name, c.name
+                );
+                out.push(constant(path, &c.value));
+            }
+        }
+    }
+    out
+}
+
+fn matches(query: &Query, source: &Source<'_>) -> Vec<Span> {
+    match query {
+        Query::Regex(regex) => regex
+            .find_iter(source.text)
+            .map(|m| Span::new(m.start(), m.end()))
+            .collect(),
+        Query::Identifier(name) => match source.kind {
+            TextKind::Code => tokenize(source.text)
+                .0
+                .into_iter()
+                .filter(|t| t.kind == TokenKind::Identifier && t.text(source.text) == name)
+                .map(|t| t.span)
+                .collect(),
+            TextKind::ResourceName if source.text == name => vec![Span::new(0, name.len())],
+            _ => vec![],
+        },
+    }
+}
+
+// Every match of `query` in the game's code (see `code_bodies`), then in text and resource
+// arguments of actions, then in game and extension constant values.
+pub fn search(game: &Game, query: &Query) -> Vec<SearchHit> {
+    let mut hits = vec![];
+    for source in sources(game) {
+        for span in matches(query, &source) {
+            let (line, column) = span.line_column(source.text);
+            let line_start = source.text[..span.start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = source.text[span.start..]
+                .find('\n')
+                .map_or(source.text.len(), |i| span.start + i);
+            hits.push(SearchHit {
+                path: source.path.clone(),
+                span,
+                line,
+                column,
+                line_text: source.text[line_start..line_end]
+                    .trim_end_matches('\r')
+                    .to_string(),
+            });
+        }
+    }
+    hits
+}
@@ -0,0 +1,497 @@
+use crate::game::Sound;
+use serde::Serialize;
+use std::convert::TryInto;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum SoundFormat {
+    Unknown,
+    Wav,
+    Midi,
+    Mp3,
+    Ogg,
+}
+
+impl SoundFormat {
+    // Extension with a leading dot, in the same form as `Sound::filetype`.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            SoundFormat::Unknown => None,
+            SoundFormat::Wav => Some(".wav"),
+            SoundFormat::Midi => Some(".mid"),
+            SoundFormat::Mp3 => Some(".mp3"),
+            SoundFormat::Ogg => Some(".ogg"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SoundInfo {
+    pub format: SoundFormat,
+    // In seconds.
+    pub duration: Option<f64>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+}
+
+impl SoundInfo {
+    fn new(format: SoundFormat) -> Self {
+        SoundInfo {
+            format,
+            duration: None,
+            sample_rate: None,
+            channels: None,
+        }
+    }
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+pub fn detect_format(data: &[u8]) -> SoundFormat {
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WAVE") {
+        SoundFormat::Wav
+    } else if data.starts_with(b"MThd") {
+        SoundFormat::Midi
+    } else if data.starts_with(b"OggS") {
+        SoundFormat::Ogg
+    } else if data.starts_with(b"ID3") || mp3_frame(data, 0).is_some() {
+        SoundFormat::Mp3
+    } else {
+        SoundFormat::Unknown
+    }
+}
+
+pub fn sound_info(data: &[u8]) -> SoundInfo {
+    let format = detect_format(data);
+    let info = match format {
+        SoundFormat::Wav => wav_info(data),
+        SoundFormat::Midi => midi_info(data),
+        SoundFormat::Mp3 => mp3_info(data),
+        SoundFormat::Ogg => ogg_info(data),
+        SoundFormat::Unknown => None,
+    };
+    info.unwrap_or_else(|| SoundInfo::new(format))
+}
+
+impl Sound {
+    pub fn format(&self) -> SoundFormat {
+        detect_format(&self.data)
+    }
+
+    pub fn info(&self) -> SoundInfo {
+        sound_info(&self.data)
+    }
+}
+
+fn wav_info(data: &[u8]) -> Option<SoundInfo> {
+    let mut info = SoundInfo::new(SoundFormat::Wav);
+    let mut byte_rate = None;
+    let mut offset = 12;
+    while let (Some(id), Some(size)) = (data.get(offset..offset + 4), u32_le(data, offset + 4)) {
+        let body = offset + 8;
+        match id {
+            b"fmt " => {
+                info.channels = u16_le(data, body + 2);
+                info.sample_rate = u32_le(data, body + 4);
+                byte_rate = u32_le(data, body + 8);
+            }
+            b"data" => {
+                // Streams sometimes leave the size unset, so don't read past the end.
+                let size = (size as usize).min(data.len() - body);
+                if let Some(byte_rate) = byte_rate.filter(|&rate| rate > 0) {
+                    info.duration = Some(size as f64 / f64::from(byte_rate));
+                }
+            }
+            _ => (),
+        }
+        // Chunks are padded to an even length.
+        offset = body.checked_add(size as usize + (size as usize & 1))?;
+    }
+    Some(info)
+}
+
+fn read_variable_length(data: &[u8], offset: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    for _ in 0..4 {
+        let byte = *data.get(*offset)?;
+        *offset += 1;
+        value = (value << 7) | u32::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn midi_info(data: &[u8]) -> Option<SoundInfo> {
+    let mut info = SoundInfo::new(SoundFormat::Midi);
+    let header_length = u32_be(data, 4)? as usize;
+    let division = u16_be(data, 12)?;
+
+    // (tick, microseconds per quarter note)
+    let mut tempos: Vec<(u64, u32)> = vec![];
+    let mut end_tick = 0u64;
+    let mut offset = 8 + header_length;
+    while let (Some(id), Some(size)) = (data.get(offset..offset + 4), u32_be(data, offset + 4)) {
+        let start = offset + 8;
+        let end = start.saturating_add(size as usize).min(data.len());
+        offset = start.saturating_add(size as usize);
+        if id != b"MTrk" {
+            continue;
+        }
+
+        let mut position = start;
+        let mut tick = 0u64;
+        let mut running_status = 0u8;
+        while position < end {
+            tick += u64::from(read_variable_length(data, &mut position)?);
+            let mut status = *data.get(position)?;
+            if status < 0x80 {
+                // Running status: reuse the previous status byte.
+                status = running_status;
+            } else {
+                position += 1;
+            }
+            match status {
+                0xFF => {
+                    let kind = *data.get(position)?;
+                    position += 1;
+                    let length = read_variable_length(data, &mut position)? as usize;
+                    if kind == 0x51 && length == 3 {
+                        let bytes = data.get(position..position + 3)?;
+                        let tempo = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+                        tempos.push((tick, tempo));
+                    }
+                    position += length;
+                    if kind == 0x2F {
+                        break;
+                    }
+                }
+                0xF0 | 0xF7 => {
+                    let length = read_variable_length(data, &mut position)? as usize;
+                    position += length;
+                }
+                0x80..=0xEF => {
+                    running_status = status;
+                    position += if status & 0xF0 == 0xC0 || status & 0xF0 == 0xD0 {
+                        1
+                    } else {
+                        2
+                    };
+                }
+                _ => return None,
+            }
+        }
+        end_tick = end_tick.max(tick);
+    }
+
+    let duration = if division & 0x8000 != 0 {
+        // SMPTE time: frames per second and ticks per frame.
+        let frames_per_second = -f64::from((division >> 8) as u8 as i8);
+        let ticks_per_frame = f64::from(division & 0xFF);
+        end_tick as f64 / (frames_per_second * ticks_per_frame)
+    } else {
+        let ticks_per_quarter = f64::from(division.max(1));
+        tempos.sort_by_key(|&(tick, _)| tick);
+        let mut seconds = 0.0;
+        let mut last_tick = 0u64;
+        let mut tempo = 500_000u32;
+        for &(tick, new_tempo) in tempos.iter().filter(|&&(tick, _)| tick < end_tick) {
+            seconds += (tick - last_tick) as f64 * f64::from(tempo) / ticks_per_quarter / 1e6;
+            last_tick = tick;
+            tempo = new_tempo;
+        }
+        seconds + (end_tick - last_tick) as f64 * f64::from(tempo) / ticks_per_quarter / 1e6
+    };
+    if duration.is_finite() {
+        info.duration = Some(duration);
+    }
+    Some(info)
+}
+
+struct Mp3Frame {
+    mpeg1: bool,
+    mono: bool,
+    bitrate: u32,
+    sample_rate: u32,
+    samples: u32,
+}
+
+fn mp3_frame(data: &[u8], offset: usize) -> Option<Mp3Frame> {
+    const BITRATES_V1: [[u32; 15]; 3] = [
+        [
+            0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+        ],
+        [
+            0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+        ],
+        [
+            0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+        ],
+    ];
+    const BITRATES_V2: [[u32; 15]; 2] = [
+        [
+            0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+        ],
+        [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    ];
+    const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+    let header = data.get(offset..offset + 4)?;
+    if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+    let version = (header[1] >> 3) & 3;
+    let layer = (header[1] >> 1) & 3;
+    let bitrate_index = (header[2] >> 4) as usize;
+    let sample_rate_index = ((header[2] >> 2) & 3) as usize;
+    if version == 1
+        || layer == 0
+        || bitrate_index == 0
+        || bitrate_index == 15
+        || sample_rate_index == 3
+    {
+        return None;
+    }
+
+    let mpeg1 = version == 3;
+    // Layer bits are 3 for layer I, 2 for layer II and 1 for layer III.
+    let layer = 4 - layer;
+    let bitrate = if mpeg1 {
+        BITRATES_V1[layer as usize - 1][bitrate_index]
+    } else {
+        BITRATES_V2[if layer == 1 { 0 } else { 1 }][bitrate_index]
+    };
+    let sample_rate = match version {
+        3 => SAMPLE_RATES[sample_rate_index],
+        2 => SAMPLE_RATES[sample_rate_index] / 2,
+        _ => SAMPLE_RATES[sample_rate_index] / 4,
+    };
+    let samples = match layer {
+        1 => 384,
+        3 if !mpeg1 => 576,
+        _ => 1152,
+    };
+    Some(Mp3Frame {
+        mpeg1,
+        mono: header[3] >> 6 == 3,
+        bitrate,
+        sample_rate,
+        samples,
+    })
+}
+
+fn mp3_info(data: &[u8]) -> Option<SoundInfo> {
+    let mut info = SoundInfo::new(SoundFormat::Mp3);
+    let mut offset = 0;
+    if data.starts_with(b"ID3") {
+        let size = data
+            .get(6..10)?
+            .iter()
+            .fold(0usize, |size, &b| (size << 7) | usize::from(b & 0x7F));
+        let footer = if data.get(5)? & 0x10 != 0 { 10 } else { 0 };
+        offset = 10 + size + footer;
+    }
+    // Skip any padding before the first frame.
+    while offset + 4 <= data.len() && mp3_frame(data, offset).is_none() {
+        offset += 1;
+    }
+    let frame = match mp3_frame(data, offset) {
+        Some(frame) => frame,
+        None => return Some(info),
+    };
+    info.sample_rate = Some(frame.sample_rate);
+    info.channels = Some(if frame.mono { 1 } else { 2 });
+
+    // Variable bitrate files usually start with a Xing or Info frame holding the frame count.
+    let side_info = match (frame.mpeg1, frame.mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17,
+    };
+    let xing = offset + 4 + side_info;
+    let frames = match data.get(xing..xing + 4) {
+        Some(b"Xing") | Some(b"Info") if u32_be(data, xing + 4)? & 1 != 0 => u32_be(data, xing + 8),
+        _ => None,
+    };
+    info.duration = Some(match frames {
+        Some(frames) => f64::from(frames) * f64::from(frame.samples) / f64::from(frame.sample_rate),
+        None => (data.len() - offset) as f64 * 8.0 / (f64::from(frame.bitrate) * 1000.0),
+    });
+    Some(info)
+}
+
+fn ogg_info(data: &[u8]) -> Option<SoundInfo> {
+    let mut info = SoundInfo::new(SoundFormat::Ogg);
+    let segments = *data.get(26)? as usize;
+    let packet = 27 + segments;
+
+    // Granule positions count samples, except for Opus where they're always at 48kHz.
+    let (granule_rate, pre_skip) = if data.get(packet..packet + 7) == Some(b"\x01vorbis") {
+        info.channels = data.get(packet + 11).map(|&c| u16::from(c));
+        info.sample_rate = u32_le(data, packet + 12);
+        (info.sample_rate?, 0)
+    } else if data.get(packet..packet + 8) == Some(b"OpusHead") {
+        info.channels = data.get(packet + 9).map(|&c| u16::from(c));
+        info.sample_rate = Some(48000);
+        (48000, u16_le(data, packet + 10)?)
+    } else {
+        return Some(info);
+    };
+
+    // The last page holds the final granule position.
+    let last_page = (0..data.len().saturating_sub(14))
+        .rev()
+        .find(|&i| &data[i..i + 4] == b"OggS")?;
+    let granule = i64::from_le_bytes(data.get(last_page + 6..last_page + 14)?.try_into().ok()?);
+    if granule > 0 && granule_rate > 0 {
+        info.duration =
+            Some((granule - i64::from(pre_skip)).max(0) as f64 / f64::from(granule_rate));
+    }
+    Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(data_size: u32, data: &[u8]) -> Vec<u8> {
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        bytes.extend_from_slice(b"fmt \x10\0\0\0");
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&22050u32.to_le_bytes());
+        bytes.extend_from_slice(&88200u32.to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        // An odd-sized chunk, followed by its padding byte.
+        bytes.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn ogg_page(granule: i64, packet: &[u8]) -> Vec<u8> {
+        let mut bytes = b"OggS\0\0".to_vec();
+        bytes.extend_from_slice(&granule.to_le_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.push(1);
+        bytes.push(packet.len() as u8);
+        bytes.extend_from_slice(packet);
+        bytes
+    }
+
+    fn info(format: SoundFormat, duration: f64, sample_rate: u32, channels: u16) -> SoundInfo {
+        SoundInfo {
+            format,
+            duration: Some(duration),
+            sample_rate: Some(sample_rate),
+            channels: Some(channels),
+        }
+    }
+
+    #[test]
+    fn wav_duration_comes_from_the_data_chunk() {
+        let data = wav(8820, &[0; 8820]);
+        assert_eq!(detect_format(&data), SoundFormat::Wav);
+        assert_eq!(sound_info(&data), info(SoundFormat::Wav, 0.1, 22050, 2));
+        // A size larger than the file is cut to what's there.
+        let truncated = wav(u32::MAX, &[0; 882]);
+        assert_eq!(sound_info(&truncated).duration, Some(0.01));
+        assert_eq!(detect_format(b"RIFF\0\0\0\0AVI "), SoundFormat::Unknown);
+    }
+
+    #[test]
+    fn midi_duration_follows_tempo_changes() {
+        let mut data = b"MThd\0\0\0\x06\0\0\0\x01\0\x60".to_vec();
+        let track: &[u8] = &[
+            // Tempo 500000, then a note on at tick 96 and, by running status, off at 224.
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, //
+            0x60, 0x90, 0x3C, 0x40, //
+            0x00, 0xFF, 0x51, 0x03, 0x03, 0xD0, 0x90, //
+            0x81, 0x00, 0x3C, 0x00, //
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        data.extend_from_slice(b"MTrk");
+        data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        data.extend_from_slice(track);
+        assert_eq!(detect_format(&data), SoundFormat::Midi);
+        // 96 ticks at half a second per quarter note, then 128 at a quarter of a second.
+        assert_eq!(sound_info(&data).duration, Some(0.5 + 128.0 / 96.0 * 0.25));
+    }
+
+    #[test]
+    fn mp3_frames_and_tags() {
+        // MPEG 1 layer III, 128 kbps, 44100 Hz, joint stereo.
+        let mut data = vec![0xFF, 0xFB, 0x90, 0x64];
+        data.resize(16000, 0);
+        assert_eq!(detect_format(&data), SoundFormat::Mp3);
+        assert_eq!(sound_info(&data), info(SoundFormat::Mp3, 1.0, 44100, 2));
+
+        // An ID3 tag of 10 bytes, then a mono frame with a Xing header counting 100 frames.
+        let mut tagged = b"ID3\x03\0\0\0\0\0\x0a".to_vec();
+        tagged.extend_from_slice(&[0; 10]);
+        tagged.extend_from_slice(&[0xFF, 0xFB, 0x90, 0xC4]);
+        tagged.extend_from_slice(&[0; 17]);
+        tagged.extend_from_slice(b"Xing\0\0\0\x01\0\0\0\x64");
+        let expected = 100.0 * 1152.0 / 44100.0;
+        assert_eq!(
+            sound_info(&tagged),
+            info(SoundFormat::Mp3, expected, 44100, 1)
+        );
+
+        // MPEG 2 has half the sample rate and half the samples per frame.
+        let mut mpeg2 = vec![0xFF, 0xF3, 0x90, 0xC4];
+        mpeg2.resize(1000, 0);
+        assert_eq!(sound_info(&mpeg2).sample_rate, Some(22050));
+        assert_eq!(
+            detect_format(&[0xFF, 0xE0, 0x00, 0x00]),
+            SoundFormat::Unknown
+        );
+    }
+
+    #[test]
+    fn ogg_duration_comes_from_the_last_granule() {
+        let mut header = b"\x01vorbis\0\0\0\0\x02".to_vec();
+        header.extend_from_slice(&44100u32.to_le_bytes());
+        let mut data = ogg_page(0, &header);
+        data.extend(ogg_page(88200, &[0; 4]));
+        assert_eq!(detect_format(&data), SoundFormat::Ogg);
+        assert_eq!(sound_info(&data), info(SoundFormat::Ogg, 2.0, 44100, 2));
+
+        let mut opus = b"OpusHead\x01\x01".to_vec();
+        opus.extend_from_slice(&312u16.to_le_bytes());
+        let mut data = ogg_page(0, &opus);
+        data.extend(ogg_page(48312, &[0; 4]));
+        assert_eq!(sound_info(&data), info(SoundFormat::Ogg, 1.0, 48000, 1));
+    }
+
+    #[test]
+    fn unknown_data_has_no_info() {
+        assert_eq!(detect_format(b""), SoundFormat::Unknown);
+        assert_eq!(
+            sound_info(b"not a sound"),
+            SoundInfo::new(SoundFormat::Unknown)
+        );
+        assert_eq!(sound_info(b"MThd"), SoundInfo::new(SoundFormat::Midi));
+    }
+}
@@ -0,0 +1,560 @@
+use crate::callgraph::CallGraph;
+use crate::code::{action_list, action_lists, code_bodies, ActionOwner, CodeLocation};
+use crate::event::Event;
+use crate::game::{Action, Game, ObjectId, ResourceId, ScriptId};
+use crate::gml::ast::{
+    var_names, walk_statements, AssignOp, Expr, ExprKind, Program, Stmt, StmtKind,
+};
+use crate::gml::{self, builtins, Span};
+use crate::inheritance::ancestors;
+use crate::library::ActionKind;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scope {
+    Global,
+    Object(ObjectId),
+    // Instance variables of an instance that can't be known without running the game, like the
+    // one running a timeline or a script that nothing calls.
+    UnknownInstance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessKind {
+    Read,
+    Write,
+    // `globalvar`, which makes the name refer to a global variable everywhere.
+    Declare,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Access {
+    pub location: CodeLocation,
+    // Where the variable is in the code at `location`.
+    pub span: Span,
+    pub kind: AccessKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub scope: Scope,
+    pub accesses: Vec<Access>,
+}
+
+impl Variable {
+    pub fn reads(&self) -> impl Iterator<Item = &Access> {
+        self.accesses.iter().filter(|a| a.kind == AccessKind::Read)
+    }
+
+    pub fn writes(&self) -> impl Iterator<Item = &Access> {
+        self.accesses.iter().filter(|a| a.kind == AccessKind::Write)
+    }
+}
+
+pub struct VariableInventory {
+    // Sorted by scope, then name.
+    pub variables: Vec<Variable>,
+}
+
+// Identifiers that never name a variable: resources, scripts and constants.
+struct Names<'a> {
+    objects: HashMap<&'a str, ObjectId>,
+    reserved: HashSet<&'a str>,
+    // Declared with `globalvar` somewhere in the game.
+    globals: HashSet<String>,
+}
+
+struct Walker<'a, 'b> {
+    names: &'b Names<'a>,
+    location: CodeLocation,
+    // Declared with `var` in this piece of code.
+    locals: HashSet<&'b str>,
+    self_scope: Scope,
+    other_scope: Scope,
+    // The code was already walked for another instance that runs it, so an access to a global or
+    // a named object's variable may be recorded already.
+    again: bool,
+    accesses: &'b mut BTreeMap<(Scope, String), Vec<Access>>,
+}
+
+impl Walker<'_, '_> {
+    fn record(&mut self, scope: Scope, name: &str, span: Span, kind: AccessKind) {
+        if builtins::is_variable(name) {
+            return;
+        }
+        let access = Access {
+            location: self.location,
+            span,
+            kind,
+        };
+        let accesses = self.accesses.entry((scope, name.to_string())).or_default();
+        if !(self.again && accesses.contains(&access)) {
+            accesses.push(access);
+        }
+    }
+
+    fn identifier(&mut self, name: &str, span: Span, kind: AccessKind) {
+        if self.locals.contains(name) || self.names.reserved.contains(name) {
+            return;
+        }
+        let scope = if self.names.globals.contains(name) {
+            Scope::Global
+        } else {
+            self.self_scope
+        };
+        self.record(scope, name, span, kind);
+    }
+
+    // Which instances `expr` refers to when it's used as `expr.field` or `with (expr)`.
+    fn object_scope(&self, expr: &Expr) -> Scope {
+        let name = match &expr.kind {
+            ExprKind::Identifier(name) => name.as_str(),
+            ExprKind::Parenthesized(inner) => return self.object_scope(inner),
+            _ => return Scope::UnknownInstance,
+        };
+        match name {
+            "global" => Scope::Global,
+            "self" => self.self_scope,
+            "other" => self.other_scope,
+            name => match self.names.objects.get(name) {
+                Some(&id) => Scope::Object(id),
+                None => Scope::UnknownInstance,
+            },
+        }
+    }
+
+    fn access(&mut self, expr: &Expr, kind: AccessKind) {
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.identifier(name, expr.span, kind),
+            ExprKind::Field(object, field) => {
+                self.read(object);
+                let scope = self.object_scope(object);
+                self.record(scope, &field.name, field.span, kind);
+            }
+            ExprKind::Index(target, indices) => {
+                for index in indices {
+                    self.read(index);
+                }
+                self.access(target, kind);
+            }
+            _ => self.read(expr),
+        }
+    }
+
+    fn read(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(_) | ExprKind::Field(..) | ExprKind::Index(..) => {
+                self.access(expr, AccessKind::Read)
+            }
+            ExprKind::Call(_, args) => {
+                for arg in args {
+                    self.read(arg);
+                }
+            }
+            ExprKind::Unary(_, operand) | ExprKind::Parenthesized(operand) => self.read(operand),
+            ExprKind::Binary(_, left, right) => {
+                self.read(left);
+                self.read(right);
+            }
+            ExprKind::Real(_) | ExprKind::String(_) | ExprKind::Error => (),
+        }
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        match &statement.kind {
+            StmtKind::Block(body) => self.statements(body),
+            StmtKind::GlobalVar(names) => {
+                for name in names {
+                    self.record(Scope::Global, &name.name, name.span, AccessKind::Declare);
+                }
+            }
+            StmtKind::Assign(target, op, value) => {
+                self.read(value);
+                if *op != AssignOp::Assign {
+                    self.access(target, AccessKind::Read);
+                }
+                self.access(target, AccessKind::Write);
+            }
+            StmtKind::Expr(e) | StmtKind::Case(e) | StmtKind::Return(Some(e)) => self.read(e),
+            StmtKind::If(condition, then, otherwise) => {
+                self.read(condition);
+                self.statement(then);
+                if let Some(otherwise) = otherwise {
+                    self.statement(otherwise);
+                }
+            }
+            StmtKind::While(e, body) | StmtKind::Repeat(e, body) => {
+                self.read(e);
+                self.statement(body);
+            }
+            StmtKind::DoUntil(body, e) => {
+                self.statement(body);
+                self.read(e);
+            }
+            StmtKind::For(init, condition, step, body) => {
+                self.statement(init);
+                self.read(condition);
+                self.statement(step);
+                self.statement(body);
+            }
+            StmtKind::With(e, body) => {
+                self.read(e);
+                let scope = self.object_scope(e);
+                let (self_scope, other_scope) = (self.self_scope, self.other_scope);
+                self.other_scope = self_scope;
+                self.self_scope = scope;
+                self.statement(body);
+                self.self_scope = self_scope;
+                self.other_scope = other_scope;
+            }
+            StmtKind::Switch(e, body) => {
+                self.read(e);
+                self.statements(body);
+            }
+            StmtKind::Var(_)
+            | StmtKind::Default
+            | StmtKind::Break
+            | StmtKind::Continue
+            | StmtKind::Exit
+            | StmtKind::Return(None)
+            | StmtKind::Empty
+            | StmtKind::Error => (),
+        }
+    }
+}
+
+// The instance code at `location` runs in, and the one `other` refers to.
+fn scopes(game: &Game, location: CodeLocation) -> (Scope, Scope) {
+    let (owner, action) = match location {
+        CodeLocation::Action { owner, action, .. } => (owner, action),
+        CodeLocation::InstanceCreation(room, instance) => {
+            let object = game
+                .room(room)
+                .and_then(|r| r.instances.iter().find(|i| i.id == instance))
+                .and_then(|i| i.object);
+            let scope = object.map_or(Scope::UnknownInstance, Scope::Object);
+            return (scope, Scope::UnknownInstance);
+        }
+        _ => return (Scope::UnknownInstance, Scope::UnknownInstance),
+    };
+    let (self_scope, other_scope) = match owner {
+        ActionOwner::Object(id, Event::Collision(other)) => (
+            Scope::Object(id),
+            other.map_or(Scope::UnknownInstance, Scope::Object),
+        ),
+        ActionOwner::Object(id, _) => (Scope::Object(id), Scope::UnknownInstance),
+        ActionOwner::Timeline(..) => (Scope::UnknownInstance, Scope::UnknownInstance),
+    };
+    // "Applies to" on the action changes which instance runs it.
+    let target = action_list(game, owner)
+        .and_then(|actions| actions.get(action))
+        .filter(|a| a.has_target)
+        .map_or(-1, |a| a.target);
+    match target {
+        -1 => (self_scope, other_scope),
+        -2 => (other_scope, self_scope),
+        id => (
+            ObjectId::from_raw(id).map_or(Scope::UnknownInstance, Scope::Object),
+            self_scope,
+        ),
+    }
+}
+
+// Works out which instances a script runs in, following it back to the code that calls it.
+struct ScopeFinder<'a> {
+    game: &'a Game,
+    calls: CallGraph,
+    scripts_visiting: Vec<ScriptId>,
+}
+
+impl ScopeFinder<'_> {
+    fn caller_scopes(&mut self, location: CodeLocation) -> BTreeSet<(Scope, Scope)> {
+        let id = match location {
+            CodeLocation::Script(id) => id,
+            _ => return std::iter::once(scopes(self.game, location)).collect(),
+        };
+        if self.scripts_visiting.contains(&id) {
+            return BTreeSet::new();
+        }
+        self.scripts_visiting.push(id);
+        let callers: Vec<_> = self.calls.callers(id).map(|c| c.location).collect();
+        let scopes = callers
+            .into_iter()
+            .flat_map(|caller| self.caller_scopes(caller))
+            .collect();
+        self.scripts_visiting.pop();
+        scopes
+    }
+
+    // The instance and `other` for each way the code at `location` can run.
+    fn scopes(&mut self, location: CodeLocation) -> BTreeSet<(Scope, Scope)> {
+        let scopes = self.caller_scopes(location);
+        if scopes.is_empty() {
+            std::iter::once((Scope::UnknownInstance, Scope::UnknownInstance)).collect()
+        } else {
+            scopes
+        }
+    }
+}
+
+fn is_variable_action(action: Option<&Action>) -> bool {
+    action.is_some_and(|a| a.kind() == ActionKind::Variable)
+}
+
+impl VariableInventory {
+    pub fn new(game: &Game) -> Self {
+        let mut reserved: HashSet<&str> = HashSet::new();
+        reserved.extend(builtins::CONSTANTS.iter().map(|&(name, _)| name));
+        reserved.extend(game.constants.iter().map(|c| c.name.as_str()));
+        reserved.extend(game.triggers.iter().map(|t| t.constant_name.as_str()));
+        reserved.extend(
+            game.extensions
+                .iter()
+                .flat_map(|e| &e.files)
+                .flat_map(|f| &f.constants)
+                .map(|c| c.name.as_str()),
+        );
+        reserved.extend(game.sprites.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.sounds.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.backgrounds.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.paths.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.scripts.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.fonts.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.timelines.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.objects.iter().map(|r| r.name.as_str()));
+        reserved.extend(game.rooms.iter().map(|r| r.name.as_str()));
+        let mut names = Names {
+            objects: game
+                .objects
+                .iter()
+                .map(|o| (o.name.as_str(), o.id))
+                .collect(),
+            reserved,
+            globals: HashSet::new(),
+        };
+
+        // The variable name of a "Set variable" action is handled with the action below.
+        let programs: Vec<(CodeLocation, Program)> = code_bodies(game)
+            .into_iter()
+            .filter(|body| match body.location {
+                CodeLocation::Action {
+                    owner,
+                    action,
+                    argument: 0,
+                } => !is_variable_action(action_list(game, owner).and_then(|a| a.get(action))),
+                _ => true,
+            })
+            .map(|body| (body.location, gml::parse(body.code).0))
+            .collect();
+
+        for (_, program) in &programs {
+            walk_statements(&program.statements, &mut |statement| {
+                if let StmtKind::GlobalVar(declared) = &statement.kind {
+                    names
+                        .globals
+                        .extend(declared.iter().map(|n| n.name.clone()));
+                }
+            });
+        }
+
+        let mut finder = ScopeFinder {
+            game,
+            calls: CallGraph::new(game),
+            scripts_visiting: vec![],
+        };
+        let mut accesses = BTreeMap::new();
+        for (location, program) in &programs {
+            let locals = var_names(&program.statements);
+            let scopes = finder.scopes(*location);
+            for (i, (self_scope, other_scope)) in scopes.into_iter().enumerate() {
+                let mut walker = Walker {
+                    names: &names,
+                    location: *location,
+                    locals: locals.clone(),
+                    self_scope,
+                    other_scope,
+                    again: i > 0,
+                    accesses: &mut accesses,
+                };
+                walker.statements(&program.statements);
+            }
+        }
+
+        for (owner, actions) in action_lists(game) {
+            for (i, action) in actions.iter().enumerate() {
+                let variable = match action.arguments.first() {
+                    Some(variable) if is_variable_action(Some(action)) => variable,
+                    _ => continue,
+                };
+                let location = CodeLocation::Action {
+                    owner,
+                    action: i,
+                    argument: 0,
+                };
+                let (self_scope, other_scope) = scopes(game, location);
+                let mut walker = Walker {
+                    names: &names,
+                    location,
+                    locals: HashSet::new(),
+                    self_scope,
+                    other_scope,
+                    again: false,
+                    accesses: &mut accesses,
+                };
+                let (program, _) = gml::parse(variable);
+                if let Some(StmtKind::Expr(target)) = program.statements.first().map(|s| &s.kind) {
+                    if action.relative {
+                        walker.access(target, AccessKind::Read);
+                    }
+                    walker.access(target, AccessKind::Write);
+                }
+            }
+        }
+
+        let variables = accesses
+            .into_iter()
+            .map(|((scope, name), accesses)| Variable {
+                name,
+                scope,
+                accesses,
+            })
+            .collect();
+        VariableInventory { variables }
+    }
+
+    pub fn globals(&self) -> impl Iterator<Item = &Variable> {
+        self.variables.iter().filter(|v| v.scope == Scope::Global)
+    }
+
+    // The instance variables an object uses, including the ones used by its ancestors' events.
+    pub fn object_variables(&self, game: &Game, id: ObjectId) -> Vec<&Variable> {
+        let mut objects = vec![id];
+        objects.extend(ancestors(game, id).iter().map(|o| o.id));
+        self.variables
+            .iter()
+            .filter(|v| match v.scope {
+                Scope::Object(owner) => objects.contains(&owner),
+                _ => false,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Object, ObjectEvent, Script};
+
+    fn code(code: &str) -> Action {
+        Action {
+            library_id: 1,
+            action_id: 603,
+            action_kind: 7,
+            parameters_used: 1,
+            parameters: vec![1],
+            target: -1,
+            arguments: vec![code.to_string()],
+            ..Action::default()
+        }
+    }
+
+    fn object(id: u32, name: &str, parent: Option<ObjectId>, create: &str) -> Object {
+        Object {
+            id: ObjectId(id),
+            name: name.to_string(),
+            parent,
+            events: vec![ObjectEvent {
+                actions: vec![code(create)],
+                ..ObjectEvent::default()
+            }],
+            ..Object::default()
+        }
+    }
+
+    fn game() -> Game {
+        let mut game = Game::default();
+        game.objects
+            .push(object(0, "obj_player", None, "scr_init(); ammo = 3"));
+        game.objects.push(object(1, "obj_enemy", None, "scr_hit()"));
+        game.objects
+            .push(object(2, "obj_boss", Some(ObjectId(1)), "scr_init()"));
+        for (id, name, script) in [
+            (0, "scr_init", "hp = 10; global.level = 0"),
+            (1, "scr_hit", "scr_damage()"),
+            (2, "scr_damage", "armor -= 1; scr_hit()"),
+            (3, "scr_unused", "var tmp; tmp = 1; unused = tmp"),
+        ] {
+            game.scripts.push(Script {
+                id: ScriptId(id),
+                name: name.to_string(),
+                script: script.to_string(),
+            });
+        }
+        game
+    }
+
+    fn scopes_of(inventory: &VariableInventory, name: &str) -> Vec<Scope> {
+        inventory
+            .variables
+            .iter()
+            .filter(|v| v.name == name)
+            .map(|v| v.scope)
+            .collect()
+    }
+
+    fn names(variables: &[&Variable]) -> Vec<String> {
+        variables.iter().map(|v| v.name.clone()).collect()
+    }
+
+    #[test]
+    fn script_variables_belong_to_the_calling_objects() {
+        let inventory = VariableInventory::new(&game());
+        assert_eq!(
+            scopes_of(&inventory, "hp"),
+            vec![Scope::Object(ObjectId(0)), Scope::Object(ObjectId(2))]
+        );
+        assert_eq!(
+            scopes_of(&inventory, "unused"),
+            vec![Scope::UnknownInstance]
+        );
+        assert_eq!(scopes_of(&inventory, "tmp"), vec![]);
+    }
+
+    #[test]
+    fn scripts_called_from_scripts_follow_the_chain() {
+        let inventory = VariableInventory::new(&game());
+        assert_eq!(
+            scopes_of(&inventory, "armor"),
+            vec![Scope::Object(ObjectId(1))]
+        );
+    }
+
+    #[test]
+    fn globals_are_recorded_once() {
+        let inventory = VariableInventory::new(&game());
+        let globals: Vec<_> = inventory.globals().collect();
+        assert_eq!(names(&globals), vec!["level"]);
+        assert_eq!(globals[0].writes().count(), 1);
+    }
+
+    #[test]
+    fn objects_include_inherited_variables() {
+        let game = game();
+        let inventory = VariableInventory::new(&game);
+        assert_eq!(
+            names(&inventory.object_variables(&game, ObjectId(0))),
+            vec!["ammo", "hp"]
+        );
+        assert_eq!(
+            names(&inventory.object_variables(&game, ObjectId(2))),
+            vec!["armor", "hp"]
+        );
+    }
+}